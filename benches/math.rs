@@ -0,0 +1,180 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Compares the `Matrix` kernels against plain scalar versions of the same algorithms. Run with
+//! `cargo bench --bench math`.
+
+extern crate anima_engine;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use anima_engine::math::{Matrix, Vector};
+
+fn scalar_mul_vec(m: &[f32; 16], v: [f32; 4]) -> [f32; 4] {
+    [
+        m[0] * v[0] + m[4] * v[1] + m[8]  * v[2] + m[12] * v[3],
+        m[1] * v[0] + m[5] * v[1] + m[9]  * v[2] + m[13] * v[3],
+        m[2] * v[0] + m[6] * v[1] + m[10] * v[2] + m[14] * v[3],
+        m[3] * v[0] + m[7] * v[1] + m[11] * v[2] + m[15] * v[3]
+    ]
+}
+
+fn scalar_mul(l: &[f32; 16], r: &[f32; 16]) -> [f32; 16] {
+    let mut result = [0.0; 16];
+
+    for i in 0..4 {
+        let column = scalar_mul_vec(l, [r[i * 4], r[i * 4 + 1], r[i * 4 + 2], r[i * 4 + 3]]);
+
+        result[i * 4..i * 4 + 4].copy_from_slice(&column);
+    }
+
+    result
+}
+
+fn scalar_inv(m: &[f32; 16]) -> Option<[f32; 16]> {
+    let s = [
+        m[0] * m[5]  - m[1] * m[4],
+        m[0] * m[9]  - m[1] * m[8],
+        m[0] * m[13] - m[1] * m[12],
+        m[4] * m[9]  - m[5] * m[8],
+        m[4] * m[13] - m[5] * m[12],
+        m[8] * m[13] - m[9] * m[12]
+    ];
+
+    let c = [
+        m[2]  * m[7]  - m[3]  * m[6],
+        m[2]  * m[11] - m[3]  * m[10],
+        m[2]  * m[15] - m[3]  * m[14],
+        m[6]  * m[11] - m[7]  * m[10],
+        m[6]  * m[15] - m[7]  * m[14],
+        m[10] * m[15] - m[11] * m[14]
+    ];
+
+    let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+
+    if det == 0.0 { return None; }
+
+    let inv_det = 1.0 / det;
+
+    Some([
+        ( m[5] * c[5] - m[9]  * c[4] + m[13] * c[3]) * inv_det,
+        (-m[1] * c[5] + m[9]  * c[2] - m[13] * c[1]) * inv_det,
+        ( m[1] * c[4] - m[5]  * c[2] + m[13] * c[0]) * inv_det,
+        (-m[1] * c[3] + m[5]  * c[1] - m[9]  * c[0]) * inv_det,
+        (-m[4] * c[5] + m[8]  * c[4] - m[12] * c[3]) * inv_det,
+        ( m[0] * c[5] - m[8]  * c[2] + m[12] * c[1]) * inv_det,
+        (-m[0] * c[4] + m[4]  * c[2] - m[12] * c[0]) * inv_det,
+        ( m[0] * c[3] - m[4]  * c[1] + m[8]  * c[0]) * inv_det,
+        ( m[7] * s[5] - m[11] * s[4] + m[15] * s[3]) * inv_det,
+        (-m[3] * s[5] + m[11] * s[2] - m[15] * s[1]) * inv_det,
+        ( m[3] * s[4] - m[7]  * s[2] + m[15] * s[0]) * inv_det,
+        (-m[3] * s[3] + m[7]  * s[1] - m[11] * s[0]) * inv_det,
+        (-m[6] * s[5] + m[10] * s[4] - m[14] * s[3]) * inv_det,
+        ( m[2] * s[5] - m[10] * s[2] + m[14] * s[1]) * inv_det,
+        (-m[2] * s[4] + m[6]  * s[2] - m[14] * s[0]) * inv_det,
+        ( m[2] * s[3] - m[6]  * s[1] + m[10] * s[0]) * inv_det
+    ])
+}
+
+fn scalar_trans_points(m: &[f32; 16], points: &mut [Vector]) {
+    for point in points.iter_mut() {
+        let r = scalar_mul_vec(m, [point.x, point.y, point.z, 1.0]);
+
+        *point = Vector::new(r[0] / r[3], r[1] / r[3], r[2] / r[3]);
+    }
+}
+
+fn scalar_trans_dirs(m: &[f32; 16], dirs: &mut [Vector]) {
+    for dir in dirs.iter_mut() {
+        let r = scalar_mul_vec(m, [dir.x, dir.y, dir.z, 0.0]);
+
+        *dir = Vector::new(r[0], r[1], r[2]);
+    }
+}
+
+// Runs `f` `iterations` times and returns the average time in nanoseconds.
+fn time<F: FnMut()>(iterations: u32, mut f: F) -> f64 {
+    for _ in 0..iterations / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        f();
+    }
+
+    let elapsed = start.elapsed();
+
+    (elapsed.as_secs() as f64 * 1e9 + elapsed.subsec_nanos() as f64) / iterations as f64
+}
+
+fn report(name: &str, scalar: f64, simd: f64) {
+    println!("{:<28} scalar {:>12.1} ns   simd {:>12.1} ns   {:>5.2}x", name, scalar, simd,
+             scalar / simd);
+}
+
+fn main() {
+    let m = Matrix::ident().scale(Vector::new(1.0, 2.0, 3.0)).trans(Vector::one());
+    let n = Matrix::new([
+        4.0, 0.5, 0.25, 0.0,
+        0.5, 3.0, 0.75, 0.0,
+        0.25, 0.75, 2.0, 0.0,
+        1.0, -2.0, 3.0, 1.0
+    ]);
+
+    report(
+        "Matrix * Matrix",
+        time(1_000_000, || { black_box(scalar_mul(&black_box(m).array, &black_box(n).array)); }),
+        time(1_000_000, || { black_box(black_box(m) * black_box(n)); })
+    );
+
+    report(
+        "Matrix::inv",
+        time(1_000_000, || { black_box(scalar_inv(&black_box(n).array)); }),
+        time(1_000_000, || { black_box(black_box(n).try_inv()); })
+    );
+
+    // A batch that fits in cache and one large enough to be bound by memory bandwidth.
+    for &(count, iterations) in [(1_000, 10_000), (100_000, 100)].iter() {
+        let points: Vec<Vector> = (0..count).map(|i| {
+            let i = i as f32;
+
+            Vector::new(i.sin(), i.cos(), i * 0.001)
+        }).collect();
+        let mut buffer = points.clone();
+
+        report(
+            &format!("Matrix::trans_points {}", count),
+            time(iterations, || {
+                buffer.copy_from_slice(&points);
+                scalar_trans_points(&black_box(n).array, &mut buffer);
+                black_box(&buffer);
+            }),
+            time(iterations, || {
+                buffer.copy_from_slice(&points);
+                black_box(n).trans_points(&mut buffer);
+                black_box(&buffer);
+            })
+        );
+
+        report(
+            &format!("Matrix::trans_dirs {}", count),
+            time(iterations, || {
+                buffer.copy_from_slice(&points);
+                scalar_trans_dirs(&black_box(n).array, &mut buffer);
+                black_box(&buffer);
+            }),
+            time(iterations, || {
+                buffer.copy_from_slice(&points);
+                black_box(n).trans_dirs(&mut buffer);
+                black_box(&buffer);
+            })
+        );
+    }
+}
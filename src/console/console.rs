@@ -0,0 +1,410 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Display;
+use std::io::{self, BufReader, Read, Write};
+use std::marker::PhantomData;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use mrusty::*;
+
+use super::pretty::pretty;
+use super::session::{self, Command, Line, Stream};
+use super::super::scripting::{self, ScriptException, Watchdog};
+
+/// A `struct` evaluating mruby code typed in a live console against a running mruby state.
+///
+/// Input comes from sessions started with `listen_stdin`, `listen_read_line`, `listen_tcp` or
+/// `listen_unix`, each running on its own thread. Sessions only read and buffer lines; complete
+/// statements are evaluated on the thread calling `poll`, between frames, so they can safely use
+/// the same `MrubyType` as the game. Statements spanning multiple lines are detected with
+/// `is_complete`; an empty line forces incomplete input to be evaluated.
+///
+/// Every statement is kept in the history. `!` lists it, `!!` runs the last statement again and
+/// `!n` runs the `n`th one. Socket sessions have no line editing; `rlwrap nc localhost <port>`
+/// provides it.
+///
+/// Statements are compiled on their own, so backtraces point at `console` without changing the
+/// file name of the state they run in. A `Watchdog` set with `budget` keeps a statement like
+/// `loop { [1] }` from hanging the game.
+///
+/// # Examples
+///
+/// ```no-run
+/// let mut console = Console::new();
+///
+/// console.listen_stdin();
+///
+/// loop {
+///     // Run a frame.
+///
+///     console.poll(&mruby);
+/// }
+/// ```
+pub struct Console {
+    name: String,
+    sender: Sender<Command>,
+    commands: Receiver<Command>,
+    history: Vec<String>,
+    watchdog: Option<(Rc<Watchdog>, Duration)>
+}
+
+impl Console {
+    /// Creates a `Console` without any session. Its prompt starts with `anima> `.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// let console = Console::new();
+    ///
+    /// assert!(console.history().is_empty());
+    /// ```
+    pub fn new() -> Console {
+        let (sender, commands) = mpsc::channel();
+
+        Console {
+            name: "anima".to_owned(),
+            sender: sender,
+            commands: commands,
+            history: vec![],
+            watchdog: None
+        }
+    }
+
+    /// Renames a `Console`. The prompt of sessions started afterwards will start with `{name}> `.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// let mut console = Console::new();
+    ///
+    /// console.rename("game");
+    /// ```
+    pub fn rename(&mut self, name: &str) {
+        self.name = name.to_owned();
+    }
+
+    /// Runs every statement under `watchdog`, raising a `ScriptTimeout` in the ones running longer
+    /// than `budget`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// # use anima_engine::console::Console;
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Watchdog;
+    /// let mruby = Mruby::new();
+    /// let mut console = Console::new();
+    ///
+    /// console.budget(Rc::new(Watchdog::new(&mruby)), Duration::new(0, 10_000_000));
+    ///
+    /// assert!(console.eval(&mruby, "loop { [1] }").starts_with("ScriptTimeout"));
+    /// ```
+    pub fn budget(&mut self, watchdog: Rc<Watchdog>, budget: Duration) {
+        self.watchdog = Some((watchdog, budget));
+    }
+
+    /// Starts a session reading from standard input and writing to standard output.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let console = Console::new();
+    ///
+    /// console.listen_stdin();
+    /// ```
+    pub fn listen_stdin(&self) {
+        self.spawn(|| {
+            Stream {
+                reader: BufReader::new(io::stdin()),
+                writer: io::stdout()
+            }
+        });
+    }
+
+    /// Starts a session on top of a `ReadLine`, e.g. mrusty's `GnuReadLine`, which provides line
+    /// editing and history navigation.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let console = Console::new();
+    ///
+    /// console.listen_read_line(GnuReadLine);
+    /// ```
+    pub fn listen_read_line<E, R>(&self, read_line: R)
+        where E: Display, R: ReadLine<E> + Send + 'static {
+        self.spawn(move || {
+            Line {
+                read_line: read_line,
+                error: PhantomData
+            }
+        });
+    }
+
+    /// Listens for TCP connections on `address`, starting a session for each one. Returns the
+    /// address actually bound, which is useful when binding port `0`.
+    ///
+    /// Only bind to local addresses; anyone who can connect can run arbitrary code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// let console = Console::new();
+    ///
+    /// let address = console.listen_tcp("127.0.0.1:0").unwrap();
+    ///
+    /// assert!(address.port() > 0);
+    /// ```
+    pub fn listen_tcp<A: ToSocketAddrs>(&self, address: A) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+
+        let name = self.name.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Ok(writer) = stream.try_clone() {
+                    connect(stream, writer, &name, &sender);
+                }
+            }
+        });
+
+        Ok(address)
+    }
+
+    /// Listens for Unix socket connections on `path`, starting a session for each one.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let console = Console::new();
+    ///
+    /// console.listen_unix("/tmp/anima.sock").unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn listen_unix<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let listener = UnixListener::bind(path)?;
+
+        let name = self.name.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Ok(writer) = stream.try_clone() {
+                    connect(stream, writer, &name, &sender);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Returns the statements evaluated so far, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Evaluates `source` at the top level of `mruby` and returns the console output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// # use anima_engine::mrusty::*;
+    /// let mruby = Mruby::new();
+    /// let mut console = Console::new();
+    ///
+    /// assert_eq!(console.eval(&mruby, "1 + 2"), "=> 3");
+    /// assert_eq!(console.eval(&mruby, "!!"), "=> 3");
+    /// ```
+    pub fn eval(&mut self, mruby: &MrubyType, source: &str) -> String {
+        let watchdog = self.watchdog.clone();
+
+        self.run(source, |source| execute(mruby, &watchdog, source))
+    }
+
+    /// Evaluates `source` in the context of `target`, whose instance variables are directly
+    /// accessible, and returns the console output. `target` is also kept in `$console_target`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// # use anima_engine::mrusty::*;
+    /// let mruby = Mruby::new();
+    /// let mut console = Console::new();
+    ///
+    /// let target = mruby.run("class Game; end; Game.new").unwrap();
+    ///
+    /// target.set_var("@score", mruby.fixnum(3));
+    ///
+    /// assert_eq!(console.eval_in(&mruby, &target, "@score * 2"), "=> 6");
+    /// ```
+    pub fn eval_in(&mut self, mruby: &MrubyType, target: &Value, source: &str) -> String {
+        let watchdog = self.watchdog.clone();
+
+        self.run(source, |source| {
+            let set_target = mruby.run("proc { |target| $console_target = target }")
+                                  .map_err(|error| output(&error))?;
+
+            set_target.call("call", vec![target.clone()]).map_err(|error| output(&error))?;
+
+            // Starting the block on the first line keeps line numbers in errors right.
+            execute(mruby, &watchdog, &format!("$console_target.instance_eval do {}\nend", source))
+        })
+    }
+
+    /// Evaluates all statements received from sessions at the top level of `mruby`. Returns the
+    /// number of evaluated statements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// # use anima_engine::mrusty::*;
+    /// let mruby = Mruby::new();
+    /// let mut console = Console::new();
+    ///
+    /// assert_eq!(console.poll(&mruby), 0);
+    /// ```
+    pub fn poll(&mut self, mruby: &MrubyType) -> usize {
+        self.receive(|console, source| console.eval(mruby, source))
+    }
+
+    /// Evaluates all statements received from sessions in the context of `target`. Returns the
+    /// number of evaluated statements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::console::Console;
+    /// # use anima_engine::mrusty::*;
+    /// let mruby = Mruby::new();
+    /// let mut console = Console::new();
+    ///
+    /// let target = mruby.run("Object.new").unwrap();
+    ///
+    /// assert_eq!(console.poll_in(&mruby, &target), 0);
+    /// ```
+    pub fn poll_in(&mut self, mruby: &MrubyType, target: &Value) -> usize {
+        self.receive(|console, source| console.eval_in(mruby, target, source))
+    }
+
+    fn spawn<T, F>(&self, terminal: F)
+        where T: session::Terminal, F: FnOnce() -> T + Send + 'static {
+        let name = self.name.clone();
+        let sender = self.sender.clone();
+
+        thread::spawn(move || session::run(terminal(), &name, sender));
+    }
+
+    fn receive<F>(&mut self, mut eval: F) -> usize where F: FnMut(&mut Console, &str) -> String {
+        let mut count = 0;
+
+        while let Ok(command) = self.commands.try_recv() {
+            let output = eval(self, &command.source);
+
+            // The session might have been closed in the meantime.
+            let _ = command.reply.send(output);
+
+            count += 1;
+        }
+
+        count
+    }
+
+    fn run<F>(&mut self, source: &str, eval: F) -> String
+        where F: FnOnce(&str) -> Result<Value, String> {
+        let source = source.trim();
+
+        let source = if source == "!" {
+            let lines: Vec<String> = self.history.iter().enumerate().map(|(i, source)| {
+                format!("{:>4}  {}", i + 1, source.replace("\n", "\n      "))
+            }).collect();
+
+            return lines.join("\n");
+        } else if source == "!!" {
+            match self.history.last() {
+                Some(source) => source.clone(),
+                None         => return "No history.".to_owned()
+            }
+        } else if let Some(index) = source.strip_prefix('!') {
+            let entry = index.parse::<usize>().ok()
+                             .and_then(|i| i.checked_sub(1))
+                             .and_then(|i| self.history.get(i));
+
+            match entry {
+                Some(source) => source.clone(),
+                None         => return format!("No history entry {}.", index)
+            }
+        } else {
+            source.to_owned()
+        };
+
+        self.history.push(source.clone());
+
+        match eval(&source) {
+            Ok(value)   => format!("=> {}", pretty(&value)),
+            Err(output) => output
+        }
+    }
+}
+
+fn execute(mruby: &MrubyType, watchdog: &Option<(Rc<Watchdog>, Duration)>,
+           source: &str) -> Result<Value, String> {
+    let code = scripting::compile("console", source).map_err(|error| format!("{}", error))?;
+
+    let result = match *watchdog {
+        Some((ref watchdog, budget)) => watchdog.runb(&code, budget),
+        None                         => mruby.runb(&code)
+    };
+
+    result.map_err(|error| output(&error))
+}
+
+fn output(error: &MrubyError) -> String {
+    match *error {
+        MrubyError::Runtime(ref error) => format!("{}", ScriptException::parse(error)),
+        ref error                      => format!("{}", error)
+    }
+}
+
+fn connect<S>(reader: S, writer: S, name: &str, sender: &Sender<Command>)
+    where S: Read + Write + Send + 'static {
+    let name = name.to_owned();
+    let sender = sender.clone();
+
+    thread::spawn(move || {
+        let stream = Stream {
+            reader: BufReader::new(reader),
+            writer: writer
+        };
+
+        session::run(stream, &name, sender);
+    });
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console::new()
+    }
+}
@@ -0,0 +1,146 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A `fn` that guesses whether `source` is a complete mruby statement or whether the console
+/// should wait for more lines.
+///
+/// `source` is incomplete when it has unclosed blocks (`def`, `class`, `do`, `if`, ...), brackets
+/// or strings, or when its last line ends in a binary operator, a comma, a dot or a `\`.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::console;
+/// assert!(console::is_complete("x = 1 if y"));
+/// assert!(!console::is_complete("def hi"));
+/// assert!(console::is_complete("def hi\n  'hi'\nend"));
+/// assert!(!console::is_complete("[1,\n 2,"));
+/// ```
+pub fn is_complete(source: &str) -> bool {
+    let mut chars = source.chars().peekable();
+
+    let mut depth = 0i32;
+    let mut expression = true;
+    let mut looping = false;
+    let mut last = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+
+                continue;
+            },
+            '"' | '\'' | '`' => {
+                if !skip_string(&mut chars, c) {
+                    return false;
+                }
+
+                expression = false;
+            },
+            '(' | '[' | '{' => {
+                depth += 1;
+                expression = true;
+            },
+            ')' | ']' | '}' => {
+                depth -= 1;
+                expression = false;
+            },
+            '\n' => {
+                expression = true;
+                looping = false;
+
+                continue;
+            },
+            ';' => {
+                expression = true;
+                looping = false;
+            },
+            ' ' | '\t' | '\r' => continue,
+            c if c.is_alphanumeric() || c == '_' || c == '@' || c == '$' => {
+                let mut word = c.to_string();
+
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                if let Some(&c) = chars.peek() {
+                    if c == '?' || c == '!' {
+                        word.push(c);
+                        chars.next();
+                    }
+                }
+
+                let qualified = last == Some('.') || last == Some(':');
+
+                if !qualified && !is_label(&chars) {
+                    match &word[..] {
+                        "def" | "class" | "module" | "begin" | "case" => depth += 1,
+                        "if" | "unless" if expression                 => depth += 1,
+                        "while" | "until" | "for" if expression       => {
+                            depth += 1;
+                            looping = true;
+                        },
+                        "do" if looping                               => looping = false,
+                        "do"                                          => depth += 1,
+                        "end"                                         => depth -= 1,
+                        _                                             => ()
+                    }
+                }
+
+                expression = matches!(&word[..],
+                    "and" | "or" | "not" | "then" | "else" | "elsif" | "do" | "begin" | "when" |
+                    "ensure" | "rescue" | "return" | "if" | "unless" | "while" | "until"
+                );
+
+                last = word.chars().last();
+
+                continue;
+            },
+            _ => expression = true
+        }
+
+        last = Some(c);
+    }
+
+    if depth > 0 {
+        return false;
+    }
+
+    match last {
+        Some(c) => !"\\,.+-*/%=|&<>".contains(c),
+        None    => true
+    }
+}
+
+fn skip_string(chars: &mut Peekable<Chars>, quote: char) -> bool {
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn is_label(chars: &Peekable<Chars>) -> bool {
+    let mut chars = chars.clone();
+
+    chars.next() == Some(':') && chars.next() != Some(':')
+}
@@ -0,0 +1,17 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` containing a live mruby console.
+
+mod console;
+mod input;
+mod pretty;
+mod session;
+
+pub use self::console::Console;
+pub use self::input::is_complete;
+pub use self::pretty::pretty;
@@ -0,0 +1,65 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use mrusty::*;
+
+use super::super::math::{Matrix, Quaternion, Vector};
+
+/// A `fn` that formats an mruby `Value` for the console. `Vector`s and `Quaternion`s are printed
+/// as their components, `Matrix`es as a grid of rows and everything else with `inspect`.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::console;
+/// # use anima_engine::math::Vector;
+/// # use anima_engine::mrusty::*;
+/// let mruby = Mruby::new();
+///
+/// Vector::require(mruby.clone());
+///
+/// let vector = mruby.obj(Vector::new(1.0, 0.5, 0.0));
+///
+/// assert_eq!(console::pretty(&vector), "Vector(1, 0.5, 0)");
+/// assert_eq!(console::pretty(&mruby.string("hi")), "\"hi\"");
+/// ```
+pub fn pretty(value: &Value) -> String {
+    if let Ok(vector) = value.to_obj::<Vector>() {
+        return format!("Vector({}, {}, {})", vector.x, vector.y, vector.z);
+    }
+
+    if let Ok(quaternion) = value.to_obj::<Quaternion>() {
+        return format!("Quaternion({}, {}, {}, {})", quaternion.x, quaternion.y, quaternion.z,
+                       quaternion.w);
+    }
+
+    if let Ok(matrix) = value.to_obj::<Matrix>() {
+        return pretty_matrix(&matrix);
+    }
+
+    value.call("inspect", vec![])
+         .ok()
+         .and_then(|inspect| inspect.to_str().ok().map(String::from))
+         .unwrap_or_default()
+}
+
+fn pretty_matrix(matrix: &Matrix) -> String {
+    let cells: Vec<String> = (0..16).map(|i| {
+        // Rows are printed one after the other while columns are stored first.
+        format!("{}", matrix.array[(i % 4) * 4 + i / 4])
+    }).collect();
+
+    let width = cells.iter().map(|cell| cell.len()).max().unwrap_or(0);
+
+    let rows: Vec<String> = cells.chunks(4).map(|row| {
+        let row: Vec<String> = row.iter().map(|cell| format!("{:>1$}", cell, width)).collect();
+
+        format!("| {} |", row.join("  "))
+    }).collect();
+
+    format!("Matrix\n{}", rows.join("\n"))
+}
@@ -0,0 +1,140 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::mpsc::{self, Sender};
+
+use mrusty::ReadLine;
+
+use super::input::is_complete;
+
+// A complete piece of console input waiting to be evaluated along with where to send its output.
+pub struct Command {
+    pub source: String,
+    pub reply: Sender<String>
+}
+
+// One end of a console session, e.g. a terminal or a socket.
+pub trait Terminal {
+    fn read(&mut self, prompt: &str) -> Option<String>;
+
+    fn write(&mut self, output: &str) -> bool;
+}
+
+pub struct Stream<R: BufRead, W: Write> {
+    pub reader: R,
+    pub writer: W
+}
+
+impl<R: BufRead, W: Write> Terminal for Stream<R, W> {
+    fn read(&mut self, prompt: &str) -> Option<String> {
+        if self.writer.write_all(prompt.as_bytes()).and_then(|_| self.writer.flush()).is_err() {
+            return None;
+        }
+
+        let mut line = String::new();
+
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_)          => Some(line.trim_end_matches(['\n', '\r']).to_owned())
+        }
+    }
+
+    fn write(&mut self, output: &str) -> bool {
+        writeln!(self.writer, "{}", output).is_ok()
+    }
+}
+
+pub struct Line<R, E> {
+    pub read_line: R,
+    pub error: PhantomData<fn() -> E>
+}
+
+impl<E: Display, R: ReadLine<E>> Terminal for Line<R, E> {
+    fn read(&mut self, prompt: &str) -> Option<String> {
+        match self.read_line.read(prompt) {
+            Ok(Some(line)) => {
+                if !line.trim().is_empty() {
+                    self.read_line.add(&line);
+                }
+
+                Some(line)
+            },
+            Ok(None)       => None,
+            Err(error)     => {
+                println!("{}", error);
+
+                None
+            }
+        }
+    }
+
+    fn write(&mut self, output: &str) -> bool {
+        println!("{}", output);
+
+        true
+    }
+}
+
+// Reads lines from `terminal` until they form complete statements, sends them to the `Console`
+// and writes back the results. A blank line sends incomplete input as is so that the `Console`
+// reports its syntax error. Returns once the `terminal` or the `Console` is closed.
+pub fn run<T: Terminal>(mut terminal: T, name: &str, commands: Sender<Command>) {
+    let single = format!("{}> ", name);
+    let multi  = format!("{}* ", name);
+
+    let mut source = String::new();
+
+    loop {
+        let line = {
+            let prompt = if source.is_empty() { &single } else { &multi };
+
+            match terminal.read(prompt) {
+                Some(line) => line,
+                None       => break
+            }
+        };
+
+        let blank = line.trim().is_empty();
+
+        if blank && source.is_empty() {
+            continue;
+        }
+
+        if !source.is_empty() {
+            source.push('\n');
+        }
+
+        source.push_str(&line);
+
+        if !blank && !is_complete(&source) {
+            continue;
+        }
+
+        let (reply, output) = mpsc::channel();
+        let command = Command {
+            source: mem::take(&mut source),
+            reply: reply
+        };
+
+        if commands.send(command).is_err() {
+            break;
+        }
+
+        match output.recv() {
+            Ok(output) => {
+                if !terminal.write(&output) {
+                    break;
+                }
+            },
+            Err(_)     => break
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::Cell;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A `trait` used by `GameLoop` to measure time. `now` returns the monotonic `Duration` elapsed
+/// since the clock's epoch; `tick` is called once at the start of every frame; `wait_until`
+/// blocks until `now` reaches `deadline` and is used to pace frames.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::game::Clock;
+/// # use anima_engine::game::ManualClock;
+/// let clock = ManualClock::new(Duration::new(1, 0));
+///
+/// clock.tick();
+///
+/// assert_eq!(clock.now(), Duration::new(1, 0));
+/// ```
+pub trait Clock {
+    fn now(&self) -> Duration;
+
+    fn tick(&self) {}
+
+    fn wait_until(&self, deadline: Duration);
+}
+
+/// A `struct` implementing `Clock` on top of the system's monotonic clock.
+///
+/// `wait_until` sleeps the thread for most of the wait, yielding the CPU, and only spins for
+/// the last `spin` of it in order to wake up precisely.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::game::Clock;
+/// # use anima_engine::game::SystemClock;
+/// let clock = SystemClock::new();
+///
+/// assert!(clock.now() <= clock.now());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    start: Instant,
+    spin: Duration
+}
+
+impl SystemClock {
+    /// Creates a `SystemClock` whose epoch is the moment of its creation. It spins for the last
+    /// 2ms of every wait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::SystemClock;
+    /// let clock = SystemClock::new();
+    /// ```
+    pub fn new() -> SystemClock {
+        SystemClock::new_spin(Duration::new(0, 2_000_000))
+    }
+
+    /// Creates a `SystemClock` whose epoch is the moment of its creation. It spins for the last
+    /// `spin` of every wait. Larger values are more precise on systems with coarse sleeps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Clock;
+    /// # use anima_engine::game::SystemClock;
+    /// let clock = SystemClock::new_spin(Duration::new(0, 0));
+    ///
+    /// clock.wait_until(Duration::new(0, 1_000_000));
+    ///
+    /// assert!(clock.now() >= Duration::new(0, 1_000_000));
+    /// ```
+    pub fn new_spin(spin: Duration) -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+            spin: spin
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        Instant::now() - self.start
+    }
+
+    fn wait_until(&self, deadline: Duration) {
+        let now = self.now();
+
+        if deadline > now + self.spin {
+            thread::sleep(deadline - now - self.spin);
+        }
+
+        while self.now() < deadline {
+            thread::yield_now();
+        }
+    }
+}
+
+/// A `struct` implementing a simulated `Clock` which only moves when told to. Every `tick`
+/// advances it by a constant `Duration`, which makes runs of a `GameLoop` fully deterministic.
+/// `wait_until` jumps straight to the deadline.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::game::Clock;
+/// # use anima_engine::game::ManualClock;
+/// let clock = ManualClock::new(Duration::new(0, 0));
+///
+/// clock.tick();
+/// clock.advance(Duration::new(2, 0));
+///
+/// assert_eq!(clock.now(), Duration::new(2, 0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    time: Cell<Duration>,
+    tick: Duration
+}
+
+impl ManualClock {
+    /// Creates a `ManualClock` starting at zero which advances by `tick` every frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::ManualClock;
+    /// let clock = ManualClock::new(Duration::new(0, 16_666_667));
+    /// ```
+    pub fn new(tick: Duration) -> ManualClock {
+        ManualClock {
+            time: Cell::new(Duration::new(0, 0)),
+            tick: tick
+        }
+    }
+
+    /// Advances the clock by `duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Clock;
+    /// # use anima_engine::game::ManualClock;
+    /// let clock = ManualClock::new(Duration::new(0, 0));
+    ///
+    /// clock.advance(Duration::new(1, 0));
+    ///
+    /// assert_eq!(clock.now(), Duration::new(1, 0));
+    /// ```
+    pub fn advance(&self, duration: Duration) {
+        self.time.set(self.time.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.time.get()
+    }
+
+    fn tick(&self) {
+        self.advance(self.tick);
+    }
+
+    fn wait_until(&self, deadline: Duration) {
+        if deadline > self.time.get() {
+            self.time.set(deadline);
+        }
+    }
+}
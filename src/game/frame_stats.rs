@@ -0,0 +1,332 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::profiler::ScopeTime;
+
+/// A `struct` collecting frame timings over a rolling window of frames.
+///
+/// Frame times are the `Duration`s between frames as measured by the `GameLoop`'s `Clock`.
+/// Frames longer than `long_frame` are counted as long frames; fixed steps dropped because a
+/// frame needed more than `max_steps` of them are counted as dropped steps. Both counts, like
+/// `frames`, cover the whole run rather than just the window.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::game::FrameStats;
+/// let mut stats = FrameStats::new(3, Duration::new(0, 30_000_000));
+///
+/// for ms in &[10, 20, 30, 40] {
+///     stats.record(Duration::new(0, ms * 1_000_000), 0, vec![]);
+/// }
+///
+/// assert_eq!(stats.frames(), 4);
+/// assert_eq!(stats.long_frames(), 1);
+/// assert_eq!(stats.min(), Duration::new(0, 20_000_000));
+/// assert_eq!(stats.max(), Duration::new(0, 40_000_000));
+/// assert_eq!(stats.avg(), Duration::new(0, 30_000_000));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    window: usize,
+    long_frame: Duration,
+    times: VecDeque<Duration>,
+    frames: u64,
+    long_frames: u64,
+    dropped_steps: u64,
+    scopes: Vec<ScopeTime>
+}
+
+impl FrameStats {
+    /// Creates a `FrameStats` keeping the last `window` frames and counting frames longer than
+    /// `long_frame`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let stats = FrameStats::new(120, Duration::new(0, 33_333_333));
+    ///
+    /// assert_eq!(stats.frames(), 0);
+    /// ```
+    pub fn new(window: usize, long_frame: Duration) -> FrameStats {
+        if window == 0 { panic!("Frame statistics window cannot be empty."); }
+
+        FrameStats {
+            window: window,
+            long_frame: long_frame,
+            times: VecDeque::with_capacity(window),
+            frames: 0,
+            long_frames: 0,
+            dropped_steps: 0,
+            scopes: vec![]
+        }
+    }
+
+    /// Records a frame lasting `dt` which dropped `dropped_steps` fixed steps and spent time in
+    /// `scopes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(10, Duration::new(1, 0));
+    ///
+    /// stats.record(Duration::new(0, 1_000), 2, vec![]);
+    ///
+    /// assert_eq!(stats.last(), Some(Duration::new(0, 1_000)));
+    /// assert_eq!(stats.dropped_steps(), 2);
+    /// ```
+    pub fn record(&mut self, dt: Duration, dropped_steps: u32, scopes: Vec<ScopeTime>) {
+        if self.times.len() == self.window {
+            self.times.pop_front();
+        }
+
+        self.times.push_back(dt);
+
+        self.frames += 1;
+        self.dropped_steps += dropped_steps as u64;
+
+        if dt > self.long_frame {
+            self.long_frames += 1;
+        }
+
+        self.scopes = scopes;
+    }
+
+    /// Returns the total number of recorded frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(2, Duration::new(1, 0));
+    ///
+    /// for _ in 0..3 {
+    ///     stats.record(Duration::new(0, 1_000), 0, vec![]);
+    /// }
+    ///
+    /// assert_eq!(stats.frames(), 3);
+    /// ```
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the total number of frames longer than the long frame threshold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(10, Duration::new(0, 20_000_000));
+    ///
+    /// stats.record(Duration::new(0, 20_000_000), 0, vec![]);
+    /// stats.record(Duration::new(0, 30_000_000), 0, vec![]);
+    ///
+    /// assert_eq!(stats.long_frames(), 1);
+    /// ```
+    pub fn long_frames(&self) -> u64 {
+        self.long_frames
+    }
+
+    /// Returns the total number of fixed steps dropped to avoid a spiral of death.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(10, Duration::new(1, 0));
+    ///
+    /// stats.record(Duration::new(0, 1_000), 3, vec![]);
+    /// stats.record(Duration::new(0, 1_000), 1, vec![]);
+    ///
+    /// assert_eq!(stats.dropped_steps(), 4);
+    /// ```
+    pub fn dropped_steps(&self) -> u64 {
+        self.dropped_steps
+    }
+
+    /// Returns the last frame time, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(10, Duration::new(1, 0));
+    ///
+    /// assert_eq!(stats.last(), None);
+    ///
+    /// stats.record(Duration::new(0, 1_000), 0, vec![]);
+    ///
+    /// assert_eq!(stats.last(), Some(Duration::new(0, 1_000)));
+    /// ```
+    pub fn last(&self) -> Option<Duration> {
+        self.times.back().cloned()
+    }
+
+    /// Returns the profiling scopes of the last frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::{FrameStats, ScopeTime};
+    /// let mut stats = FrameStats::new(10, Duration::new(1, 0));
+    /// let scope = ScopeTime {
+    ///     name: "update".to_owned(),
+    ///     time: Duration::new(0, 1_000),
+    ///     calls: 1
+    /// };
+    ///
+    /// stats.record(Duration::new(0, 2_000), 0, vec![scope.clone()]);
+    ///
+    /// assert_eq!(stats.scopes(), &[scope]);
+    /// ```
+    pub fn scopes(&self) -> &[ScopeTime] {
+        &self.scopes
+    }
+
+    /// Returns the shortest frame time in the window, or zero if no frames were recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(2, Duration::new(1, 0));
+    ///
+    /// for ms in &[50, 10, 30] {
+    ///     stats.record(Duration::new(0, ms * 1_000_000), 0, vec![]);
+    /// }
+    ///
+    /// assert_eq!(stats.min(), Duration::new(0, 10_000_000));
+    /// ```
+    pub fn min(&self) -> Duration {
+        self.times.iter().min().cloned().unwrap_or(Duration::new(0, 0))
+    }
+
+    /// Returns the longest frame time in the window, or zero if no frames were recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(2, Duration::new(1, 0));
+    ///
+    /// for ms in &[50, 10, 30] {
+    ///     stats.record(Duration::new(0, ms * 1_000_000), 0, vec![]);
+    /// }
+    ///
+    /// assert_eq!(stats.max(), Duration::new(0, 30_000_000));
+    /// ```
+    pub fn max(&self) -> Duration {
+        self.times.iter().max().cloned().unwrap_or(Duration::new(0, 0))
+    }
+
+    /// Returns the average frame time in the window, or zero if no frames were recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(2, Duration::new(1, 0));
+    ///
+    /// for ms in &[50, 10, 30] {
+    ///     stats.record(Duration::new(0, ms * 1_000_000), 0, vec![]);
+    /// }
+    ///
+    /// assert_eq!(stats.avg(), Duration::new(0, 20_000_000));
+    /// ```
+    pub fn avg(&self) -> Duration {
+        if self.times.is_empty() {
+            return Duration::new(0, 0);
+        }
+
+        self.sum() / self.times.len() as u32
+    }
+
+    /// Returns the frame time which `percentile` percent of the window's frames do not exceed
+    /// (nearest-rank), or zero if no frames were recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(100, Duration::new(1, 0));
+    ///
+    /// for ms in 1..101 {
+    ///     stats.record(Duration::new(0, ms * 1_000_000), 0, vec![]);
+    /// }
+    ///
+    /// assert_eq!(stats.percentile(50.0), Duration::new(0, 50_000_000));
+    /// assert_eq!(stats.percentile(99.0), Duration::new(0, 99_000_000));
+    /// assert_eq!(stats.percentile(100.0), Duration::new(0, 100_000_000));
+    /// ```
+    pub fn percentile(&self, percentile: f32) -> Duration {
+        if self.times.is_empty() {
+            return Duration::new(0, 0);
+        }
+
+        let mut times: Vec<_> = self.times.iter().cloned().collect();
+
+        times.sort();
+
+        let percentile = percentile.clamp(0.0, 100.0) as f64;
+        let rank = (percentile / 100.0 * times.len() as f64).ceil() as usize;
+
+        times[rank.max(1) - 1]
+    }
+
+    /// Returns the average number of frames per second in the window, or `0.0` if no time was
+    /// recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::FrameStats;
+    /// let mut stats = FrameStats::new(10, Duration::new(1, 0));
+    ///
+    /// stats.record(Duration::new(0, 20_000_000), 0, vec![]);
+    /// stats.record(Duration::new(0, 20_000_000), 0, vec![]);
+    ///
+    /// assert_eq!(stats.fps(), 50.0);
+    /// ```
+    pub fn fps(&self) -> f32 {
+        let sum = self.sum();
+        let secs = sum.as_secs() as f64 + sum.subsec_nanos() as f64 / 1_000_000_000.0;
+
+        if secs == 0.0 {
+            return 0.0;
+        }
+
+        (self.times.len() as f64 / secs) as f32
+    }
+
+    fn sum(&self) -> Duration {
+        self.times.iter().fold(Duration::new(0, 0), |sum, time| sum + *time)
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> FrameStats {
+        FrameStats::new(120, Duration::new(0, 33_333_333))
+    }
+}
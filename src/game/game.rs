@@ -7,27 +7,124 @@
 
 use std::time::Duration;
 
+use super::profiler::Profiler;
+use super::time_control::TimeControl;
+
 /// A `trait` runnable within a `GameLoop`. `dt` is the `Duration` since last frame. `update`
 /// should return the boolean value of whether the game should continue.
 ///
+/// `init` is called once before the first frame and `shutdown` once after the game stops, either
+/// because `update` returned `false` or because the `GameLoop` was stopped.
+///
+/// When the `GameLoop` runs with a `Timestep::Fixed`, it calls `fixed_update`, which defaults to
+/// `update`, with the fixed step, and `draw` receives the interpolation `alpha` (between `0.0` and
+/// `1.0`) between the last two updated states.
+///
+/// `pause` and `resume` are called at the start of the first frame after the `GameLoop`'s
+/// `TimeControl` was paused or resumed.
+///
 /// # Examples
 ///
 /// ```
 /// # use std::time::Duration;
 /// # use anima_engine::game::Game;
 /// # use anima_engine::game::GameLoop;
-/// pub struct MyGame;
+/// pub struct MyGame {
+///     frames: u32
+/// }
 ///
 /// impl Game for MyGame {
-///     fn update(&self, dt: Duration) -> bool {
-///         // Update game state.
+///     fn init(&mut self) {
+///         self.frames = 0;
+///     }
+///
+///     fn update(&mut self, dt: Duration) -> bool {
+///         self.frames += 1;
+///
 ///         // Return `false` when game needs to stop.
-///         false
+///         self.frames < 10
 ///     }
 /// }
 ///
-/// GameLoop::new(MyGame);
+/// let mut game_loop = GameLoop::new(MyGame { frames: 0 });
+///
+/// game_loop.run();
+///
+/// assert_eq!(game_loop.into_game().frames, 10);
 /// ```
 pub trait Game {
+    /// Receives the `GameLoop`'s `TimeControl` right before `init`. Keep a clone of it to scale,
+    /// pause or step game time.
+    fn attach_time(&mut self, _time: &TimeControl) {}
+
+    /// Receives the `GameLoop`'s `Profiler` right before `init`. Keep a clone of it to time
+    /// scopes of your own.
+    fn attach_profiler(&mut self, _profiler: &Profiler) {}
+
+    /// Prepares the game right before its first frame.
+    fn init(&mut self) {}
+
+    fn update(&mut self, dt: Duration) -> bool;
+
+    /// Advances the game by exactly `step` in `Timestep::Fixed` mode. Calls `update` by default.
+    fn fixed_update(&mut self, step: Duration) -> bool {
+        self.update(step)
+    }
+
+    /// Renders the game once per frame, after all `update`s of that frame. `alpha` is always
+    /// `1.0` in `Timestep::Variable` mode.
+    fn draw(&mut self, _alpha: f32) {}
+
+    /// Reacts to game time being paused.
+    fn pause(&mut self) {}
+
+    /// Reacts to game time being resumed.
+    fn resume(&mut self) {}
+
+    /// Cleans up after the game's last frame.
+    fn shutdown(&mut self) {}
+}
+
+/// A `trait` for games updated through a shared reference, which is what `Game` used to require.
+/// Every `SharedGame` is a `Game`, so existing implementors only need to rename their `impl`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::cell::Cell;
+/// # use std::time::Duration;
+/// # use anima_engine::game::GameLoop;
+/// # use anima_engine::game::SharedGame;
+/// pub struct MyGame {
+///     frames: Cell<u32>
+/// }
+///
+/// impl SharedGame for MyGame {
+///     fn update(&self, dt: Duration) -> bool {
+///         self.frames.set(self.frames.get() + 1);
+///
+///         false
+///     }
+/// }
+///
+/// let mut game_loop = GameLoop::new(MyGame { frames: Cell::new(0) });
+///
+/// game_loop.run();
+///
+/// assert_eq!(game_loop.game.frames.get(), 1);
+/// ```
+pub trait SharedGame {
     fn update(&self, dt: Duration) -> bool;
+
+    fn draw(&self, _alpha: f32) {}
+}
+
+impl<T: SharedGame> Game for T {
+    fn update(&mut self, dt: Duration) -> bool {
+        SharedGame::update(self, dt)
+    }
+
+    fn draw(&mut self, alpha: f32) {
+        SharedGame::draw(self, alpha)
+    }
 }
@@ -5,12 +5,57 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use super::clock::{Clock, SystemClock};
+use super::frame_stats::FrameStats;
 use super::game::Game;
+use super::profiler::Profiler;
+use super::time_control::TimeControl;
 
-use std::time::Instant;
+use std::time::Duration;
+
+/// A callback run with the `GameLoop`'s `FrameStats` after every frame.
+type FrameCallback = Box<dyn FnMut(&FrameStats)>;
+
+/// An `enum` describing how a `GameLoop` feeds time to its `Game`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timestep {
+    /// `update` once per frame with the time since the last frame
+    Variable,
+    /// `update` zero or more times per frame with a constant `step`; at most `max_steps` updates
+    /// are run per frame, the rest of the backlog being dropped
+    Fixed {
+        /// `Duration` passed to every `update`; cannot be zero
+        step: Duration,
+        /// maximum number of catch-up `update`s per frame
+        max_steps: u32
+    }
+}
+
+/// An `enum` describing how often a `GameLoop` runs its frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameRate {
+    /// run frames back to back, as fast as possible
+    Unlimited,
+    /// run at most this many frames per second, waiting on the `Clock` between frames; `Capped(0)`
+    /// runs like `Unlimited`
+    Capped(u32)
+}
 
 /// A `struct` that helps you create a very simple game loop.
 ///
+/// The loop measures time with a `Clock`, `SystemClock` by default. Using a `ManualClock` along
+/// with `step` or `run_for` makes it possible to drive a `Game` frame by frame, e.g. in tests.
+///
+/// Setting `frame_rate` to `FrameRate::Capped` makes the loop wait on its `Clock` before every
+/// frame so that it runs at most that many frames per second instead of busy-looping.
+///
+/// Time passed to the `Game` goes through the loop's `TimeControl`, which can scale, pause and
+/// single-step it. While paused, a `Timestep::Variable` `update` receives a zero `Duration` and a
+/// `Timestep::Fixed` one is not called at all; a step runs exactly one `update`.
+///
+/// Every frame is recorded in `stats`. `update` and `draw` are timed in the `"update"` and
+/// `"draw"` scopes of the loop's `Profiler`, which the `Game` can use to open scopes of its own.
+///
 /// # Examples
 ///
 /// ```
@@ -20,7 +65,7 @@ use std::time::Instant;
 /// pub struct MyGame;
 ///
 /// impl Game for MyGame {
-///     fn update(&self, dt: Duration) -> bool {
+///     fn update(&mut self, dt: Duration) -> bool {
 ///         // Update game state.
 ///         // Return `false` when game needs to stop.
 ///         false
@@ -29,12 +74,57 @@ use std::time::Instant;
 ///
 /// GameLoop::new(MyGame).run();
 /// ```
-pub struct GameLoop<T: Game> {
-    pub game: T
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::game::FrameRate;
+/// # use anima_engine::game::Game;
+/// # use anima_engine::game::GameLoop;
+/// # use anima_engine::game::ManualClock;
+/// pub struct MyGame;
+///
+/// impl Game for MyGame {
+///     fn update(&mut self, dt: Duration) -> bool {
+///         true
+///     }
+/// }
+///
+/// let mut game_loop = GameLoop::new_clock(MyGame, ManualClock::new(Duration::new(0, 0)));
+///
+/// game_loop.frame_rate = FrameRate::Capped(50);
+/// game_loop.run_for(Duration::new(1, 0));
+///
+/// assert_eq!(game_loop.stats.max(), Duration::new(0, 20_000_000));
+/// ```
+pub struct GameLoop<T: Game, C: Clock = SystemClock> {
+    pub game: T,
+    /// `FrameRate` the `Game` is paced at; `FrameRate::Unlimited` by default
+    pub frame_rate: FrameRate,
+    /// `Clock` measuring the time between frames
+    pub clock: C,
+    /// `TimeControl` shared with the `Game`
+    pub time: TimeControl,
+    /// `FrameStats` of the frames run so far
+    pub stats: FrameStats,
+    /// `Profiler` shared with the `Game`
+    pub profiler: Profiler,
+    timestep: Timestep,
+    on_frame: Option<FrameCallback>,
+    state: State,
+    last: Option<Duration>,
+    accumulator: Duration,
+    paused: bool
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Idle,
+    Running,
+    Stopped
 }
 
 impl<T: Game> GameLoop<T> {
-    /// Creates a `GameLoop`.
+    /// Creates a `GameLoop` with a `Timestep::Variable`.
     ///
     /// # Examples
     ///
@@ -45,7 +135,7 @@ impl<T: Game> GameLoop<T> {
     /// pub struct MyGame;
     ///
     /// impl Game for MyGame {
-    ///     fn update(&self, dt: Duration) -> bool {
+    ///     fn update(&mut self, dt: Duration) -> bool {
     ///         // Update game state.
     ///         // Return `false` when game needs to stop.
     ///         false
@@ -55,11 +145,85 @@ impl<T: Game> GameLoop<T> {
     /// GameLoop::new(MyGame);
     /// ```
     pub fn new(game: T) -> GameLoop<T> {
-        GameLoop { game: game }
+        GameLoop::new_clock(game, SystemClock::new())
+    }
+
+    /// Creates a `GameLoop` with a `Timestep::Fixed`. `fixed_update` will always receive `step`
+    /// and will be called at most `max_steps` times per frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         // Advance physics by exactly `dt`.
+    ///         false
+    ///     }
+    ///
+    ///     fn draw(&mut self, alpha: f32) {
+    ///         // Render state interpolated between the last two updates.
+    ///     }
+    /// }
+    ///
+    /// GameLoop::new_fixed(MyGame, Duration::new(0, 16_666_667), 5).run();
+    /// ```
+    pub fn new_fixed(game: T, step: Duration, max_steps: u32) -> GameLoop<T> {
+        let mut game_loop = GameLoop::new(game);
+
+        game_loop.set_timestep(Timestep::Fixed { step: step, max_steps: max_steps });
+
+        game_loop
+    }
+}
+
+impl<T: Game, C: Clock> GameLoop<T, C> {
+    /// Creates a `GameLoop` with a `Timestep::Variable` measuring time with `clock`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// # use anima_engine::game::ManualClock;
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         assert_eq!(dt, Duration::new(1, 0));
+    ///
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new_clock(MyGame, ManualClock::new(Duration::new(1, 0)));
+    ///
+    /// game_loop.step(3);
+    /// ```
+    pub fn new_clock(game: T, clock: C) -> GameLoop<T, C> {
+        GameLoop {
+            game: game,
+            frame_rate: FrameRate::Unlimited,
+            clock: clock,
+            time: TimeControl::new(),
+            stats: FrameStats::default(),
+            profiler: Profiler::new(),
+            timestep: Timestep::Variable,
+            on_frame: None,
+            state: State::Idle,
+            last: None,
+            accumulator: Duration::new(0, 0),
+            paused: false
+        }
     }
 
     /// Runs `GameLoop`'s `Game` in a loop while feeding the time between frames to the `Game`'s
-    /// `update`.
+    /// `update` according to the `GameLoop`'s `Timestep`. Returns once the `Game` has stopped.
     ///
     /// # Examples
     ///
@@ -70,7 +234,7 @@ impl<T: Game> GameLoop<T> {
     /// pub struct MyGame;
     ///
     /// impl Game for MyGame {
-    ///     fn update(&self, dt: Duration) -> bool {
+    ///     fn update(&mut self, dt: Duration) -> bool {
     ///         // Update game state.
     ///         // Return `false` when game needs to stop.
     ///         false
@@ -79,17 +243,364 @@ impl<T: Game> GameLoop<T> {
     ///
     /// GameLoop::new(MyGame).run();
     /// ```
-    pub fn run(&self) {
-        let mut last = Instant::now();
+    pub fn run(&mut self) {
+        while self.frame() {}
+    }
 
-        loop {
-            let start = Instant::now();
+    /// Runs at most `n` frames of the `GameLoop`. Returns `false` if the `Game` has stopped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// # use anima_engine::game::ManualClock;
+    /// pub struct MyGame {
+    ///     frames: u32
+    /// }
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         self.frames += 1;
+    ///
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let game = MyGame { frames: 0 };
+    /// let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+    ///
+    /// assert!(game_loop.step(10));
+    /// assert_eq!(game_loop.game.frames, 10);
+    /// ```
+    pub fn step(&mut self, n: u32) -> bool {
+        for _ in 0..n {
+            if !self.frame() {
+                return false;
+            }
+        }
 
-            if !self.game.update(start - last) {
-                break;
+        true
+    }
+
+    /// Runs frames of the `GameLoop` until `duration` has passed on its `Clock`. Returns `false`
+    /// if the `Game` has stopped.
+    ///
+    /// Make sure the `Clock` actually advances between frames, e.g. that a `ManualClock` has a
+    /// non-zero tick.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Clock;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// # use anima_engine::game::ManualClock;
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new_clock(MyGame, ManualClock::new(Duration::new(0, 10)));
+    ///
+    /// assert!(game_loop.run_for(Duration::new(0, 100)));
+    /// assert_eq!(game_loop.clock.now(), Duration::new(0, 100));
+    /// ```
+    pub fn run_for(&mut self, duration: Duration) -> bool {
+        let end = self.clock.now() + duration;
+
+        while self.clock.now() < end {
+            if !self.frame() {
+                return false;
             }
+        }
+
+        true
+    }
+
+    /// Returns the `Timestep` used when running the `Game`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::{Game, GameLoop, Timestep};
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         false
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(GameLoop::new(MyGame).timestep(), Timestep::Variable);
+    /// ```
+    pub fn timestep(&self) -> Timestep {
+        self.timestep
+    }
 
-            last = start;
+    /// Sets the `Timestep` used when running the `Game`. Panics if it is a `Timestep::Fixed` with
+    /// a zero `step`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::{Game, GameLoop, Timestep};
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         false
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new(MyGame);
+    /// let timestep = Timestep::Fixed { step: Duration::new(0, 16_666_667), max_steps: 5 };
+    ///
+    /// game_loop.set_timestep(timestep);
+    ///
+    /// assert_eq!(game_loop.timestep(), timestep);
+    /// ```
+    pub fn set_timestep(&mut self, timestep: Timestep) {
+        if let Timestep::Fixed { step, .. } = timestep {
+            if step == Duration::new(0, 0) { panic!("Fixed timestep cannot be zero."); }
+        }
+
+        self.timestep = timestep;
+    }
+
+    /// Sets a callback run with the `GameLoop`'s `FrameStats` after every frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// # use anima_engine::game::ManualClock;
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new_clock(MyGame, ManualClock::new(Duration::new(0, 1)));
+    ///
+    /// game_loop.on_frame(|stats| {
+    ///     if stats.frames() % 60 == 0 {
+    ///         println!("{} fps", stats.fps());
+    ///     }
+    /// });
+    /// game_loop.step(120);
+    /// ```
+    pub fn on_frame<F>(&mut self, callback: F) where F: FnMut(&FrameStats) + 'static {
+        self.on_frame = Some(Box::new(callback));
+    }
+
+    /// Stops the `GameLoop`, calling the `Game`'s `shutdown` if it was running. Further frames
+    /// will not be run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// # use anima_engine::game::ManualClock;
+    /// pub struct MyGame;
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new_clock(MyGame, ManualClock::new(Duration::new(0, 1)));
+    ///
+    /// assert!(game_loop.step(1));
+    ///
+    /// game_loop.stop();
+    ///
+    /// assert!(!game_loop.step(1));
+    /// ```
+    pub fn stop(&mut self) {
+        if self.state == State::Running {
+            self.game.shutdown();
+        }
+
+        self.state = State::Stopped;
+    }
+
+    /// Returns the `GameLoop`'s `Game`, consuming the `GameLoop`. The `Game` is not shut down if
+    /// it was still running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::Game;
+    /// # use anima_engine::game::GameLoop;
+    /// pub struct MyGame {
+    ///     score: u32
+    /// }
+    ///
+    /// impl Game for MyGame {
+    ///     fn update(&mut self, dt: Duration) -> bool {
+    ///         self.score += 10;
+    ///
+    ///         false
+    ///     }
+    /// }
+    ///
+    /// let mut game_loop = GameLoop::new(MyGame { score: 0 });
+    ///
+    /// game_loop.run();
+    ///
+    /// assert_eq!(game_loop.into_game().score, 10);
+    /// ```
+    pub fn into_game(self) -> T {
+        self.game
+    }
+
+    fn frame(&mut self) -> bool {
+        match self.state {
+            State::Idle    => {
+                self.game.attach_time(&self.time);
+                self.game.attach_profiler(&self.profiler);
+                self.game.init();
+                self.state = State::Running;
+            },
+            State::Running => (),
+            State::Stopped => return false
+        }
+
+        if !self.advance() {
+            self.stop();
+
+            return false;
         }
+
+        true
     }
+
+    fn advance(&mut self) -> bool {
+        let last = match self.last {
+            Some(last) => last,
+            None       => self.clock.now()
+        };
+
+        self.clock.tick();
+
+        match (self.last, self.frame_rate) {
+            (Some(_), FrameRate::Capped(fps)) if fps > 0 => {
+                self.clock.wait_until(last + from_nanos(1_000_000_000 / fps as u64));
+            },
+            _ => ()
+        }
+
+        let now = self.clock.now();
+        let paused = self.time.is_paused();
+        let dt = self.time.advance(now - last);
+
+        self.last = Some(now);
+
+        if paused != self.paused {
+            self.paused = paused;
+
+            if paused {
+                self.game.pause();
+            } else {
+                self.game.resume();
+            }
+        }
+
+        let mut dropped = 0;
+
+        match self.timestep {
+            Timestep::Variable => {
+                if !self.update(dt.unwrap_or(Duration::new(0, 0))) {
+                    return false;
+                }
+
+                self.draw(1.0);
+            },
+            Timestep::Fixed { step, max_steps } => {
+                if let Some(dt) = dt {
+                    if paused {
+                        if !self.fixed_update(step) {
+                            return false;
+                        }
+                    } else {
+                        self.accumulator += dt;
+
+                        let mut steps = 0;
+
+                        while self.accumulator >= step {
+                            if steps == max_steps {
+                                // Drop the backlog that cannot be caught up to avoid a spiral of
+                                // death.
+                                dropped = (nanos(self.accumulator) / nanos(step)) as u32;
+
+                                self.accumulator = from_nanos(nanos(self.accumulator) %
+                                                              nanos(step));
+
+                                break;
+                            }
+
+                            if !self.fixed_update(step) {
+                                return false;
+                            }
+
+                            self.accumulator -= step;
+                            steps += 1;
+                        }
+                    }
+                }
+
+                self.draw((nanos(self.accumulator) as f64 / nanos(step) as f64) as f32);
+            }
+        }
+
+        self.stats.record(now - last, dropped, self.profiler.frame());
+
+        if let Some(ref mut on_frame) = self.on_frame {
+            on_frame(&self.stats);
+        }
+
+        true
+    }
+
+    fn update(&mut self, dt: Duration) -> bool {
+        let _scope = self.profiler.scope("update");
+
+        self.game.update(dt)
+    }
+
+    fn fixed_update(&mut self, step: Duration) -> bool {
+        let _scope = self.profiler.scope("update");
+
+        self.game.fixed_update(step)
+    }
+
+    fn draw(&mut self, alpha: f32) {
+        let _scope = self.profiler.scope("draw");
+
+        self.game.draw(alpha);
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+fn from_nanos(nanos: u64) -> Duration {
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
 }
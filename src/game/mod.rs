@@ -7,10 +7,29 @@
 
 //! A `mod` containing game running constructs.
 
+mod clock;
+mod frame_stats;
 mod game;
 mod game_loop;
 mod mruby_game;
+mod mruby_game_error;
+mod profiler;
+mod time_control;
 
+pub use self::clock::Clock;
+pub use self::clock::ManualClock;
+pub use self::clock::SystemClock;
+pub use self::frame_stats::FrameStats;
 pub use self::game::Game;
+pub use self::game::SharedGame;
+pub use self::game_loop::FrameRate;
 pub use self::game_loop::GameLoop;
+pub use self::game_loop::Timestep;
 pub use self::mruby_game::MrubyGame;
+pub use self::mruby_game_error::ErrorAction;
+pub use self::mruby_game_error::MrubyGameError;
+pub use self::profiler::Profiler;
+pub use self::profiler::PROFILER_PRELUDE;
+pub use self::profiler::Scope;
+pub use self::profiler::ScopeTime;
+pub use self::time_control::TimeControl;
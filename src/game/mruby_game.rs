@@ -5,65 +5,710 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::path::Path;
-
-use std::time::Duration;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
 
 use mrusty::*;
 
 use super::game::Game;
-use super::super::scripting;
-
+use super::mruby_game_error::{ErrorAction, MrubyGameError};
+use super::profiler::{Profiler, PROFILER_PRELUDE};
+use super::time_control::TimeControl;
+use super::super::console::Console;
+use super::super::scripting::{self, Bundle, BundleError, Loader, Registry, Watchdog};
 
 /// A `struct` used to run games from mruby directly.
 ///
 /// Make sure you point to an mruby file with a `Game` `Class` defined which implements a method
 /// `update(dt)`, where `dt` is a `Float` representing the time since the last frame.
 ///
+/// `Game` can also define any of the following methods, which are called when defined:
+///
+/// * `init`, right before the first frame
+/// * `fixed_update(step)`, instead of `update` when the `GameLoop` runs with a
+///   `Timestep::Fixed`
+/// * `draw(alpha)`, once per frame after all updates
+/// * `on_pause` and `on_resume`, at the start of the first frame after game time was paused or
+///   resumed
+/// * `on_event(event)`, with every event passed to `event`
+/// * `shutdown`, once the game has stopped
+///
+/// The game keeps running until it calls `quit`, after which the current frame is its last one.
+/// An `update` returning `false` stops the game as well; any other value is ignored, unless
+/// `strict_returns` makes it an `MrubyGameError::ReturnType`.
+///
+/// Once run by a `GameLoop`, the `Game` instance can scale, pause and step game time through
+/// `@time`, the loop's `TimeControl`, and time parts of its frames through `@profiler`, the
+/// loop's `Profiler`:
+///
+/// ```ruby
+/// class Game
+///   def update(dt)
+///     @time.scale = 0.25 if slow_motion?
+///
+///     @profiler.scope 'ai' do
+///       think
+///     end
+///
+///     quit if game_over?
+///   end
+/// end
+/// ```
+///
+/// Errors raised by any of these methods are passed to a handler, set with `on_error`, which
+/// decides whether the game continues, pauses or stops. By default they are printed to standard
+/// error and the game stops.
+///
+/// After a call to `watch`, the script and the files it `require`s are hot-reloaded whenever they
+/// change on disk: changed files are re-executed into the running mruby state, which redefines the
+/// methods of `Game` while the `Game` instance keeps its instance variables. If `Game` defines a
+/// `reloaded` method, it is called after every reload.
+///
+/// A `Console` attached with `attach_console` is polled right before every update. Its
+/// statements are evaluated in the context of the `Game` instance.
+///
+/// After a call to `budget`, calls into the `Game` instance are interrupted with a
+/// `ScriptTimeout` once they run longer than the budget, which reaches the error handler as an
+/// `MrubyGameError::Timeout` carrying the script's backtrace. Console statements run under the
+/// same budget, their timeouts only showing up in the console. See `Watchdog` for the loops which
+/// cannot be interrupted.
+///
 /// # Examples
 ///
 /// ```no-run
-/// let game = MrubyGame::new(Path::new("game.rb"));
+/// let game = MrubyGame::new(Path::new("game.rb")).unwrap();
 ///
 /// GameLoop::new(game).run();
 /// ```
 pub struct MrubyGame {
     pub mruby: MrubyType,
-    pub game: Value
+    pub game: Value,
+    script: PathBuf,
+    registry: Registry,
+    files: Vec<(PathBuf, Option<Stamp>)>,
+    watch: Option<Duration>,
+    last_poll: Instant,
+    watchdog: Option<(Rc<Watchdog>, Duration)>,
+    time: Option<TimeControl>,
+    console: Option<Console>,
+    on_error: Box<dyn FnMut(&MrubyGameError) -> ErrorAction>,
+    on_reload: ReloadCallback,
+    last_error: Option<MrubyGameError>,
+    strict_returns: bool,
+    running: bool
 }
 
+type Stamp = (SystemTime, u64);
+
+/// A callback run with the result of every hot-reload.
+type ReloadCallback = Box<dyn FnMut(Result<(), &MrubyGameError>)>;
+
+const LIFECYCLE: &'static str = "
+  module Anima
+    module Lifecycle
+      def quit
+        @anima_quit = true
+      end
+    end
+  end
+
+  Game.include Anima::Lifecycle
+";
+
+const PROFILER_CHECK: &'static str = "
+  if Object.const_defined?(:Profiler) && !(Profiler.is_a?(Class) && Profiler.superclass == Object)
+    raise TypeError, 'Profiler is already defined by the script'
+  end
+";
+
 impl MrubyGame {
     /// Creates a new `MrubyGame` from an mruby script.
     ///
     /// Make sure you point to an mruby file with a `Game` `Class` defined which implements a
     /// method `update(dt)`, where `dt` is a `Float` representing the time since the last frame.
     ///
+    /// The script can `require` the whole Anima API and any script file from its directory.
+    ///
+    /// Returns an `MrubyGameError` if the script cannot be loaded or run, or if it does not
+    /// define `Game`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// match MrubyGame::new(Path::new("game.rb")) {
+    ///     Ok(game)   => GameLoop::new(game).run(),
+    ///     Err(error) => println!("{}", error)
+    /// }
+    /// ```
+    pub fn new(script: &Path) -> Result<MrubyGame, MrubyGameError> {
+        MrubyGame::new_registry(script, Registry::new_engine().loader(Loader::new(root(script))))
+    }
+
+    /// Creates a new `MrubyGame` from an mruby script run in states built by `registry`, which
+    /// decides the API and the files the script can `require`. Only files loaded through the
+    /// `registry`'s `Loader` are hot-reloaded.
+    ///
+    /// Returns an `MrubyGameError` if the script cannot be loaded or run, or if it does not
+    /// define `Game`.
+    ///
     /// # Examples
     ///
     /// ```no-run
-    /// let game = MrubyGame::new(Path::new("game.rb"));
+    /// let registry = Registry::new_engine().without("game")
+    ///                                      .def_file::<Input>("input")
+    ///                                      .loader(Loader::new(Path::new("game")));
+    ///
+    /// let game = MrubyGame::new_registry(Path::new("game.rb"), registry).unwrap();
     ///
     /// GameLoop::new(game).run();
     /// ```
-    pub fn new(script: &Path) -> MrubyGame {
-        let mruby = scripting::get_mruby();
+    pub fn new_registry(script: &Path, registry: Registry) -> Result<MrubyGame, MrubyGameError> {
+        let mruby = load(script, &registry)?;
+        let game = mruby.run("Game.new")?;
 
-        mruby.execute(script).unwrap();
+        // Bundled scripts have no files to watch.
+        let files = match registry.get_loader().and_then(|loader| loader.bundle()) {
+            Some(_) => vec![],
+            None    => files(&mruby, script)
+        };
 
-        let game = mruby.run("Game.new")
-                        .unwrap_or_else(|_| { panic!("Game class must be defined in mruby") });
-
-        MrubyGame {
+        Ok(MrubyGame {
+            files: files,
             mruby: mruby,
-            game: game
+            game: game,
+            script: script.to_owned(),
+            registry: registry,
+            watch: None,
+            last_poll: Instant::now(),
+            watchdog: None,
+            time: None,
+            console: None,
+            on_error: Box::new(|error| {
+                eprintln!("{}", error);
+
+                ErrorAction::Stop
+            }),
+            on_reload: Box::new(|result| {
+                if let Err(error) = result {
+                    eprintln!("Reload failed: {}", error);
+                }
+            }),
+            last_error: None,
+            strict_returns: false,
+            running: true
+        })
+    }
+
+    /// Creates a new `MrubyGame` running the script of a `Bundle` built by `compile`, with the
+    /// whole Anima API. Bundled games are not hot-reloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let bundle = Bundle::read(&mut File::open("game.bundle").unwrap()).unwrap();
+    /// let game = MrubyGame::new_bundle(bundle).unwrap();
+    ///
+    /// GameLoop::new(game).run();
+    /// ```
+    pub fn new_bundle(bundle: Bundle) -> Result<MrubyGame, MrubyGameError> {
+        MrubyGame::new_bundle_registry(bundle, Registry::new_engine())
+    }
+
+    /// Creates a new `MrubyGame` running the script of a `Bundle` built by `compile` in states
+    /// built by `registry`. The `registry`'s `Loader`, if any, is replaced by one resolving files
+    /// in the `Bundle`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let bundle = Bundle::read(&mut File::open("game.bundle").unwrap()).unwrap();
+    /// let registry = Registry::new_engine().def_file::<Input>("input");
+    ///
+    /// let game = MrubyGame::new_bundle_registry(bundle, registry).unwrap();
+    ///
+    /// GameLoop::new(game).run();
+    /// ```
+    pub fn new_bundle_registry(bundle: Bundle,
+                               registry: Registry) -> Result<MrubyGame, MrubyGameError> {
+        let script = bundle.script().to_owned();
+
+        MrubyGame::new_registry(&script, registry.loader(Loader::new_bundle(bundle)))
+    }
+
+    /// Compiles the script and the files it `require`d while loading to mruby bytecode, ready to
+    /// be run with `new_bundle`. Files `require`d later on need to be added to the `Bundle`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    /// let bundle = game.compile().unwrap();
+    ///
+    /// bundle.write(&mut File::create("game.bundle").unwrap()).unwrap();
+    /// ```
+    pub fn compile(&self) -> Result<Bundle, MrubyGameError> {
+        let loader = match self.registry.get_loader() {
+            Some(loader) => loader.clone(),
+            None         => Loader::new(root(&self.script))
+        };
+
+        let mut bundle = Bundle::new(&loader, &self.script)?;
+
+        for &(ref path, _) in &self.files {
+            if *path != self.script {
+                bundle.add(path)?;
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// Starts hot-reloading the script, checking whether it or the files it `require`s changed
+    /// at most once every `interval`, right before `update`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.watch(Duration::new(0, 500_000_000));
+    ///
+    /// GameLoop::new(game).run();
+    /// ```
+    pub fn watch(&mut self, interval: Duration) {
+        self.watch = Some(interval);
+    }
+
+    /// Stops hot-reloading the script.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.watch(Duration::new(1, 0));
+    /// game.unwatch();
+    /// ```
+    pub fn unwatch(&mut self) {
+        self.watch = None;
+    }
+
+    /// Limits every call into the `Game` instance, as well as every statement of an attached
+    /// `Console`, to `budget` of wall time.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.budget(Duration::new(0, 100_000_000));
+    ///
+    /// GameLoop::new(game).run();
+    /// ```
+    pub fn budget(&mut self, budget: Duration) {
+        let watchdog = match self.watchdog {
+            Some((ref watchdog, _)) => watchdog.clone(),
+            None                    => Rc::new(Watchdog::new(&self.mruby))
+        };
+
+        if let Some(ref mut console) = self.console {
+            console.budget(watchdog.clone(), budget);
+        }
+
+        self.watchdog = Some((watchdog, budget));
+    }
+
+    /// Attaches a `Console` which will be polled before every update.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    /// let console = Console::new();
+    ///
+    /// console.listen_stdin();
+    /// game.attach_console(console);
+    ///
+    /// GameLoop::new(game).run();
+    /// ```
+    pub fn attach_console(&mut self, mut console: Console) {
+        if let Some((ref watchdog, budget)) = self.watchdog {
+            console.budget(watchdog.clone(), budget);
+        }
+
+        self.console = Some(console);
+    }
+
+    /// Reports `update` results other than `true`, `false` or `nil` to the error handler as an
+    /// `MrubyGameError::ReturnType` when `strict` is `true`. They are ignored by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.strict_returns(true);
+    /// game.on_error(|error| {
+    ///     eprintln!("{}", error);
+    ///
+    ///     ErrorAction::Continue
+    /// });
+    /// ```
+    pub fn strict_returns(&mut self, strict: bool) {
+        self.strict_returns = strict;
+    }
+
+    /// Sets the handler called after every hot reload with its result.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.on_reload(|result| {
+    ///     match result {
+    ///         Ok(())     => println!("Reloaded."),
+    ///         Err(error) => println!("Reload failed: {}", error)
+    ///     }
+    /// });
+    /// ```
+    pub fn on_reload<F>(&mut self, handler: F)
+        where F: FnMut(Result<(), &MrubyGameError>) + 'static {
+        self.on_reload = Box::new(handler);
+    }
+
+    /// Reloads the script if it or any of the files it `require`s changed since they were last
+    /// loaded. Returns whether anything was reloaded.
+    ///
+    /// The script is first run in a scratch mruby state; the running game is only touched if that
+    /// succeeds, so a broken script leaves the previous version running. A failed reload is not
+    /// retried until the files change again.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// if let Err(error) = game.reload() {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn reload(&mut self) -> Result<bool, MrubyGameError> {
+        let mut changed = vec![];
+
+        for &mut (ref path, ref mut stamp) in &mut self.files {
+            let current = self::stamp(path);
+
+            if current != *stamp {
+                *stamp = current;
+
+                changed.push(path.clone());
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(false);
+        }
+
+        load(&self.script, &self.registry)?;
+
+        for path in &changed {
+            self.mruby.execute(path)?;
+        }
+
+        self.files = files(&self.mruby, &self.script);
+
+        if self.mruby.run("Game.method_defined? :reloaded")?.to_bool()? {
+            self.call("reloaded", vec![])?;
+        }
+
+        Ok(true)
+    }
+
+    /// Sets the handler called with every error raised by the `Game` instance. Its `ErrorAction`
+    /// decides how the game carries on.
+    ///
+    /// `ErrorAction::Pause` only pauses game time; `update` is still called every frame, with a
+    /// zero `dt` in `Timestep::Variable` mode.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// game.on_error(|error| {
+    ///     println!("{}", error);
+    ///
+    ///     ErrorAction::Pause
+    /// });
+    /// ```
+    pub fn on_error<F>(&mut self, handler: F)
+        where F: FnMut(&MrubyGameError) -> ErrorAction + 'static {
+        self.on_error = Box::new(handler);
+    }
+
+    /// Returns the last error raised by the `Game` instance, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game_loop = GameLoop::new(MrubyGame::new(Path::new("game.rb")).unwrap());
+    ///
+    /// game_loop.run();
+    ///
+    /// if let Some(error) = game_loop.game.last_error() {
+    ///     println!("Game crashed: {}", error);
+    /// }
+    /// ```
+    pub fn last_error(&self) -> Option<&MrubyGameError> {
+        self.last_error.as_ref()
+    }
+
+    /// Passes `event`, usually a `Symbol` or a `Hash`, to the `Game` instance's `on_event` right
+    /// away. Events are dropped if `Game` does not define `on_event`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let mut game = MrubyGame::new(Path::new("game.rb")).unwrap();
+    ///
+    /// let event = game.mruby.run("{ type: :key_down, key: :space }").unwrap();
+    ///
+    /// game.event(event);
+    /// ```
+    pub fn event(&mut self, event: Value) {
+        self.callback("on_event", vec![event]);
+    }
+
+    fn advance(&mut self, name: &str, dt: Duration) -> bool {
+        if let Some(interval) = self.watch {
+            if self.last_poll.elapsed() >= interval {
+                self.last_poll = Instant::now();
+
+                match self.reload() {
+                    Ok(true)   => (self.on_reload)(Ok(())),
+                    Ok(false)  => (),
+                    Err(error) => (self.on_reload)(Err(&error))
+                }
+            }
+        }
+
+        if let Some(ref mut console) = self.console {
+            console.poll_in(&self.mruby, &self.game);
+        }
+
+        if self.running {
+            let dt = dt.as_secs() as f64 + dt.subsec_nanos() as f64 / 1_000_000_000.0;
+            let dt = self.mruby.float(dt);
+
+            match self.call(name, vec![dt]) {
+                Ok(result) => {
+                    let nil = result.call("nil?", vec![]).and_then(|nil| nil.to_bool())
+                                                         .unwrap_or(false);
+
+                    // Only an explicit `false` stops the game, not a `nil`.
+                    match result.to_bool() {
+                        Ok(false) if !nil             => self.running = false,
+                        Err(_) if self.strict_returns => {
+                            let inspect = result.call("inspect", vec![])
+                                                .ok()
+                                                .and_then(|inspect| {
+                                                    inspect.to_str().ok().map(String::from)
+                                                })
+                                                .unwrap_or_default();
+
+                            if !self.fail(MrubyGameError::ReturnType(inspect)) {
+                                self.running = false;
+                            }
+                        },
+                        _                             => ()
+                    }
+                },
+                Err(error) => self.error(error)
+            }
+
+            self.check_quit();
+        }
+
+        self.running
+    }
+
+    fn callback(&mut self, name: &str, args: Vec<Value>) {
+        if self.responds(name) {
+            if let Err(error) = self.call(name, args) {
+                self.error(error);
+            }
+
+            self.check_quit();
+        }
+    }
+
+    fn responds(&self, name: &str) -> bool {
+        self.game.call("respond_to?", vec![self.mruby.symbol(name)])
+                 .and_then(|responds| responds.to_bool())
+                 .unwrap_or(false)
+    }
+
+    fn check_quit(&mut self) {
+        if let Some(quit) = self.game.get_var("@anima_quit") {
+            if quit.to_bool().unwrap_or(false) {
+                self.running = false;
+            }
+        }
+    }
+
+    fn error(&mut self, error: MrubyError) {
+        if !self.fail(MrubyGameError::from(error)) {
+            self.running = false;
+        }
+    }
+
+    fn call(&self, name: &str, args: Vec<Value>) -> Result<Value, MrubyError> {
+        match self.watchdog {
+            Some((ref watchdog, budget)) => watchdog.call(&self.game, name, args, budget),
+            None                         => self.game.call(name, args)
+        }
+    }
+
+    fn fail(&mut self, error: MrubyGameError) -> bool {
+        let action = (self.on_error)(&error);
+
+        self.last_error = Some(error);
+
+        match action {
+            ErrorAction::Continue => true,
+            ErrorAction::Pause    => {
+                if let Some(ref time) = self.time {
+                    time.pause();
+                }
+
+                true
+            },
+            ErrorAction::Stop     => false
         }
     }
 }
 
 impl Game for MrubyGame {
-    fn update(&self, dt: Duration) -> bool {
-        let dt = self.mruby.float(dt.as_secs() as f64 + dt.subsec_nanos() as f64 / 1000_000_000.0);
+    fn attach_time(&mut self, time: &TimeControl) {
+        TimeControl::require(self.mruby.clone());
+
+        self.game.set_var("@time", self.mruby.obj(time.clone()));
+
+        self.time = Some(time.clone());
+    }
+
+    fn attach_profiler(&mut self, profiler: &Profiler) {
+        // Scripts can define their own `Profiler` before one is attached, which mrusty cannot
+        // define the `Class` over.
+        let result = self.mruby.run(PROFILER_CHECK).and_then(|_| {
+            Profiler::require(self.mruby.clone());
+
+            self.mruby.run(PROFILER_PRELUDE)
+        });
+
+        if let Err(error) = result {
+            return self.error(error);
+        }
+
+        self.game.set_var("@profiler", self.mruby.obj(profiler.clone()));
+    }
+
+    fn init(&mut self) {
+        self.callback("init", vec![]);
+    }
+
+    fn update(&mut self, dt: Duration) -> bool {
+        self.advance("update", dt)
+    }
+
+    fn fixed_update(&mut self, step: Duration) -> bool {
+        let name = if self.responds("fixed_update") { "fixed_update" } else { "update" };
 
-        self.game.call("update", vec![dt]).unwrap().to_bool().unwrap()
+        self.advance(name, step)
     }
+
+    fn draw(&mut self, alpha: f32) {
+        let alpha = self.mruby.float(alpha as f64);
+
+        self.callback("draw", vec![alpha]);
+    }
+
+    fn pause(&mut self) {
+        self.callback("on_pause", vec![]);
+    }
+
+    fn resume(&mut self) {
+        self.callback("on_resume", vec![]);
+    }
+
+    fn shutdown(&mut self) {
+        self.callback("shutdown", vec![]);
+    }
+}
+
+fn load(script: &Path, registry: &Registry) -> Result<MrubyType, MrubyGameError> {
+    let mruby = registry.build()?;
+
+    match registry.get_loader().and_then(|loader| loader.bundle()) {
+        Some(bundle) => {
+            let code = match bundle.code(script) {
+                Some(code) => code,
+                None       => {
+                    let script = script.to_string_lossy().into_owned();
+
+                    return Err(MrubyGameError::Bundle(BundleError::Missing(script)));
+                }
+            };
+
+            scripting::run(&mruby, code)?
+        },
+        None         => mruby.execute(script)?
+    };
+
+    if !mruby.is_defined("Game") {
+        return Err(MrubyGameError::MissingGame);
+    }
+
+    mruby.run(LIFECYCLE)?;
+
+    Ok(mruby)
+}
+
+// Returns the files loaded into `mruby`, in an order where required files come before the ones
+// requiring them.
+fn files(mruby: &MrubyType, script: &Path) -> Vec<(PathBuf, Option<Stamp>)> {
+    let mut paths = vec![];
+
+    if let Ok(loaded) = mruby.run("$LOADED_FEATURES").and_then(|loaded| loaded.to_vec()) {
+        for path in loaded {
+            if let Ok(path) = path.to_str() {
+                paths.push(PathBuf::from(path));
+            }
+        }
+    }
+
+    paths.push(script.to_owned());
+
+    paths.into_iter().map(|path| {
+        let stamp = stamp(&path);
+
+        (path, stamp)
+    }).collect()
+}
+
+// Returns the game's root directory, the one holding `script`.
+fn root(script: &Path) -> &Path {
+    match script.parent() {
+        Some(root) if root != Path::new("") => root,
+        _                                   => Path::new(".")
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    fs::metadata(path).ok().and_then(|metadata| {
+        metadata.modified().ok().map(|modified| (modified, metadata.len()))
+    })
 }
@@ -0,0 +1,102 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+use mrusty::MrubyError;
+
+use super::super::scripting::{BundleError, ScriptException};
+
+/// An `enum` describing what went wrong while running an `MrubyGame`'s script.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MrubyGameError {
+    /// script file could not be read
+    Load(String),
+    /// `Bundle` could not be built or read
+    Bundle(BundleError),
+    /// script could not be parsed
+    Syntax(ScriptException),
+    /// script does not define a `Game` `Class`
+    MissingGame,
+    /// script raised an exception
+    Exception(ScriptException),
+    /// script ran past its budget and was interrupted with a `ScriptTimeout`
+    Timeout(ScriptException),
+    /// `update` returned something other than `true`, `false` or `nil`, given here as its
+    /// `inspect`; only reported with `MrubyGame::strict_returns`
+    ReturnType(String)
+}
+
+impl From<MrubyError> for MrubyGameError {
+    fn from(error: MrubyError) -> MrubyGameError {
+        match error {
+            MrubyError::Runtime(error) => {
+                let exception = ScriptException::parse(&error);
+
+                match &exception.class[..] {
+                    "SyntaxError"   => MrubyGameError::Syntax(exception),
+                    "ScriptTimeout" => MrubyGameError::Timeout(exception),
+                    _               => MrubyGameError::Exception(exception)
+                }
+            },
+            error => MrubyGameError::Load(format!("{}", error))
+        }
+    }
+}
+
+impl From<BundleError> for MrubyGameError {
+    fn from(error: BundleError) -> MrubyGameError {
+        match error {
+            BundleError::Syntax(exception) => MrubyGameError::Syntax(exception),
+            error                          => MrubyGameError::Bundle(error)
+        }
+    }
+}
+
+impl fmt::Display for MrubyGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MrubyGameError::Load(ref error)          => write!(f, "Cannot load script: {}", error),
+            MrubyGameError::Bundle(ref error)        => write!(f, "{}", error),
+            MrubyGameError::Syntax(ref exception)    => write!(f, "{}", exception),
+            MrubyGameError::MissingGame              => {
+                write!(f, "Game class must be defined in mruby")
+            },
+            MrubyGameError::Exception(ref exception) => write!(f, "{}", exception),
+            MrubyGameError::Timeout(ref exception)   => write!(f, "{}", exception),
+            MrubyGameError::ReturnType(ref value)    => {
+                write!(f, "Game#update must return true, false or nil, not {}", value)
+            }
+        }
+    }
+}
+
+impl Error for MrubyGameError {
+    fn description(&self) -> &str {
+        match *self {
+            MrubyGameError::Load(_)       => "mruby script cannot be loaded",
+            MrubyGameError::Bundle(_)     => "mruby bundle cannot be built or read",
+            MrubyGameError::Syntax(_)     => "mruby script has a syntax error",
+            MrubyGameError::MissingGame   => "mruby script does not define Game",
+            MrubyGameError::Exception(_)  => "mruby script raised an exception",
+            MrubyGameError::Timeout(_)    => "mruby script ran past its budget",
+            MrubyGameError::ReturnType(_) => "mruby Game#update returned a non-boolean"
+        }
+    }
+}
+
+/// An `enum` telling an `MrubyGame` how to carry on after an error in its `Game` instance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorAction {
+    /// keep running the game as if nothing happened
+    Continue,
+    /// pause game time through the `GameLoop`'s `TimeControl` and keep running
+    Pause,
+    /// stop the `GameLoop`, shutting the game down
+    Stop
+}
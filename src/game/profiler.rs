@@ -0,0 +1,254 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A `struct` containing the time spent in a named profiling scope during one frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeTime {
+    /// `String` name of the scope
+    pub name: String,
+    /// `Duration` spent in the scope, nested scopes included
+    pub time: Duration,
+    /// `u32` number of times the scope was entered
+    pub calls: u32
+}
+
+/// A `struct` measuring the time spent in named scopes. It is a shared handle: clones refer to
+/// the same state, so scopes opened by the `Game` end up in its `GameLoop`'s `FrameStats`.
+///
+/// Scopes can be nested, in which case the outer scope's time includes the inner one's. Times
+/// are always measured with the system's monotonic clock.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::game::Profiler;
+/// let profiler = Profiler::new();
+///
+/// {
+///     let _scope = profiler.scope("physics");
+///
+///     // Simulate physics.
+/// }
+///
+/// let scopes = profiler.frame();
+///
+/// assert_eq!(scopes[0].name, "physics");
+/// assert_eq!(scopes[0].calls, 1);
+/// assert!(profiler.frame().is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    state: Rc<RefCell<ProfilerState>>
+}
+
+#[derive(Debug)]
+struct ProfilerState {
+    open: Vec<(String, Instant)>,
+    scopes: Vec<ScopeTime>
+}
+
+/// A `struct` guarding an open `Profiler` scope. The scope ends when it is dropped.
+pub struct Scope {
+    profiler: Profiler
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        self.profiler.end();
+    }
+}
+
+impl Profiler {
+    /// Creates a `Profiler` with no recorded scopes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::Profiler;
+    /// let profiler = Profiler::new();
+    ///
+    /// assert!(profiler.frame().is_empty());
+    /// ```
+    pub fn new() -> Profiler {
+        Profiler {
+            state: Rc::new(RefCell::new(ProfilerState {
+                open: vec![],
+                scopes: vec![]
+            }))
+        }
+    }
+
+    /// Opens a scope called `name`. Every `begin` needs a matching `end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::Profiler;
+    /// let profiler = Profiler::new();
+    ///
+    /// profiler.begin("ai");
+    /// profiler.end();
+    ///
+    /// assert_eq!(profiler.frame()[0].name, "ai");
+    /// ```
+    pub fn begin(&self, name: &str) {
+        self.state.borrow_mut().open.push((name.to_owned(), Instant::now()));
+    }
+
+    /// Closes the last opened scope. Does nothing if there is no open scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::Profiler;
+    /// let profiler = Profiler::new();
+    ///
+    /// profiler.begin("outer");
+    /// profiler.begin("inner");
+    /// profiler.end();
+    /// profiler.end();
+    ///
+    /// let scopes = profiler.frame();
+    ///
+    /// assert_eq!(scopes[0].name, "inner");
+    /// assert!(scopes[0].time <= scopes[1].time);
+    /// ```
+    pub fn end(&self) {
+        let mut state = self.state.borrow_mut();
+
+        if let Some((name, start)) = state.open.pop() {
+            let time = Instant::now() - start;
+
+            if let Some(scope) = state.scopes.iter_mut().find(|scope| scope.name == name) {
+                scope.time += time;
+                scope.calls += 1;
+
+                return;
+            }
+
+            state.scopes.push(ScopeTime {
+                name: name,
+                time: time,
+                calls: 1
+            });
+        }
+    }
+
+    /// Opens a scope called `name` which ends when the returned `Scope` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::Profiler;
+    /// let profiler = Profiler::new();
+    ///
+    /// for _ in 0..3 {
+    ///     let _scope = profiler.scope("loop");
+    /// }
+    ///
+    /// assert_eq!(profiler.frame()[0].calls, 3);
+    /// ```
+    pub fn scope(&self, name: &str) -> Scope {
+        self.begin(name);
+
+        Scope { profiler: self.clone() }
+    }
+
+    /// Returns the scopes closed since the last call, in order of first completion, and starts
+    /// recording a new frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::Profiler;
+    /// let profiler = Profiler::new();
+    ///
+    /// profiler.scope("render");
+    ///
+    /// assert_eq!(profiler.frame().len(), 1);
+    /// assert_eq!(profiler.frame().len(), 0);
+    /// ```
+    pub fn frame(&self) -> Vec<ScopeTime> {
+        let mut state = self.state.borrow_mut();
+
+        state.scopes.drain(..).collect()
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+use mrusty::*;
+
+mrusty_class!(Profiler, {
+    def!("initialize", || {
+        Profiler::new()
+    });
+
+    def!("begin", |mruby, slf: Profiler, name: str| {
+        slf.begin(name);
+
+        mruby.nil()
+    });
+
+    def!("end", |mruby, slf: Profiler| {
+        slf.end();
+
+        mruby.nil()
+    });
+
+    def!("to_s", |mruby, _slf: Profiler| {
+        mruby.string("<Profiler>")
+    });
+});
+
+/// mruby code completing the `Profiler` `Class` with a block-based `scope`.
+pub const PROFILER_PRELUDE: &'static str = "
+  class Profiler
+    def scope(name)
+      self.begin name
+
+      yield
+    ensure
+      self.end
+    end
+  end
+";
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Profiler;
+
+    describe!(Profiler, "
+      context 'when new' do
+        subject { Profiler.new }
+
+        it 'opens a scope on #begin' do
+          expect(subject.begin 'ai').to be_nil
+        end
+
+        it 'closes a scope on #end' do
+          subject.begin 'ai'
+
+          expect(subject.end).to be_nil
+        end
+
+        it 'converts to String on #to_s' do
+          expect(subject.to_s).to eql '<Profiler>'
+        end
+      end
+    ");
+}
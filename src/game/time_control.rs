@@ -0,0 +1,360 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A `struct` controlling the flow of game time in a `GameLoop`. It is a shared handle: clones
+/// refer to the same state, so the `Game` and the `GameLoop` see each other's changes.
+///
+/// Game time runs `scale` times as fast as real time and stops while paused. A `step` lets
+/// exactly one more frame through while paused. Unscaled real time stays available through
+/// `real_dt` and `real_time`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::game::TimeControl;
+/// let time = TimeControl::new();
+///
+/// time.set_scale(0.5);
+///
+/// assert_eq!(time.advance(Duration::new(2, 0)), Some(Duration::new(1, 0)));
+///
+/// time.pause();
+///
+/// assert_eq!(time.advance(Duration::new(2, 0)), None);
+/// assert_eq!(time.real_time(), Duration::new(4, 0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimeControl {
+    state: Rc<RefCell<TimeState>>
+}
+
+#[derive(Debug)]
+struct TimeState {
+    scale: f32,
+    paused: bool,
+    steps: u32,
+    real_dt: Duration,
+    real_time: Duration
+}
+
+impl TimeControl {
+    /// Creates a running `TimeControl` with a scale of `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// assert_eq!(time.scale(), 1.0);
+    /// assert!(!time.is_paused());
+    /// ```
+    pub fn new() -> TimeControl {
+        TimeControl {
+            state: Rc::new(RefCell::new(TimeState {
+                scale: 1.0,
+                paused: false,
+                steps: 0,
+                real_dt: Duration::new(0, 0),
+                real_time: Duration::new(0, 0)
+            }))
+        }
+    }
+
+    /// Returns the time scale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// assert_eq!(TimeControl::new().scale(), 1.0);
+    /// ```
+    pub fn scale(&self) -> f32 {
+        self.state.borrow().scale
+    }
+
+    /// Sets the time scale. `0.5` runs the game in slow motion at half speed. Panics when `scale`
+    /// is negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.set_scale(2.0);
+    ///
+    /// assert_eq!(time.scale(), 2.0);
+    /// ```
+    pub fn set_scale(&self, scale: f32) {
+        if scale < 0.0 { panic!("Time scale cannot be negative."); }
+
+        self.state.borrow_mut().scale = scale;
+    }
+
+    /// Returns whether game time is paused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// assert!(!TimeControl::new().is_paused());
+    /// ```
+    pub fn is_paused(&self) -> bool {
+        self.state.borrow().paused
+    }
+
+    /// Pauses game time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.pause();
+    ///
+    /// assert!(time.is_paused());
+    /// ```
+    pub fn pause(&self) {
+        self.state.borrow_mut().paused = true;
+    }
+
+    /// Resumes game time, dropping pending steps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.pause();
+    /// time.resume();
+    ///
+    /// assert!(!time.is_paused());
+    /// ```
+    pub fn resume(&self) {
+        let mut state = self.state.borrow_mut();
+
+        state.paused = false;
+        state.steps = 0;
+    }
+
+    /// Requests one more frame of game time while paused. Has no effect while running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.pause();
+    /// time.step();
+    ///
+    /// assert_eq!(time.advance(Duration::new(1, 0)), Some(Duration::new(1, 0)));
+    /// assert_eq!(time.advance(Duration::new(1, 0)), None);
+    /// ```
+    pub fn step(&self) {
+        let mut state = self.state.borrow_mut();
+
+        if state.paused {
+            state.steps += 1;
+        }
+    }
+
+    /// Returns the unscaled `Duration` of the last frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.pause();
+    /// time.advance(Duration::new(1, 0));
+    ///
+    /// assert_eq!(time.real_dt(), Duration::new(1, 0));
+    /// ```
+    pub fn real_dt(&self) -> Duration {
+        self.state.borrow().real_dt
+    }
+
+    /// Returns the unscaled `Duration` of all frames so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.advance(Duration::new(1, 0));
+    /// time.advance(Duration::new(2, 0));
+    ///
+    /// assert_eq!(time.real_time(), Duration::new(3, 0));
+    /// ```
+    pub fn real_time(&self) -> Duration {
+        self.state.borrow().real_time
+    }
+
+    /// Records a frame lasting `real_dt` and returns the scaled game `Duration` it amounts to,
+    /// or `None` if game time is paused. Consumes one pending step while paused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::game::TimeControl;
+    /// let time = TimeControl::new();
+    ///
+    /// time.set_scale(3.0);
+    ///
+    /// assert_eq!(time.advance(Duration::new(1, 0)), Some(Duration::new(3, 0)));
+    /// ```
+    pub fn advance(&self, real_dt: Duration) -> Option<Duration> {
+        let mut state = self.state.borrow_mut();
+
+        state.real_dt = real_dt;
+        state.real_time += real_dt;
+
+        if state.paused {
+            if state.steps == 0 {
+                return None;
+            }
+
+            state.steps -= 1;
+        }
+
+        let nanos = real_dt.as_secs() as f64 * 1_000_000_000.0 + real_dt.subsec_nanos() as f64;
+        let nanos = (nanos * state.scale as f64).round() as u64;
+
+        Some(Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32))
+    }
+}
+
+impl Default for TimeControl {
+    fn default() -> TimeControl {
+        TimeControl::new()
+    }
+}
+
+use mrusty::*;
+
+fn secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+mrusty_class!(TimeControl, {
+    def!("initialize", || {
+        TimeControl::new()
+    });
+
+    def!("scale", |mruby, slf: TimeControl| {
+        mruby.float(slf.scale() as f64)
+    });
+
+    def!("scale=", |mruby, slf: TimeControl, scale: f64| {
+        if scale < 0.0 {
+            return mruby.raise("ArgumentError", "scale cannot be negative");
+        }
+
+        slf.set_scale(scale as f32);
+
+        mruby.float(scale)
+    });
+
+    def!("paused?", |mruby, slf: TimeControl| {
+        mruby.bool(slf.is_paused())
+    });
+
+    def!("pause", |mruby, slf: TimeControl| {
+        slf.pause();
+
+        mruby.nil()
+    });
+
+    def!("resume", |mruby, slf: TimeControl| {
+        slf.resume();
+
+        mruby.nil()
+    });
+
+    def!("step", |mruby, slf: TimeControl| {
+        slf.step();
+
+        mruby.nil()
+    });
+
+    def!("real_dt", |mruby, slf: TimeControl| {
+        mruby.float(secs(slf.real_dt()))
+    });
+
+    def!("real_time", |mruby, slf: TimeControl| {
+        mruby.float(secs(slf.real_time()))
+    });
+
+    def!("to_s", |mruby, slf: TimeControl| {
+        let string = format!("<TimeControl: @scale={} @paused={}>", slf.scale(), slf.is_paused());
+
+        mruby.string(&string)
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::TimeControl;
+
+    describe!(TimeControl, "
+      context 'when new' do
+        subject { TimeControl.new }
+
+        it 'returns 1.0 on #scale' do
+          expect(subject.scale).to eql 1.0
+        end
+
+        it 'is not paused on #paused?' do
+          expect(subject.paused?).to be_falsey
+        end
+
+        it 'sets scale on #scale=' do
+          subject.scale = 0.5
+
+          expect(subject.scale).to eql 0.5
+        end
+
+        it 'pauses on #pause' do
+          subject.pause
+
+          expect(subject.paused?).to be_truthy
+        end
+
+        it 'resumes on #resume' do
+          subject.pause
+          subject.resume
+
+          expect(subject.paused?).to be_falsey
+        end
+
+        it 'returns 0.0 on #real_time' do
+          expect(subject.real_time).to eql 0.0
+        end
+
+        it 'converts to String on #to_s' do
+          expect(subject.to_s).to eql '<TimeControl: @scale=1 @paused=false>'
+        end
+      end
+    ");
+}
@@ -10,6 +10,7 @@
 #[macro_use]
 pub extern crate mrusty;
 
+pub mod console;
 pub mod game;
 pub mod math;
 pub mod scripting;
@@ -0,0 +1,369 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::hit;
+use super::hit::Hit;
+use super::matrix::Matrix;
+use super::plane::Plane;
+use super::vector::Vector;
+
+/// An axis-aligned bounding box `struct`. `Aabb` holds `f32`s and `DAabb` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Aabb, Vector};
+/// let b1 = Aabb::new(Vector::zero(), Vector::new_unf(2.0));
+/// let b2 = Aabb::new(Vector::one(), Vector::new_unf(4.0));
+///
+/// assert_eq!(b1.center(), Vector::one());
+/// assert!(b1.hit_aabb(b2).is_some());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb<T = f32> {
+    /// `Vector<T>` minimum corner
+    pub min: Vector<T>,
+    /// `Vector<T>` maximum corner
+    pub max: Vector<T>
+}
+
+impl<T: Scalar> Aabb<T> {
+    /// Creates a box using two opposite corners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::new(1.0, 0.0, 1.0), Vector::new(0.0, 1.0, 0.0));
+    ///
+    /// assert_eq!(b, Aabb { min: Vector::zero(), max: Vector::one() });
+    /// ```
+    pub fn new(corner1: Vector<T>, corner2: Vector<T>) -> Aabb<T> {
+        Aabb {
+            min: Vector::new(corner1.x.min(corner2.x),
+                             corner1.y.min(corner2.y),
+                             corner1.z.min(corner2.z)),
+            max: Vector::new(corner1.x.max(corner2.x),
+                             corner1.y.max(corner2.y),
+                             corner1.z.max(corner2.z))
+        }
+    }
+
+    /// Creates the smallest box containing `points`. Panics if `points` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new_points(&[Vector::zero(), Vector::up(), Vector::new(2.0, -1.0, 0.0)]);
+    ///
+    /// assert_eq!(b, Aabb::new(Vector::new(0.0, -1.0, 0.0), Vector::new(2.0, 1.0, 0.0)));
+    /// ```
+    pub fn new_points(points: &[Vector<T>]) -> Aabb<T> {
+        let first = match points.first() {
+            Some(&first) => Aabb { min: first, max: first },
+            None         => panic!("Cannot bound an empty set of points.")
+        };
+
+        points.iter().fold(first, |aabb, &point| Aabb::new(
+            Vector::new(aabb.min.x.min(point.x), aabb.min.y.min(point.y), aabb.min.z.min(point.z)),
+            Vector::new(aabb.max.x.max(point.x), aabb.max.y.max(point.y), aabb.max.z.max(point.z))
+        ))
+    }
+
+    /// Computes the center of a box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(b.center(), Vector::one());
+    /// ```
+    pub fn center(&self) -> Vector<T> {
+        (self.min + self.max) * (T::one() / T::from_f64(2.0))
+    }
+
+    /// Computes the half-sizes of a box on every axis.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(b.half_extents(), Vector::one());
+    /// ```
+    pub fn half_extents(&self) -> Vector<T> {
+        (self.max - self.min) * (T::one() / T::from_f64(2.0))
+    }
+
+    /// Returns the 8 corners of a box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::one());
+    ///
+    /// assert_eq!(b.corners()[0], Vector::zero());
+    /// assert_eq!(b.corners()[7], Vector::one());
+    /// ```
+    pub fn corners(&self) -> [Vector<T>; 8] {
+        let (min, max) = (self.min, self.max);
+
+        [
+            Vector::new(min.x, min.y, min.z),
+            Vector::new(max.x, min.y, min.z),
+            Vector::new(min.x, max.y, min.z),
+            Vector::new(max.x, max.y, min.z),
+            Vector::new(min.x, min.y, max.z),
+            Vector::new(max.x, min.y, max.z),
+            Vector::new(min.x, max.y, max.z),
+            Vector::new(max.x, max.y, max.z)
+        ]
+    }
+
+    /// Checks whether a point is inside a box or on its surface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::one());
+    ///
+    /// assert!(b.contains(Vector::one()));
+    /// assert!(!b.contains(Vector::new_unf(2.0)));
+    /// ```
+    pub fn contains(&self, point: Vector<T>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+        point.y >= self.min.y && point.y <= self.max.y &&
+        point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    /// Computes the point inside a box closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::one());
+    ///
+    /// assert_eq!(b.closest_point(Vector::new(0.5, 3.0, -1.0)), Vector::new(0.5, 1.0, 0.0));
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        Vector::new(point.x.max(self.min.x).min(self.max.x),
+                    point.y.max(self.min.y).min(self.max.y),
+                    point.z.max(self.min.z).min(self.max.z))
+    }
+
+    /// Checks whether two boxes overlap. The `Hit` separates them along the axis on which they
+    /// overlap the least.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Hit, Vector};
+    /// let b1 = Aabb::new(Vector::zero(), Vector::one());
+    /// let b2 = Aabb::new(Vector::new(0.0, 0.75, 0.0), Vector::new(1.0, 2.0, 1.0));
+    ///
+    /// assert_eq!(b1.hit_aabb(b2), Some(Hit::new(0.25, Vector::down())));
+    /// ```
+    pub fn hit_aabb(&self, other: Aabb<T>) -> Option<Hit<T>> {
+        let overlaps = [
+            self.max.x.min(other.max.x) - self.min.x.max(other.min.x),
+            self.max.y.min(other.max.y) - self.min.y.max(other.min.y),
+            self.max.z.min(other.max.z) - self.min.z.max(other.min.z)
+        ];
+
+        let mut axis = 0;
+
+        for i in 0..3 {
+            if overlaps[i] <= T::zero() {
+                return None;
+            }
+
+            if overlaps[i] < overlaps[axis] {
+                axis = i;
+            }
+        }
+
+        let to_other = other.center() - self.center();
+        let to_other = [to_other.x, to_other.y, to_other.z];
+
+        let mut normal = [T::zero(); 3];
+        normal[axis] = if to_other[axis] > T::zero() { -T::one() } else { T::one() };
+
+        Some(Hit::new(overlaps[axis], Vector::new(normal[0], normal[1], normal[2])))
+    }
+
+    /// Checks whether a box crosses a plane. The `Hit` moves the box to the side of the plane its
+    /// center is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Hit, Plane, Vector};
+    /// let b = Aabb::new(Vector::zero(), Vector::one());
+    /// let p = Plane::new(Vector::up(), 0.25);
+    ///
+    /// assert_eq!(b.hit_plane(p), Some(Hit::new(0.25, Vector::up())));
+    /// ```
+    pub fn hit_plane(&self, plane: Plane<T>) -> Option<Hit<T>> {
+        let extents = self.half_extents();
+        let normal = plane.normal;
+
+        let radius = extents.x * normal.x.abs() +
+                     extents.y * normal.y.abs() +
+                     extents.z * normal.z.abs();
+
+        hit::hit_plane(plane, self.center(), radius)
+    }
+
+    /// Converts a box to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, DAabb, DVector, Vector};
+    /// let b = DAabb::new(DVector::zero(), DVector::one());
+    ///
+    /// assert_eq!(b.cast::<f32>(), Aabb::new(Vector::zero(), Vector::one()));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Aabb<U> {
+        Aabb {
+            min: self.min.cast(),
+            max: self.max.cast()
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+use super::sphere::Sphere;
+
+/// Computes the box bounding a transformed box.
+impl<T: Scalar> Mul<Aabb<T>> for Matrix<T> {
+    type Output = Aabb<T>;
+
+    fn mul(self, aabb: Aabb<T>) -> Aabb<T> {
+        let mut corners = aabb.corners();
+
+        for corner in corners.iter_mut() {
+            *corner = self * *corner;
+        }
+
+        Aabb::new_points(&corners)
+    }
+}
+
+impl From<Aabb<f32>> for Aabb<f64> {
+    fn from(aabb: Aabb<f32>) -> Aabb<f64> {
+        aabb.cast()
+    }
+}
+
+mrusty_class!(Aabb, {
+    def!("initialize", |corner1: Vector, corner2: Vector| {
+        Aabb::new((*corner1).clone(), (*corner2).clone())
+    });
+
+    def!("min", |mruby, slf: Aabb| {
+        mruby.obj(slf.min)
+    });
+
+    def!("max", |mruby, slf: Aabb| {
+        mruby.obj(slf.max)
+    });
+
+    def!("center", |mruby, slf: Aabb| {
+        mruby.obj(slf.center())
+    });
+
+    def!("half_extents", |mruby, slf: Aabb| {
+        mruby.obj(slf.half_extents())
+    });
+
+    def!("contains?", |mruby, slf: Aabb, point: Vector| {
+        mruby.bool(slf.contains((*point).clone()))
+    });
+
+    def!("closest_point", |mruby, slf: Aabb, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("hit", |mruby, slf: Aabb, shape: Value| {
+        if let Ok(other) = shape.to_obj::<Aabb>() {
+            hit::to_value(&mruby, slf.hit_aabb((*other).clone()))
+        } else if let Ok(plane) = shape.to_obj::<Plane>() {
+            hit::to_value(&mruby, slf.hit_plane((*plane).clone()))
+        } else if let Ok(sphere) = shape.to_obj::<Sphere>() {
+            hit::to_value(&mruby, sphere.hit_aabb((*slf).clone()).map(|hit| {
+                Hit::new(hit.distance, -hit.normal)
+            }))
+        } else {
+            mruby.raise("ArgumentError", "expecting Aabb, Plane or Sphere")
+        }
+    });
+
+    def!("transform", |mruby, slf: Aabb, matrix: Matrix| {
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Aabb;
+    use super::super::{Matrix, Plane, Sphere, Vector};
+
+    describe!(Aabb, (Matrix, Plane, Sphere, Vector), "
+      context 'when unit' do
+        subject { Aabb.new Vector.zero, Vector.one }
+
+        it 'returns corners on #min and #max' do
+          expect(subject.min).to eql Vector.zero
+          expect(subject.max).to eql Vector.one
+        end
+
+        it 'computes center on #center' do
+          expect(subject.center).to eql Vector.uniform(0.5)
+        end
+
+        it 'computes half extents on #half_extents' do
+          expect(subject.half_extents).to eql Vector.uniform(0.5)
+        end
+
+        it 'checks points on #contains?' do
+          expect(subject.contains? Vector.uniform(0.5)).to be true
+          expect(subject.contains? Vector.uniform(2.0)).to be false
+        end
+
+        it 'computes closest point on #closest_point' do
+          expect(subject.closest_point Vector.uniform(2.0)).to eql Vector.one
+        end
+
+        it 'hits overlapping shapes on #hit' do
+          depth, normal = subject.hit(Aabb.new(Vector.new(0.0, 0.75, 0.0), Vector.one))
+
+          expect(depth).to eql 0.25
+          expect(normal).to eql Vector.down
+          expect(subject.hit Plane.new(Vector.up, 2.0)).to be_nil
+          expect(subject.hit Sphere.new(Vector.new(0.5, 1.5, 0.5), 1.0)).not_to be_nil
+        end
+
+        it 'transforms on #transform' do
+          expect(subject.transform(Matrix.identity.trans(Vector.up)).min).to eql Vector.up
+        end
+      end
+    ");
+}
@@ -5,7 +5,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use math::Vector;
+use math::Scalar;
+use super::vector::Vector;
 
 /// A `macro` useful for defining Bézier curves.
 ///
@@ -87,16 +88,17 @@ macro_rules! path {
     }
 }
 
-/// A `struct` useful for creating square and cubic Bézier curves.
+/// A `struct` useful for creating square and cubic Bézier curves. `Bezier` works with `f32`s and
+/// `DBezier` with `f64`s.
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Bezier {
-    v1: Vector,
-    v2: Vector,
-    v3: Vector,
-    v4: Option<Vector>
+pub struct Bezier<T = f32> {
+    v1: Vector<T>,
+    v2: Vector<T>,
+    v3: Vector<T>,
+    v4: Option<Vector<T>>
 }
 
-impl Bezier {
+impl<T: Scalar> Bezier<T> {
     /// Creates a square Bézier from `v1` to `v3` curving towards `v2`.
     ///
     /// # Examples
@@ -110,7 +112,7 @@ impl Bezier {
     ///     Vector::new(2.0, 0.0, 0.0)
     /// );
     /// ```
-    pub fn new_sqr(v1: Vector, v2: Vector, v3: Vector) -> Bezier {
+    pub fn new_sqr(v1: Vector<T>, v2: Vector<T>, v3: Vector<T>) -> Bezier<T> {
         Bezier {
             v1: v1,
             v2: v2,
@@ -133,7 +135,7 @@ impl Bezier {
     ///     Vector::new(1.0, 0.0, 0.0)
     /// );
     /// ```
-    pub fn new_cub(v1: Vector, v2: Vector, v3: Vector, v4: Vector) -> Bezier {
+    pub fn new_cub(v1: Vector<T>, v2: Vector<T>, v3: Vector<T>, v4: Vector<T>) -> Bezier<T> {
         Bezier {
             v1: v1,
             v2: v2,
@@ -170,17 +172,17 @@ impl Bezier {
     ///
     /// assert_eq!(b.interpolate(0.5), Vector::new(0.5, 0.75, 0.0));
     /// ```
-    pub fn interpolate(&self, ratio: f32) -> Vector {
+    pub fn interpolate(&self, ratio: T) -> Vector<T> {
         match self.v4 {
             Some(v4) => {
-                self.v1 * (1.0 - ratio).powi(3) +
-                self.v2 * 3.0 * (1.0 - ratio).powi(2) * ratio +
-                self.v3 * 3.0 * (1.0 - ratio) * ratio.powi(2) +
+                self.v1 * (T::one() - ratio).powi(3) +
+                self.v2 * T::from_f64(3.0) * (T::one() - ratio).powi(2) * ratio +
+                self.v3 * T::from_f64(3.0) * (T::one() - ratio) * ratio.powi(2) +
                 v4 * ratio.powi(3)
             },
             None => {
-                self.v1 * (1.0 - ratio).powi(2) +
-                self.v2 * 2.0 * (1.0 - ratio) * ratio +
+                self.v1 * (T::one() - ratio).powi(2) +
+                self.v2 * T::from_f64(2.0) * (T::one() - ratio) * ratio +
                 self.v3 * ratio.powi(2)
             }
         }
@@ -207,15 +209,41 @@ impl Bezier {
     ///
     /// assert!((b.len(20) - consts::PI / 2.0).abs() < EPSILON);
     /// ```
-    pub fn len(&self, steps: i32) -> f32 {
-        let (length, _) = (1..steps + 1).fold((0.0, self.v1), |(l, v), i| {
-            let n = self.interpolate((i as f32) / (steps as f32));
+    pub fn len(&self, steps: i32) -> T {
+        let (length, _) = (1..steps + 1).fold((T::zero(), self.v1), |(l, v), i| {
+            let n = self.interpolate(T::from_f64(i as f64) / T::from_f64(steps as f64));
 
             (l + v.dist(n), n)
         });
 
         length
     }
+
+    /// Converts a Bézier curve to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Bezier, DBezier, DVector, Vector};
+    /// let b = DBezier::new_sqr(DVector::zero(), DVector::new_unf(0.1), DVector::one());
+    ///
+    /// assert_eq!(b.cast::<f32>(), Bezier::new_sqr(Vector::zero(), Vector::new_unf(0.1),
+    ///                                             Vector::one()));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Bezier<U> {
+        Bezier {
+            v1: self.v1.cast(),
+            v2: self.v2.cast(),
+            v3: self.v3.cast(),
+            v4: self.v4.map(|v4| v4.cast())
+        }
+    }
+}
+
+impl From<Bezier<f32>> for Bezier<f64> {
+    fn from(bezier: Bezier<f32>) -> Bezier<f64> {
+        bezier.cast()
+    }
 }
 
 use mrusty::*;
@@ -259,15 +287,15 @@ mrusty_class!(Bezier, {
 
 /// A `struct` useful for creating a path of Bézier curves.
 #[derive(Clone, Debug, PartialEq)]
-pub struct BezierPath {
-    /// `Vec<Bezier>` of curves forming the path
-    pub curves: Vec<Bezier>,
-    /// `Vec<f32>` containing the lengths of the `Bezier` curves with the same indices;
+pub struct BezierPath<T = f32> {
+    /// `Vec<Bezier<T>>` of curves forming the path
+    pub curves: Vec<Bezier<T>>,
+    /// `Vec<T>` containing the lengths of the `Bezier` curves with the same indices;
     /// (normalized so that they add up to `1.0`)
-    pub lengths: Vec<f32>
+    pub lengths: Vec<T>
 }
 
-impl BezierPath {
+impl<T: Scalar> BezierPath<T> {
     /// Creates a Bézier path using `Bezier` curves. Curves must be connected.
     ///
     /// # Examples
@@ -291,15 +319,15 @@ impl BezierPath {
     ///     lengths: vec!(1.0)
     /// });
     /// ```
-    pub fn new(curves: Vec<Bezier>) -> BezierPath {
+    pub fn new(curves: Vec<Bezier<T>>) -> BezierPath<T> {
         const STEPS: i32 = 20;
 
-        let lengths: Vec<f32> = curves.iter().map(|c| c.len(STEPS)).collect();
-        let sum = lengths.iter().fold(0.0, |s, l| s + l);
+        let lengths: Vec<T> = curves.iter().map(|c| c.len(STEPS)).collect();
+        let sum = lengths.iter().fold(T::zero(), |s, &l| s + l);
 
         BezierPath {
             curves: curves,
-            lengths: lengths.iter().map(|l| l / sum).collect()
+            lengths: lengths.iter().map(|&l| l / sum).collect()
         }
     }
 
@@ -326,10 +354,10 @@ impl BezierPath {
     /// assert_eq!(p.interpolate(0.5), Vector::new(5.0, 5.0, 0.0));
     /// assert_eq!(p.interpolate(1.2), Vector::new(12.0, 12.0, 0.0));
     /// ```
-    pub fn interpolate(&self, ratio: f32) -> Vector {
-        let mut sum = 0.0;
+    pub fn interpolate(&self, ratio: T) -> Vector<T> {
+        let mut sum = T::zero();
 
-        let curve_length = self.curves.iter().zip(self.lengths.iter()).find(|&(_, l)| {
+        let curve_length = self.curves.iter().zip(self.lengths.iter()).find(|&(_, &l)| {
             if ratio <= sum + l {
                 true
             } else {
@@ -340,13 +368,13 @@ impl BezierPath {
         });
 
         let (curve, ratio) = match curve_length {
-            Some((curve, length)) => (curve, (ratio - sum) / length),
+            Some((curve, &length)) => (curve, (ratio - sum) / length),
             None                  => {
                 let curve = self.curves.last();
                 let length = self.lengths.last();
 
                 match (curve, length) {
-                    (Some(curve), Some(length)) => {
+                    (Some(curve), Some(&length)) => {
                         (curve, (ratio - sum + length) / length)
                     },
                     _ => panic!("Cannot interpolate an empty path.")
@@ -382,8 +410,8 @@ impl BezierPath {
     ///
     /// assert!((p.len(20) - 14.142137).abs() < EPSILON);
     /// ```
-    pub fn len(&self, steps: i32) -> f32 {
-        self.curves.iter().map(|curve| curve.len(steps)).fold(0.0, |s, l| s + l)
+    pub fn len(&self, steps: i32) -> T {
+        self.curves.iter().map(|curve| curve.len(steps)).fold(T::zero(), |s, l| s + l)
     }
 }
 
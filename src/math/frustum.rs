@@ -0,0 +1,400 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::aabb::Aabb;
+use super::matrix::{self, DepthRange, Matrix};
+use super::plane::Plane;
+use super::sphere::Sphere;
+use super::vector::Vector;
+
+/// An `enum` describing where a point or a bounding volume lies relative to a `Frustum`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Containment {
+    /// completely inside
+    Inside,
+    /// crossing at least one of the planes
+    Intersecting,
+    /// completely outside
+    Outside
+}
+
+/// A `struct` representing the volume visible through a view-projection matrix, bounded by six
+/// planes whose normals point inwards. `Frustum` holds `f32`s and `DFrustum` holds `f64`s.
+///
+/// Tests are conservative: a volume that lies outside the frustum but not completely outside
+/// any of its planes, e.g. next to one of its corners, is reported as intersecting.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Containment, DepthRange, Frustum, Matrix, Sphere, Vector};
+/// # use std::f32::consts;
+/// let proj = Matrix::new_persp(consts::PI / 2.0, 1.0, 1.0, 10.0, DepthRange::OpenGl);
+/// let view = Matrix::new_look_at(Vector::zero(), Vector::forward(), Vector::up());
+///
+/// let f = Frustum::from_matrix(proj * view, DepthRange::OpenGl);
+///
+/// assert_eq!(f.classify_point(Vector::new(0.0, 0.0, 5.0)), Containment::Inside);
+/// assert_eq!(f.classify_sphere(Sphere::new(Vector::zero(), 2.0)), Containment::Intersecting);
+/// assert_eq!(f.classify_point(Vector::new(0.0, 0.0, -5.0)), Containment::Outside);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum<T = f32> {
+    /// `[Plane<T>; 6]` left, right, bottom, top, near and far planes
+    pub planes: [Plane<T>; 6]
+}
+
+impl<T: Scalar> Frustum<T> {
+    /// Creates a frustum using its six planes, with normals pointing inwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Containment, Frustum, Plane, Vector};
+    /// let p = Plane::new(Vector::up(), -1.0);
+    /// let f = Frustum::new([p; 6]);
+    ///
+    /// assert_eq!(f.classify_point(Vector::zero()), Containment::Inside);
+    /// ```
+    pub fn new(planes: [Plane<T>; 6]) -> Frustum<T> {
+        Frustum { planes: planes }
+    }
+
+    /// Extracts the frustum of a projection or view-projection matrix whose clip space *z* lies
+    /// in the `depth` range. Reversed-depth matrices work too; their near and far planes are
+    /// swapped. The far plane of an infinite projection has a zero normal and never culls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Containment, DepthRange, Frustum, Matrix, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::Vulkan);
+    /// let f = Frustum::from_matrix(m, DepthRange::Vulkan);
+    ///
+    /// assert_eq!(f.planes[4].normal, Vector::back());
+    /// assert_eq!(f.classify_point(Vector::new(0.5, 0.5, -2.0)), Containment::Inside);
+    /// assert_eq!(f.classify_point(Vector::new(0.5, 0.5, -4.0)), Containment::Outside);
+    /// ```
+    pub fn from_matrix(matrix: Matrix<T>, depth: DepthRange) -> Frustum<T> {
+        let m = matrix.array;
+        let row = |i: usize| [m[i], m[i + 4], m[i + 8], m[i + 12]];
+
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        let near = match depth {
+            DepthRange::OpenGl => add(w, z),
+            DepthRange::Vulkan => z
+        };
+
+        Frustum {
+            planes: [
+                plane(add(w, x)),
+                plane(sub(w, x)),
+                plane(add(w, y)),
+                plane(sub(w, y)),
+                plane(near),
+                plane(sub(w, z))
+            ]
+        }
+    }
+
+    /// Classifies a point as either inside or outside the frustum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Containment, DepthRange, Frustum, Matrix, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::OpenGl);
+    /// let f = Frustum::from_matrix(m, DepthRange::OpenGl);
+    ///
+    /// assert_eq!(f.classify_point(Vector::new(1.0, 0.0, -1.0)), Containment::Inside);
+    /// assert_eq!(f.classify_point(Vector::new(2.0, 0.0, -1.0)), Containment::Outside);
+    /// ```
+    pub fn classify_point(&self, point: Vector<T>) -> Containment {
+        if self.planes.iter().all(|plane| plane.dist(point) >= T::zero()) {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// Classifies a sphere relative to the frustum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Containment, DepthRange, Frustum, Matrix, Sphere, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::OpenGl);
+    /// let f = Frustum::from_matrix(m, DepthRange::OpenGl);
+    ///
+    /// let s = Sphere::new(Vector::new(0.0, 0.0, -2.0), 0.5);
+    ///
+    /// assert_eq!(f.classify_sphere(s), Containment::Inside);
+    /// ```
+    pub fn classify_sphere(&self, sphere: Sphere<T>) -> Containment {
+        self.classify(sphere.center, |_| sphere.radius)
+    }
+
+    /// Classifies an axis-aligned box relative to the frustum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Containment, DepthRange, Frustum, Matrix, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::OpenGl);
+    /// let f = Frustum::from_matrix(m, DepthRange::OpenGl);
+    ///
+    /// let b = Aabb::new(Vector::new(0.5, 0.5, -2.0), Vector::new(1.5, 1.5, -1.5));
+    ///
+    /// assert_eq!(f.classify_aabb(b), Containment::Intersecting);
+    /// ```
+    pub fn classify_aabb(&self, aabb: Aabb<T>) -> Containment {
+        let extents = aabb.half_extents();
+
+        self.classify(aabb.center(), |normal| radius(normal, extents))
+    }
+
+    /// Returns the indices of the spheres that are not completely outside the frustum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Frustum, Matrix, Sphere, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::OpenGl);
+    /// let f = Frustum::from_matrix(m, DepthRange::OpenGl);
+    ///
+    /// let spheres = [
+    ///     Sphere::new(Vector::new(0.0, 0.0, -2.0), 0.5),
+    ///     Sphere::new(Vector::new(3.0, 0.0, -2.0), 0.5),
+    ///     Sphere::new(Vector::new(1.25, 0.0, -2.0), 0.5)
+    /// ];
+    ///
+    /// assert_eq!(f.cull_spheres(&spheres), vec![0, 2]);
+    /// ```
+    pub fn cull_spheres(&self, spheres: &[Sphere<T>]) -> Vec<usize> {
+        let planes = self.planes;
+
+        spheres.iter().enumerate().filter(|&(_, sphere)| {
+            planes.iter().all(|plane| plane.dist(sphere.center) >= -sphere.radius)
+        }).map(|(i, _)| i).collect()
+    }
+
+    /// Returns the indices of the axis-aligned boxes that are not completely outside the
+    /// frustum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, DepthRange, Frustum, Matrix, Vector};
+    /// let m = Matrix::new_ortho(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, DepthRange::OpenGl);
+    /// let f = Frustum::from_matrix(m, DepthRange::OpenGl);
+    ///
+    /// let boxes = [
+    ///     Aabb::new(Vector::new(2.0, 0.0, -2.0), Vector::new(3.0, 1.0, -1.0)),
+    ///     Aabb::new(Vector::new(0.0, 0.0, -2.0), Vector::new(1.0, 1.0, -1.0))
+    /// ];
+    ///
+    /// assert_eq!(f.cull_aabbs(&boxes), vec![1]);
+    /// ```
+    pub fn cull_aabbs(&self, aabbs: &[Aabb<T>]) -> Vec<usize> {
+        let planes = self.planes;
+        let mut abs_normals = [Vector::zero(); 6];
+
+        for (abs, plane) in abs_normals.iter_mut().zip(planes.iter()) {
+            *abs = Vector::new(plane.normal.x.abs(), plane.normal.y.abs(), plane.normal.z.abs());
+        }
+
+        aabbs.iter().enumerate().filter(|&(_, aabb)| {
+            let center = aabb.center();
+            let extents = aabb.half_extents();
+
+            planes.iter().zip(abs_normals.iter()).all(|(plane, abs)| {
+                plane.dist(center) >= -extents.dot(*abs)
+            })
+        }).map(|(i, _)| i).collect()
+    }
+
+    /// Converts a frustum to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DFrustum, DPlane, DVector};
+    /// let f = DFrustum::new([DPlane::new(DVector::up(), 1.0); 6]);
+    ///
+    /// assert_eq!(f.cast::<f32>().planes[0].distance, 1.0);
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Frustum<U> {
+        let mut planes = [Plane::new(Vector::up(), U::zero()); 6];
+
+        for (cast, plane) in planes.iter_mut().zip(self.planes.iter()) {
+            *cast = plane.cast();
+        }
+
+        Frustum { planes: planes }
+    }
+
+    fn classify<F>(&self, center: Vector<T>, radius: F) -> Containment
+        where F: Fn(Vector<T>) -> T {
+        let mut result = Containment::Inside;
+
+        for plane in self.planes.iter() {
+            let dist = plane.dist(center);
+            let radius = radius(plane.normal);
+
+            if dist < -radius {
+                return Containment::Outside;
+            }
+
+            if dist < radius {
+                result = Containment::Intersecting;
+            }
+        }
+
+        result
+    }
+}
+
+fn add<T: Scalar>(l: [T; 4], r: [T; 4]) -> [T; 4] {
+    [l[0] + r[0], l[1] + r[1], l[2] + r[2], l[3] + r[3]]
+}
+
+fn sub<T: Scalar>(l: [T; 4], r: [T; 4]) -> [T; 4] {
+    [l[0] - r[0], l[1] - r[1], l[2] - r[2], l[3] - r[3]]
+}
+
+// Converts the (a, b, c, d) coefficients of a * x + b * y + c * z + d >= 0 into a plane. Planes
+// at infinity get a zero normal and an infinitely negative distance so that they contain
+// everything.
+fn plane<T: Scalar>(coefficients: [T; 4]) -> Plane<T> {
+    let normal = Vector::new(coefficients[0], coefficients[1], coefficients[2]);
+
+    if normal.len() > T::from_f64(1e-6) * coefficients[3].abs() {
+        Plane::new(normal, -coefficients[3])
+    } else {
+        Plane {
+            normal: Vector::zero(),
+            distance: T::from_f64(-f64::INFINITY)
+        }
+    }
+}
+
+fn radius<T: Scalar>(normal: Vector<T>, extents: Vector<T>) -> T {
+    extents.x * normal.x.abs() + extents.y * normal.y.abs() + extents.z * normal.z.abs()
+}
+
+use mrusty::*;
+
+impl From<Frustum<f32>> for Frustum<f64> {
+    fn from(frustum: Frustum<f32>) -> Frustum<f64> {
+        frustum.cast()
+    }
+}
+
+fn containment(mruby: &MrubyType, containment: Containment) -> Value {
+    match containment {
+        Containment::Inside       => mruby.symbol("inside"),
+        Containment::Intersecting => mruby.symbol("intersecting"),
+        Containment::Outside      => mruby.symbol("outside")
+    }
+}
+
+mrusty_class!(Frustum, {
+    def!("initialize", |mruby; args| {
+        let matrix = match args.first().map(|matrix| matrix.to_obj::<Matrix>()) {
+            Some(Ok(matrix)) => (*matrix).clone(),
+            _                => return mruby.raise("ArgumentError", "expecting Matrix")
+        };
+
+        let depth = match matrix::depth_range(mruby.clone(), &args[1..]) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        Frustum::from_matrix(matrix, depth)
+    });
+
+    def!("planes", |mruby, slf: Frustum| {
+        mruby.array(slf.planes.iter().map(|plane| mruby.obj(*plane)).collect())
+    });
+
+    def!("classify", |mruby, slf: Frustum, volume: Value| {
+        if let Ok(point) = volume.to_obj::<Vector>() {
+            containment(&mruby, slf.classify_point((*point).clone()))
+        } else if let Ok(sphere) = volume.to_obj::<Sphere>() {
+            containment(&mruby, slf.classify_sphere((*sphere).clone()))
+        } else if let Ok(aabb) = volume.to_obj::<Aabb>() {
+            containment(&mruby, slf.classify_aabb((*aabb).clone()))
+        } else {
+            mruby.raise("ArgumentError", "expecting Vector, Sphere or Aabb")
+        }
+    });
+
+    def!("cull", |mruby, slf: Frustum, volumes: Vec| {
+        let mut visible = vec![];
+
+        for (i, volume) in volumes.iter().enumerate() {
+            let containment = if let Ok(sphere) = volume.to_obj::<Sphere>() {
+                slf.classify_sphere((*sphere).clone())
+            } else if let Ok(aabb) = volume.to_obj::<Aabb>() {
+                slf.classify_aabb((*aabb).clone())
+            } else {
+                return mruby.raise("ArgumentError", "expecting Array of Sphere or Aabb");
+            };
+
+            if containment != Containment::Outside {
+                visible.push(mruby.fixnum(i as i32));
+            }
+        }
+
+        mruby.array(visible)
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Frustum;
+    use super::super::{Aabb, Matrix, Plane, Sphere, Vector};
+
+    describe!(Frustum, (Aabb, Matrix, Plane, Sphere, Vector), "
+      context 'when orthographic' do
+        subject do
+          Frustum.new Matrix.orthographic(-1.0, 1.0, -1.0, 1.0, 1.0, 3.0, :vulkan), :vulkan
+        end
+
+        it 'returns six planes on #planes' do
+          expect(subject.planes.size).to eql 6
+          expect(subject.planes[4].normal).to eql Vector.back
+        end
+
+        it 'classifies points on #classify' do
+          expect(subject.classify Vector.new(0.0, 0.0, -2.0)).to eql :inside
+          expect(subject.classify Vector.zero).to eql :outside
+        end
+
+        it 'classifies spheres on #classify' do
+          expect(subject.classify Sphere.new(Vector.new(1.0, 0.0, -2.0), 0.5)).to eql :intersecting
+        end
+
+        it 'classifies boxes on #classify' do
+          expect(subject.classify Aabb.new(Vector.new(2.0, 0.0, -2.0), Vector.one)).to eql :outside
+        end
+
+        it 'returns visible indices on #cull' do
+          volumes = [
+            Sphere.new(Vector.new(3.0, 0.0, -2.0), 0.5),
+            Aabb.new(Vector.new(0.0, 0.0, -2.0), Vector.one)
+          ]
+
+          expect(subject.cull volumes).to eql [1]
+        end
+      end
+    ");
+}
@@ -0,0 +1,75 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use mrusty::*;
+
+use math::Scalar;
+use super::plane::Plane;
+use super::vector::Vector;
+
+/// A `struct` describing an intersection. When a ray hits a shape, `distance` is measured along
+/// the ray and `normal` is the normal of the surface that was hit. When two shapes overlap,
+/// `distance` is the penetration depth and `normal` the direction in which the first shape has to
+/// move by `distance` to stop overlapping the second one.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Hit, Ray, Sphere, Vector};
+/// let ray = Ray::new(Vector::zero(), Vector::forward());
+/// let sphere = Sphere::new(Vector::new(0.0, 0.0, 5.0), 1.0);
+///
+/// assert_eq!(ray.hit_sphere(sphere), Some(Hit::new(4.0, Vector::back())));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit<T = f32> {
+    /// `T` distance along the ray or penetration depth
+    pub distance: T,
+    /// `Vector<T>` normalized surface normal or separation direction
+    pub normal: Vector<T>
+}
+
+impl<T: Scalar> Hit<T> {
+    /// Creates a hit using a distance and a normal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Vector};
+    /// let hit = Hit::new(1.0, Vector::up());
+    ///
+    /// assert_eq!(hit, Hit { distance: 1.0, normal: Vector::up() });
+    /// ```
+    pub fn new(distance: T, normal: Vector<T>) -> Hit<T> {
+        Hit {
+            distance: distance,
+            normal: normal
+        }
+    }
+}
+
+/// Checks whether a convex shape reaching `radius` along the normal of `plane` from `center`
+/// crosses it. The `Hit` moves the shape to the side of the plane `center` is on.
+pub fn hit_plane<T: Scalar>(plane: Plane<T>, center: Vector<T>, radius: T) -> Option<Hit<T>> {
+    let dist = plane.dist(center);
+
+    if dist.abs() >= radius {
+        None
+    } else if dist < T::zero() {
+        Some(Hit::new(radius + dist, -plane.normal))
+    } else {
+        Some(Hit::new(radius - dist, plane.normal))
+    }
+}
+
+/// Converts an optional `Hit` to an mruby `[distance, normal]` `Array`, or `nil`.
+pub fn to_value(mruby: &MrubyType, hit: Option<Hit>) -> Value {
+    match hit {
+        Some(hit) => mruby.array(vec![mruby.float(hit.distance as f64), mruby.obj(hit.normal)]),
+        None      => mruby.nil()
+    }
+}
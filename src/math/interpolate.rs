@@ -6,7 +6,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 /// A `trait` to be implemented by `struct`ures that need to handle 2-way
-/// interpolation. (not necessarily linear)
+/// interpolation. (not necessarily linear) `T` is the type of the ratio, `f32` by default.
 ///
 /// # Examples
 /// ```
@@ -23,6 +23,6 @@
 ///     }
 /// }
 /// ```
-pub trait Interpolate {
-    fn interpolate(&self, other: Self, ratio: f32) -> Self;
+pub trait Interpolate<T = f32> {
+    fn interpolate(&self, other: Self, ratio: T) -> Self;
 }
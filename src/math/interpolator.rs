@@ -5,9 +5,10 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::f32::consts;
+use math::Scalar;
 
-/// A `struct` useful to easily compute interpolation ratios.
+/// A `struct` useful to easily compute interpolation ratios. `Interpolator` works with `f32`s and
+/// `DInterpolator` with `f64`s.
 ///
 /// # Examples
 ///
@@ -49,11 +50,11 @@ use std::f32::consts;
 /// assert!((q1.angle(qi) - consts::PI / 4.0).abs() < EPSILON);
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Interpolator {
-    /// `f32` specifying the starting time of interpolation (maps to `0.0`)
-    pub start: f32,
-    /// `f32` specifying the duration of interpolation (`start + duration` maps to `0.0`)
-    pub duration: f32,
+pub struct Interpolator<T = f32> {
+    /// `T` specifying the starting time of interpolation (maps to `0.0`)
+    pub start: T,
+    /// `T` specifying the duration of interpolation (`start + duration` maps to `0.0`)
+    pub duration: T,
     /// `Behavior` of the interpolation
     pub behavior: Behavior
 }
@@ -71,7 +72,7 @@ pub enum Behavior {
     AccDec
 }
 
-impl Interpolator {
+impl<T: Scalar> Interpolator<T> {
     /// Creates an interpolator by defining its starting time, duration and behavior.
     ///
     /// # Examples
@@ -83,7 +84,7 @@ impl Interpolator {
     ///
     /// assert_eq!(i, Interpolator { start: 0.0, duration: 10.0, behavior: Behavior::Linear });
     /// ```
-    pub fn new(start: f32, duration: f32, behavior: Behavior) -> Interpolator {
+    pub fn new(start: T, duration: T, behavior: Behavior) -> Interpolator<T> {
         Interpolator {
             start: start,
             duration: duration,
@@ -108,22 +109,48 @@ impl Interpolator {
     /// assert_eq!(dec.ratio(0.25), 0.4375);
     /// assert_eq!(acd.ratio(0.25), 0.14644668);
     /// ```
-    pub fn ratio(&self, time: f32) -> f32 {
+    pub fn ratio(&self, time: T) -> T {
         let ratio = self.convert(time);
 
         match self.behavior {
             Behavior::Linear => ratio,
             Behavior::Acc    => ratio.powi(2),
-            Behavior::Dec    => 1.0 - (1.0 - ratio).powi(2),
-            Behavior::AccDec => ((ratio + 1.0) * consts::PI).cos() / 2.0 + 0.5
+            Behavior::Dec    => T::one() - (T::one() - ratio).powi(2),
+            Behavior::AccDec => {
+                ((ratio + T::one()) * T::pi()).cos() / T::from_f64(2.0) + T::from_f64(0.5)
+            }
+        }
+    }
+
+    /// Converts an interpolator to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Behavior, DInterpolator, Interpolator};
+    /// let i = DInterpolator::new(0.1, 0.2, Behavior::Acc);
+    ///
+    /// assert_eq!(i.cast::<f32>(), Interpolator::new(0.1, 0.2, Behavior::Acc));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Interpolator<U> {
+        Interpolator {
+            start: U::from_f64(self.start.to_f64()),
+            duration: U::from_f64(self.duration.to_f64()),
+            behavior: self.behavior
         }
     }
 
-    fn convert(&self, time: f32) -> f32 {
+    fn convert(&self, time: T) -> T {
         (time - self.start) / self.duration
     }
 }
 
+impl From<Interpolator<f32>> for Interpolator<f64> {
+    fn from(interpolator: Interpolator<f32>) -> Interpolator<f64> {
+        interpolator.cast()
+    }
+}
+
 use mrusty::*;
 
 mrusty_class!(Interpolator, {
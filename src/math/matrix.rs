@@ -5,9 +5,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use math::{Quaternion, Vector};
-
-/// A simple matrix `struct` tailored specifically for graphics.
+use math::Scalar;
+use super::quaternion::Quaternion;
+use super::simd;
+use super::vector::Vector;
+use super::vector4::Vector4;
+
+/// A simple matrix `struct` tailored specifically for graphics. `Matrix` holds `f32`s and
+/// `DMatrix` holds `f64`s.
 ///
 /// # Examples
 ///
@@ -25,12 +30,31 @@ use math::{Quaternion, Vector};
 /// assert_eq!(inv * m, Matrix::ident());
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Matrix {
-    /// `[f32; 16]` containing values; columns incremented first
-    pub array: [f32; 16]
+pub struct Matrix<T = f32> {
+    /// `[T; 16]` containing values; columns incremented first
+    pub array: [T; 16]
 }
 
-impl Matrix {
+/// An `enum` containing the clip space depth ranges projection matrices can map to. Both place
+/// the near plane at the start of the range unless reversed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthRange {
+    /// OpenGL, *z* between `-1.0` and `1.0`
+    OpenGl,
+    /// Vulkan, *z* between `0.0` and `1.0`
+    Vulkan
+}
+
+impl DepthRange {
+    fn bounds<T: Scalar>(&self) -> (T, T) {
+        match *self {
+            DepthRange::OpenGl => (-T::one(), T::one()),
+            DepthRange::Vulkan => (T::zero(), T::one())
+        }
+    }
+}
+
+impl<T: Scalar> Matrix<T> {
     /// Creates a matrix using a length 16 array. (columns incremented first)
     ///
     /// # Examples
@@ -41,7 +65,7 @@ impl Matrix {
     ///
     /// assert_eq!(m, Matrix { array: [1.0; 16] });
     /// ```
-    pub fn new(array: [f32; 16]) -> Matrix {
+    pub fn new(array: [T; 16]) -> Matrix<T> {
         Matrix { array: array }
     }
 
@@ -55,17 +79,195 @@ impl Matrix {
     ///
     /// assert_eq!(m * Matrix::ident(), Matrix { array: [2.0; 16] });
     /// ```
-    pub fn ident() -> Matrix {
-        let mut array = [0.0; 16];
+    pub fn ident() -> Matrix<T> {
+        let mut array = [T::zero(); 16];
+
+        array[0]  = T::one();
+        array[5]  = T::one();
+        array[10] = T::one();
+        array[15] = T::one();
+
+        Matrix { array: array }
+    }
+
+    /// Creates a perspective projection matrix from a vertical field of view in radians, an aspect
+    /// ratio (width / height) and the distances to the near and far planes.
+    ///
+    /// Projection matrices are right-handed: the camera looks towards *-z* with *+y* up, and
+    /// points in front of it end up with *z* inside the `depth` range. Vulkan's *y* axis points
+    /// down in clip space; flip the viewport to account for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// # use std::f32::consts;
+    /// let m = Matrix::new_persp(consts::PI / 2.0, 1.0, 1.0, 10.0, DepthRange::OpenGl);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!(((m * Vector::new(1.0, 0.0, -1.0)).x - 1.0).abs() < EPSILON);
+    /// assert!(((m * Vector::new(0.0, 0.0, -1.0)).z + 1.0).abs() < EPSILON);
+    /// assert!(((m * Vector::new(0.0, 0.0, -10.0)).z - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn new_persp(fovy: T, aspect: T, near: T, far: T, depth: DepthRange) -> Matrix<T> {
+        let (x_scale, y_scale) = Matrix::persp_scales(fovy, aspect);
+
+        Matrix::persp(x_scale, y_scale, T::zero(), T::zero(), near, Some(far), depth.bounds())
+    }
+
+    /// Creates a perspective projection matrix like `new_persp` whose far plane is infinitely far
+    /// away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// # use std::f32::consts;
+    /// let m = Matrix::new_persp_inf(consts::PI / 2.0, 1.0, 1.0, DepthRange::Vulkan);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((m * Vector::new(0.0, 0.0, -1.0)).z.abs() < EPSILON);
+    /// assert!((m * Vector::new(0.0, 0.0, -1000000.0)).z < 1.0);
+    /// ```
+    pub fn new_persp_inf(fovy: T, aspect: T, near: T, depth: DepthRange) -> Matrix<T> {
+        let (x_scale, y_scale) = Matrix::persp_scales(fovy, aspect);
+
+        Matrix::persp(x_scale, y_scale, T::zero(), T::zero(), near, None, depth.bounds())
+    }
+
+    /// Creates a perspective projection matrix like `new_persp` with reversed depth: the near
+    /// plane maps to the end of the `depth` range and the far plane to its start. Paired with
+    /// `DepthRange::Vulkan` and a floating point depth buffer, this spreads precision evenly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// # use std::f32::consts;
+    /// let m = Matrix::new_persp_rev(consts::PI / 2.0, 1.0, 1.0, 10.0, DepthRange::Vulkan);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!(((m * Vector::new(0.0, 0.0, -1.0)).z - 1.0).abs() < EPSILON);
+    /// assert!((m * Vector::new(0.0, 0.0, -10.0)).z.abs() < EPSILON);
+    /// ```
+    pub fn new_persp_rev(fovy: T, aspect: T, near: T, far: T, depth: DepthRange) -> Matrix<T> {
+        let (x_scale, y_scale) = Matrix::persp_scales(fovy, aspect);
+        let (start, end) = depth.bounds();
+
+        Matrix::persp(x_scale, y_scale, T::zero(), T::zero(), near, Some(far), (end, start))
+    }
+
+    /// Creates a perspective projection matrix with reversed depth like `new_persp_rev` whose far
+    /// plane is infinitely far away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// # use std::f32::consts;
+    /// let m = Matrix::new_persp_inf_rev(consts::PI / 2.0, 1.0, 1.0, DepthRange::Vulkan);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!(((m * Vector::new(0.0, 0.0, -1.0)).z - 1.0).abs() < EPSILON);
+    /// assert!((m * Vector::new(0.0, 0.0, -1000000.0)).z > 0.0);
+    /// ```
+    pub fn new_persp_inf_rev(fovy: T, aspect: T, near: T, depth: DepthRange) -> Matrix<T> {
+        let (x_scale, y_scale) = Matrix::persp_scales(fovy, aspect);
+        let (start, end) = depth.bounds();
 
-        array[0]  = 1.0;
-        array[5]  = 1.0;
-        array[10] = 1.0;
-        array[15] = 1.0;
+        Matrix::persp(x_scale, y_scale, T::zero(), T::zero(), near, None, (end, start))
+    }
+
+    /// Creates a perspective projection matrix from the planes of its view frustum. `left`,
+    /// `right`, `bottom` and `top` are measured on the near plane. Conventions are the same as
+    /// `new_persp`'s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// let m = Matrix::new_frustum(-1.0, 3.0, -1.0, 1.0, 1.0, 10.0, DepthRange::OpenGl);
+    ///
+    /// assert_eq!((m * Vector::new(3.0, 1.0, -1.0)).x, 1.0);
+    /// assert_eq!((m * Vector::new(3.0, 1.0, -1.0)).y, 1.0);
+    /// ```
+    pub fn new_frustum(left: T, right: T, bottom: T, top: T, near: T, far: T,
+                       depth: DepthRange) -> Matrix<T> {
+        let two = T::from_f64(2.0);
+
+        Matrix::persp(
+            two * near / (right - left),
+            two * near / (top - bottom),
+            (right + left) / (right - left),
+            (top + bottom) / (top - bottom),
+            near,
+            Some(far),
+            depth.bounds()
+        )
+    }
+
+    /// Creates an orthographic projection matrix from the planes of its view box. Conventions are
+    /// the same as `new_persp`'s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DepthRange, Matrix, Vector};
+    /// let m = Matrix::new_ortho(0.0, 4.0, 0.0, 2.0, 1.0, 3.0, DepthRange::Vulkan);
+    ///
+    /// assert_eq!(m * Vector::new(0.0, 0.0, -1.0), Vector::new(-1.0, -1.0, 0.0));
+    /// assert_eq!(m * Vector::new(4.0, 2.0, -3.0), Vector::new(1.0, 1.0, 1.0));
+    /// ```
+    pub fn new_ortho(left: T, right: T, bottom: T, top: T, near: T, far: T,
+                     depth: DepthRange) -> Matrix<T> {
+        let two = T::from_f64(2.0);
+        let (start, end): (T, T) = depth.bounds();
+        let z_scale = (start - end) / (far - near);
+
+        let mut array = [T::zero(); 16];
+
+        array[0]  = two / (right - left);
+        array[5]  = two / (top - bottom);
+        array[10] = z_scale;
+        array[12] = -(right + left) / (right - left);
+        array[13] = -(top + bottom) / (top - bottom);
+        array[14] = start + z_scale * near;
+        array[15] = T::one();
 
         Matrix { array: array }
     }
 
+    /// Creates a right-handed view matrix of a camera placed at `eye` looking towards `target`.
+    /// `up` is the approximate upwards direction; it must not be parallel to the view direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::new_look_at(Vector::new(0.0, 0.0, 5.0), Vector::zero(), Vector::up());
+    ///
+    /// assert_eq!(m * Vector::zero(), Vector::new(0.0, 0.0, -5.0));
+    /// assert_eq!(m * Vector::new(1.0, 2.0, 5.0), Vector::new(1.0, 2.0, 0.0));
+    /// ```
+    pub fn new_look_at(eye: Vector<T>, target: Vector<T>, up: Vector<T>) -> Matrix<T> {
+        let f = (target - eye).norm();
+        let s = f.cross(up).norm();
+        let u = s.cross(f);
+
+        Matrix {
+            array: [
+                s.x,         u.x,         -f.x,       T::zero(),
+                s.y,         u.y,         -f.y,       T::zero(),
+                s.z,         u.z,         -f.z,       T::zero(),
+                -s.dot(eye), -u.dot(eye), f.dot(eye), T::one()
+            ]
+        }
+    }
+
     /// Translates a matrix according to the scale represented by a vector.
     /// The translation is applied to the left. (`t * m`)
     ///
@@ -79,7 +281,7 @@ impl Matrix {
     ///
     /// assert_eq!(m * v, Vector { x: 2.0, y: 1.0, z: 2.0 });
     /// ```
-    pub fn trans(&self, vector: Vector) -> Matrix {
+    pub fn trans(&self, vector: Vector<T>) -> Matrix<T> {
         let m = self.array;
         let v = vector;
 
@@ -118,7 +320,7 @@ impl Matrix {
     ///
     /// assert_eq!(m * v, Vector { x: 4.0, y: 6.0, z: 8.0 });
     /// ```
-    pub fn scale(&self, vector: Vector) -> Matrix {
+    pub fn scale(&self, vector: Vector<T>) -> Matrix<T> {
         let m = self.array;
         let v = vector;
 
@@ -158,27 +360,28 @@ impl Matrix {
     ///
     /// assert_eq!(Matrix::ident().rot(q) * v, Vector { x: -1.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn rot(self, quaternion: Quaternion) -> Matrix {
+    pub fn rot(self, quaternion: Quaternion<T>) -> Matrix<T> {
         let q = quaternion;
+        let two = T::from_f64(2.0);
 
         let m = Matrix {
             array: [
-                1.0 - 2.0 * (q.y.powi(2) + q.z.powi(2)),
-                2.0 * (q.x * q.y + q.z * q.w),
-                2.0 * (q.x * q.z - q.y * q.w),
-                0.0,
-                2.0 * (q.x * q.y - q.z * q.w),
-                1.0 - 2.0 * (q.x.powi(2) + q.z.powi(2)),
-                2.0 * (q.y * q.z + q.x * q.w),
-                0.0,
-                2.0 * (q.x * q.z + q.y * q.w),
-                2.0 * (q.y * q.z - q.x * q.w),
-                1.0 - 2.0 * (q.x.powi(2) + q.y.powi(2)),
-                0.0,
-                0.0,
-                0.0,
-                0.0,
-                1.0
+                T::one() - two * (q.y.powi(2) + q.z.powi(2)),
+                two * (q.x * q.y + q.z * q.w),
+                two * (q.x * q.z - q.y * q.w),
+                T::zero(),
+                two * (q.x * q.y - q.z * q.w),
+                T::one() - two * (q.x.powi(2) + q.z.powi(2)),
+                two * (q.y * q.z + q.x * q.w),
+                T::zero(),
+                two * (q.x * q.z + q.y * q.w),
+                two * (q.y * q.z - q.x * q.w),
+                T::one() - two * (q.x.powi(2) + q.y.powi(2)),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::zero(),
+                T::one()
             ]
         };
 
@@ -200,61 +403,252 @@ impl Matrix {
     ///
     /// assert_eq!(Matrix::ident().rot_around(q, p) * v, Vector { x: 3.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn rot_around(&self, quaternion: Quaternion, point: Vector) -> Matrix {
+    pub fn rot_around(&self, quaternion: Quaternion<T>, point: Vector<T>) -> Matrix<T> {
         self.trans(-point).rot(quaternion).trans(point)
     }
 
-    /// Inverts a matrix.
+    /// Transforms points in place, like multiplying each of them by the matrix. `f32` matrices
+    /// use SIMD where available.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use anima_engine::math::Matrix;
-    /// assert_eq!(Matrix::ident().inv(), Matrix::ident());
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().scale(Vector::new_unf(2.0)).trans(Vector::up());
+    /// let mut points = [Vector::zero(), Vector::one()];
+    ///
+    /// m.trans_points(&mut points);
+    ///
+    /// assert_eq!(points, [Vector::up(), Vector::new(2.0, 3.0, 2.0)]);
+    /// ```
+    pub fn trans_points(&self, points: &mut [Vector<T>]) {
+        T::trans_points(&self.array, points);
+    }
+
+    /// Transforms directions in place. Directions get a *w* of 0.0, so translation does not
+    /// affect them. `f32` matrices use SIMD where available.
+    ///
+    /// # Examples
+    ///
     /// ```
-    pub fn inv(&self) -> Matrix {
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().scale(Vector::new_unf(2.0)).trans(Vector::up());
+    /// let mut dirs = [Vector::zero(), Vector::one()];
+    ///
+    /// m.trans_dirs(&mut dirs);
+    ///
+    /// assert_eq!(dirs, [Vector::zero(), Vector::new_unf(2.0)]);
+    /// ```
+    pub fn trans_dirs(&self, dirs: &mut [Vector<T>]) {
+        T::trans_dirs(&self.array, dirs);
+    }
+
+    /// Transposes a matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().trans(Vector::new(1.0, 2.0, 3.0)).transpose();
+    ///
+    /// assert_eq!(&m.array[12..], &[0.0, 0.0, 0.0, 1.0]);
+    /// assert_eq!(&m.array[3..4], &[1.0]);
+    /// ```
+    pub fn transpose(&self) -> Matrix<T> {
         let m = self.array;
 
-        let s0 = m[0] * m[5]  - m[1] * m[4];
-        let s1 = m[0] * m[9]  - m[1] * m[8];
-        let s2 = m[0] * m[13] - m[1] * m[12];
-        let s3 = m[4] * m[9]  - m[5] * m[8];
-        let s4 = m[4] * m[13] - m[5] * m[12];
-        let s5 = m[8] * m[13] - m[9] * m[12];
+        Matrix {
+            array: [
+                m[0], m[4], m[8],  m[12],
+                m[1], m[5], m[9],  m[13],
+                m[2], m[6], m[10], m[14],
+                m[3], m[7], m[11], m[15]
+            ]
+        }
+    }
 
-        let c5 = m[10] * m[15] - m[11] * m[14];
-        let c4 = m[6]  * m[15] - m[7]  * m[14];
-        let c3 = m[6]  * m[11] - m[7]  * m[10];
-        let c2 = m[2]  * m[15] - m[3]  * m[14];
-        let c1 = m[2]  * m[11] - m[3]  * m[10];
-        let c0 = m[2]  * m[7]  - m[3]  * m[6];
+    /// Computes the determinant of a matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().scale(Vector::new(1.0, 2.0, 3.0));
+    ///
+    /// assert_eq!(m.det(), 6.0);
+    /// assert_eq!(Matrix::new([1.0; 16]).det(), 0.0);
+    /// ```
+    pub fn det(&self) -> T {
+        simd::det(&self.array)
+    }
 
-        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+    /// Inverts a matrix. Panics if the matrix is not invertible; see `try_inv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Matrix;
+    /// assert_eq!(Matrix::ident().inv(), Matrix::ident());
+    /// ```
+    pub fn inv(&self) -> Matrix<T> {
+        match self.try_inv() {
+            Some(inv) => inv,
+            None      => panic!("Matrix {:?} is not invertable.", self.array)
+        }
+    }
 
-        if det == 0.0 { panic!("Matrix {:?} is not invertable.", m); }
+    /// Inverts a matrix, returning `None` if its determinant is `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().scale(Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(m.try_inv(), Some(Matrix::ident().scale(Vector::new_unf(0.5))));
+    /// assert_eq!(Matrix::new([0.0; 16]).try_inv(), None);
+    /// ```
+    pub fn try_inv(&self) -> Option<Matrix<T>> {
+        T::inv_mat(&self.array).map(Matrix::new)
+    }
 
-        let inv_det = det.recip();
+    /// Computes the matrix that transforms normals, the inverse transpose of the rotation and
+    /// scale part of a matrix. Translation is dropped. Returns `None` if the matrix is not
+    /// invertible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Vector};
+    /// let m = Matrix::ident().scale(Vector::new(2.0, 1.0, 1.0)).trans(Vector::one());
+    ///
+    /// // The normal of the x = y plane, scaled along x.
+    /// let normal = m.normal().unwrap() * Vector::new(1.0, -1.0, 0.0);
+    ///
+    /// assert_eq!(normal, Vector::new(0.5, -1.0, 0.0));
+    /// ```
+    pub fn normal(&self) -> Option<Matrix<T>> {
+        let m = self.array;
+        let zero = T::zero();
 
-        Matrix {
+        let linear = Matrix {
             array: [
-                ( m[5] * c5 - m[9]  * c4 + m[13] * c3) * inv_det,
-                (-m[1] * c5 + m[9]  * c2 - m[13] * c1) * inv_det,
-                ( m[1] * c4 - m[5]  * c2 + m[13] * c0) * inv_det,
-                (-m[1] * c3 + m[5]  * c1 - m[9]  * c0) * inv_det,
-                (-m[4] * c5 + m[8]  * c4 - m[12] * c3) * inv_det,
-                ( m[0] * c5 - m[8]  * c2 + m[12] * c1) * inv_det,
-                (-m[0] * c4 + m[4]  * c2 - m[12] * c0) * inv_det,
-                ( m[0] * c3 - m[4]  * c1 + m[8]  * c0) * inv_det,
-                ( m[7] * s5 - m[11] * s4 + m[15] * s3) * inv_det,
-                (-m[3] * s5 + m[11] * s2 - m[15] * s1) * inv_det,
-                ( m[3] * s4 - m[7]  * s2 + m[15] * s0) * inv_det,
-                (-m[3] * s3 + m[7]  * s1 - m[11] * s0) * inv_det,
-                (-m[6] * s5 + m[10] * s4 - m[14] * s3) * inv_det,
-                ( m[2] * s5 - m[10] * s2 + m[14] * s1) * inv_det,
-                (-m[2] * s4 + m[6]  * s2 - m[14] * s0) * inv_det,
-                ( m[2] * s3 - m[6]  * s1 + m[10] * s0) * inv_det
+                m[0], m[1], m[2],  zero,
+                m[4], m[5], m[6],  zero,
+                m[8], m[9], m[10], zero,
+                zero, zero, zero,  T::one()
             ]
+        };
+
+        linear.try_inv().map(|inv| inv.transpose())
+    }
+
+    /// Composes a matrix that scales, then rotates, then translates, like
+    /// `Matrix::ident().scale(scale).rot(rotation).trans(translation)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Vector};
+    /// let m = Matrix::compose(Vector::one(), Quaternion::ident(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(m * Vector::one(), Vector::new_unf(3.0));
+    /// ```
+    pub fn compose(translation: Vector<T>, rotation: Quaternion<T>,
+                   scale: Vector<T>) -> Matrix<T> {
+        Matrix::ident().scale(scale).rot(rotation).trans(translation)
+    }
+
+    /// Decomposes a matrix built like `compose` into its translation, rotation and scale. A
+    /// mirroring matrix gets a negative *x* scale. Shear and projection are lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Vector};
+    /// # use std::f32::consts;
+    /// let rotation = Quaternion::new_rot(Vector::up(), consts::PI / 2.0);
+    /// let m = Matrix::compose(Vector::new(1.0, 2.0, 3.0), rotation, Vector::new(1.0, 2.0, 4.0));
+    ///
+    /// let (translation, q, scale) = m.decompose();
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert_eq!(translation, Vector::new(1.0, 2.0, 3.0));
+    /// assert!((q.dot(rotation) - 1.0).abs() < EPSILON);
+    /// assert!(scale.dist(Vector::new(1.0, 2.0, 4.0)) < EPSILON);
+    /// ```
+    pub fn decompose(&self) -> (Vector<T>, Quaternion<T>, Vector<T>) {
+        let m = self.array;
+
+        let translation = Vector::new(m[12], m[13], m[14]);
+
+        let x = Vector::new(m[0], m[1], m[2]);
+        let y = Vector::new(m[4], m[5], m[6]);
+        let z = Vector::new(m[8], m[9], m[10]);
+
+        let mut scale = Vector::new(x.len(), y.len(), z.len());
+        let mut rotation = *self;
+
+        if x.cross(y).dot(z) < T::zero() {
+            scale.x = -scale.x;
+
+            for value in &mut rotation.array[0..3] {
+                *value = -*value;
+            }
         }
+
+        (translation, Quaternion::from_matrix(rotation), scale)
+    }
+
+    /// Converts a matrix to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DMatrix, Matrix};
+    /// let m = DMatrix::new([0.1; 16]);
+    ///
+    /// assert_eq!(m.cast::<f32>(), Matrix::new([0.1; 16]));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Matrix<U> {
+        let mut array = [U::zero(); 16];
+
+        for (cast, value) in array.iter_mut().zip(self.array.iter()) {
+            *cast = U::from_f64(value.to_f64());
+        }
+
+        Matrix { array: array }
+    }
+
+    fn persp_scales(fovy: T, aspect: T) -> (T, T) {
+        let y_scale = T::one() / (fovy / T::from_f64(2.0)).tan();
+
+        (y_scale / aspect, y_scale)
+    }
+
+    fn persp(x_scale: T, y_scale: T, x_offset: T, y_offset: T, near: T, far: Option<T>,
+             depths: (T, T)) -> Matrix<T> {
+        let (near_depth, far_depth) = depths;
+
+        // Depth is z_scale * z + z_offset divided by -z.
+        let z_scale = match far {
+            Some(far) => (near_depth * near - far_depth * far) / (far - near),
+            None      => -far_depth
+        };
+        let z_offset = (near_depth + z_scale) * near;
+
+        let mut array = [T::zero(); 16];
+
+        array[0]  = x_scale;
+        array[5]  = y_scale;
+        array[8]  = x_offset;
+        array[9]  = y_offset;
+        array[10] = z_scale;
+        array[11] = -T::one();
+        array[14] = z_offset;
+
+        Matrix { array: array }
     }
 }
 
@@ -262,19 +656,19 @@ use std::ops::Mul;
 
 use mrusty::*;
 
-impl Mul<Vector> for Matrix {
-    type Output = Vector;
+impl From<Matrix<f32>> for Matrix<f64> {
+    fn from(matrix: Matrix<f32>) -> Matrix<f64> {
+        matrix.cast()
+    }
+}
 
-    fn mul(self, vector: Vector) -> Vector {
-        let l = self.array;
-        let r = [vector.x, vector.y, vector.z, 1.0];
+/// Transforms a point: the `Vector` gets a *w* of 1.0 and the result is divided by the resulting
+/// *w*. Multiply a `Vector4` to keep *w* or to transform directions.
+impl<T: Scalar> Mul<Vector<T>> for Matrix<T> {
+    type Output = Vector<T>;
 
-        let result = [
-            l[0] * r[0]  + l[4] * r[1]  + l[8]  * r[2]  + l[12]  * r[3],
-            l[1] * r[0]  + l[5] * r[1]  + l[9]  * r[2]  + l[13]  * r[3],
-            l[2] * r[0]  + l[6] * r[1]  + l[10] * r[2]  + l[14]  * r[3],
-            l[3] * r[0]  + l[7] * r[1]  + l[11] * r[2]  + l[15]  * r[3]
-        ];
+    fn mul(self, vector: Vector<T>) -> Vector<T> {
+        let result = T::mul_mat_vec(&self.array, [vector.x, vector.y, vector.z, T::one()]);
 
         Vector {
             x: result[0] / result[3],
@@ -284,33 +678,35 @@ impl Mul<Vector> for Matrix {
     }
 }
 
-impl Mul<Matrix> for Matrix {
-    type Output = Matrix;
+impl<T: Scalar> Mul<Vector4<T>> for Matrix<T> {
+    type Output = Vector4<T>;
 
-    fn mul(self, other: Matrix) -> Matrix {
-        let l = self.array;
-        let r = other.array;
+    fn mul(self, vector: Vector4<T>) -> Vector4<T> {
+        Vector4::new_arr(T::mul_mat_vec(&self.array, [vector.x, vector.y, vector.z, vector.w]))
+    }
+}
 
-        Matrix {
-            array: [
-                l[0] * r[0]  + l[4] * r[1]  + l[8]  * r[2]  + l[12]  * r[3],
-                l[1] * r[0]  + l[5] * r[1]  + l[9]  * r[2]  + l[13]  * r[3],
-                l[2] * r[0]  + l[6] * r[1]  + l[10] * r[2]  + l[14]  * r[3],
-                l[3] * r[0]  + l[7] * r[1]  + l[11] * r[2]  + l[15]  * r[3],
-                l[0] * r[4]  + l[4] * r[5]  + l[8]  * r[6]  + l[12]  * r[7],
-                l[1] * r[4]  + l[5] * r[5]  + l[9]  * r[6]  + l[13]  * r[7],
-                l[2] * r[4]  + l[6] * r[5]  + l[10] * r[6]  + l[14]  * r[7],
-                l[3] * r[4]  + l[7] * r[5]  + l[11] * r[6]  + l[15]  * r[7],
-                l[0] * r[8]  + l[4] * r[9]  + l[8]  * r[10] + l[12]  * r[11],
-                l[1] * r[8]  + l[5] * r[9]  + l[9]  * r[10] + l[13]  * r[11],
-                l[2] * r[8]  + l[6] * r[9]  + l[10] * r[10] + l[14]  * r[11],
-                l[3] * r[8]  + l[7] * r[9]  + l[11] * r[10] + l[15]  * r[11],
-                l[0] * r[12] + l[4] * r[13] + l[8]  * r[14] + l[12]  * r[15],
-                l[1] * r[12] + l[5] * r[13] + l[9]  * r[14] + l[13]  * r[15],
-                l[2] * r[12] + l[6] * r[13] + l[10] * r[14] + l[14]  * r[15],
-                l[3] * r[12] + l[7] * r[13] + l[11] * r[14] + l[15]  * r[15]
-            ]
+impl<T: Scalar> Mul<Matrix<T>> for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: Matrix<T>) -> Matrix<T> {
+        Matrix { array: T::mul_mat(&self.array, &other.array) }
+    }
+}
+
+pub fn depth_range(mruby: MrubyType, args: &[Value]) -> Result<DepthRange, Value> {
+    match args.len() {
+        0 => Ok(DepthRange::OpenGl),
+        1 => {
+            match args[0].to_str() {
+                Ok("opengl") => Ok(DepthRange::OpenGl),
+                Ok("vulkan") => Ok(DepthRange::Vulkan),
+                _            => {
+                    Err(mruby.raise("ArgumentError", "depth range must be one of :opengl, :vulkan"))
+                }
+            }
         }
+        _ => Err(mruby.raise("ArgumentError", "wrong number of arguments"))
     }
 }
 
@@ -326,7 +722,74 @@ mrusty_class!(Matrix, {
     });
 
     def_self!("identity", |mruby, _slf: Value| {
-        mruby.obj(Matrix::ident())
+        mruby.obj(Matrix::<f32>::ident())
+    });
+
+    def_self!("perspective", |mruby, _slf: Value, fovy: f64, aspect: f64, near: f64, far: f64;
+                              args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_persp(fovy as f32, aspect as f32, near as f32, far as f32, depth))
+    });
+
+    def_self!("perspective_infinite", |mruby, _slf: Value, fovy: f64, aspect: f64, near: f64;
+                                       args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_persp_inf(fovy as f32, aspect as f32, near as f32, depth))
+    });
+
+    def_self!("perspective_reversed", |mruby, _slf: Value, fovy: f64, aspect: f64, near: f64,
+                                       far: f64; args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_persp_rev(fovy as f32, aspect as f32, near as f32, far as f32,
+                                        depth))
+    });
+
+    def_self!("perspective_infinite_reversed", |mruby, _slf: Value, fovy: f64, aspect: f64,
+                                                near: f64; args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_persp_inf_rev(fovy as f32, aspect as f32, near as f32, depth))
+    });
+
+    def_self!("frustum", |mruby, _slf: Value, left: f64, right: f64, bottom: f64, top: f64,
+                          near: f64, far: f64; args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_frustum(left as f32, right as f32, bottom as f32, top as f32,
+                                      near as f32, far as f32, depth))
+    });
+
+    def_self!("orthographic", |mruby, _slf: Value, left: f64, right: f64, bottom: f64, top: f64,
+                               near: f64, far: f64; args| {
+        let depth = match depth_range(mruby.clone(), &args) {
+            Ok(depth)  => depth,
+            Err(error) => return error
+        };
+
+        mruby.obj(Matrix::new_ortho(left as f32, right as f32, bottom as f32, top as f32,
+                                    near as f32, far as f32, depth))
+    });
+
+    def_self!("look_at", |mruby, _slf: Value, eye: Vector, target: Vector, up: Vector| {
+        mruby.obj(Matrix::new_look_at((*eye).clone(), (*target).clone(), (*up).clone()))
     });
 
     def!("to_a", |mruby, slf: Matrix| {
@@ -354,12 +817,17 @@ mrusty_class!(Matrix, {
 
                 mruby.obj((*slf).clone() * (*vector).clone())
             }
+            "Vector4" => {
+                let vector = other.to_obj::<Vector4>().unwrap();
+
+                mruby.obj((*slf).clone() * (*vector).clone())
+            }
             "Matrix" => {
                 let matrix = other.to_obj::<Matrix>().unwrap();
 
                 mruby.obj((*slf).clone() * (*matrix).clone())
             }
-            _ => mruby.raise("TypeError", "expecting Vector or Matrix")
+            _ => mruby.raise("TypeError", "expecting Vector, Vector4 or Matrix")
         }
     });
 
@@ -381,7 +849,36 @@ mrusty_class!(Matrix, {
     });
 
     def!("inv", |mruby, slf: Matrix| {
-        mruby.obj(slf.inv())
+        match slf.try_inv() {
+            Some(inv) => mruby.obj(inv),
+            None      => mruby.raise("ArgumentError", "matrix is not invertible")
+        }
+    });
+
+    def!("transpose", |mruby, slf: Matrix| {
+        mruby.obj(slf.transpose())
+    });
+
+    def!("det", |mruby, slf: Matrix| {
+        mruby.float(slf.det() as f64)
+    });
+
+    def!("normal", |mruby, slf: Matrix| {
+        match slf.normal() {
+            Some(normal) => mruby.obj(normal),
+            None         => mruby.nil()
+        }
+    });
+
+    def_self!("compose", |mruby, _slf: Value, translation: Vector, rotation: Quaternion,
+                          scale: Vector| {
+        mruby.obj(Matrix::compose((*translation).clone(), (*rotation).clone(), (*scale).clone()))
+    });
+
+    def!("decompose", |mruby, slf: Matrix| {
+        let (translation, rotation, scale) = slf.decompose();
+
+        mruby.array(vec![mruby.obj(translation), mruby.obj(rotation), mruby.obj(scale)])
     });
 });
 
@@ -391,9 +888,10 @@ mod tests {
 
     use super::Matrix;
     use super::super::Vector;
+    use super::super::Vector4;
     use super::super::Quaternion;
 
-    describe!(Matrix, (Vector, Quaternion), "
+    describe!(Matrix, (Vector, Vector4, Quaternion), "
       context 'when identity' do
         subject { Matrix.identity }
         let(:unit) { Vector.uniform 1.0 }
@@ -408,6 +906,12 @@ mod tests {
           expect(subject * Matrix.new([2.0] * 16)).to eql Matrix.new([2.0] * 16)
         end
 
+        it 'keeps w when multiplying a Vector4 on #*' do
+          direction = unit.extend 0.0
+
+          expect(subject.trans(Vector.uniform(1.0)) * direction).to eql direction
+        end
+
         it 'adds translation to a matrix on #trans' do
           expect(subject.trans(Vector.uniform(1.0)) * unit).to eql Vector.uniform 2.0
         end
@@ -433,9 +937,57 @@ mod tests {
           expect(rotated.z).to be_within(0.000001).of -1.0
         end
 
+        it 'projects in perspective on .perspective' do
+          projection = Matrix.perspective Math::PI / 2.0, 1.0, 1.0, 10.0, :vulkan
+
+          expect((projection * Vector.new(0.0, 0.0, -1.0)).z).to be_within(0.000001).of 0.0
+          expect((projection * Vector.new(0.0, 0.0, -10.0)).z).to be_within(0.000001).of 1.0
+        end
+
+        it 'projects orthographically on .orthographic' do
+          projection = Matrix.orthographic 0.0, 4.0, 0.0, 2.0, 1.0, 3.0
+
+          expect(projection * Vector.new(4.0, 2.0, -3.0)).to eql unit
+        end
+
+        it 'raises on unknown depth ranges' do
+          expect { Matrix.perspective_infinite 1.0, 1.0, 1.0, :direct3d }.to raise_error(
+            ArgumentError
+          )
+        end
+
+        it 'looks at a target on .look_at' do
+          view = Matrix.look_at Vector.new(0.0, 0.0, 5.0), Vector.zero, Vector.up
+
+          expect(view * Vector.zero).to eql Vector.new(0.0, 0.0, -5.0)
+        end
+
         it 'computes inverse on #inv' do
           expect(subject.scale(Vector.uniform(2.0)).inv * unit).to eql Vector.uniform 0.5
         end
+
+        it 'raises on #inv when singular' do
+          expect { Matrix.new([1.0] * 16).inv }.to raise_error ArgumentError
+        end
+
+        it 'transposes on #transpose' do
+          expect(subject.trans(unit).transpose.to_a[3]).to eql 1.0
+        end
+
+        it 'computes determinant on #det' do
+          expect(subject.scale(Vector.uniform(2.0)).det).to eql 8.0
+        end
+
+        it 'computes normal matrix on #normal' do
+          expect(subject.scale(Vector.uniform(2.0)).normal * unit).to eql Vector.uniform 0.5
+          expect(Matrix.new([1.0] * 16).normal).to be_nil
+        end
+
+        it 'decomposes on #decompose' do
+          composed = Matrix.compose unit, Quaternion.identity, Vector.uniform(2.0)
+
+          expect(composed.decompose).to eql [unit, Quaternion.identity, Vector.uniform(2.0)]
+        end
       end
     ");
 }
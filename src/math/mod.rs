@@ -7,22 +7,109 @@
 
 //! A `mod` containing linear math constructs useful in graphics.
 
+mod scalar;
+mod simd;
+
 mod vector;
+mod vector2;
+mod vector4;
 mod quaternion;
 mod matrix;
+mod transform;
+
+mod hit;
+mod ray;
+mod plane;
+mod aabb;
+mod sphere;
+mod triangle;
+mod obb;
+mod frustum;
 
 mod interpolate;
 mod interpolator;
 
 mod bezier;
 
-pub use self::vector::Vector;
-pub use self::quaternion::Quaternion;
-pub use self::matrix::Matrix;
+pub use self::scalar::Scalar;
+
+/// A 3D vector of `f32`s.
+pub type Vector = self::vector::Vector<f32>;
+/// A 3D vector of `f64`s.
+pub type DVector = self::vector::Vector<f64>;
+/// A 2D vector of `f32`s.
+pub type Vector2 = self::vector2::Vector2<f32>;
+/// A 2D vector of `f64`s.
+pub type DVector2 = self::vector2::Vector2<f64>;
+/// A 4D vector of `f32`s.
+pub type Vector4 = self::vector4::Vector4<f32>;
+/// A 4D vector of `f64`s.
+pub type DVector4 = self::vector4::Vector4<f64>;
+/// A quaternion of `f32`s.
+pub type Quaternion = self::quaternion::Quaternion<f32>;
+/// A quaternion of `f64`s.
+pub type DQuaternion = self::quaternion::Quaternion<f64>;
+pub use self::quaternion::EulerOrder;
+/// A squad path of `f32` quaternions.
+pub type SquadPath = self::quaternion::SquadPath<f32>;
+/// A squad path of `f64` quaternions.
+pub type DSquadPath = self::quaternion::SquadPath<f64>;
+/// A 4x4 matrix of `f32`s.
+pub type Matrix = self::matrix::Matrix<f32>;
+/// A 4x4 matrix of `f64`s.
+pub type DMatrix = self::matrix::Matrix<f64>;
+pub use self::matrix::DepthRange;
+/// A transform of `f32`s.
+pub type Transform = self::transform::Transform<f32>;
+/// A transform of `f64`s.
+pub type DTransform = self::transform::Transform<f64>;
+
+/// A hit of `f32`s.
+pub type Hit = self::hit::Hit<f32>;
+/// A hit of `f64`s.
+pub type DHit = self::hit::Hit<f64>;
+/// A ray of `f32`s.
+pub type Ray = self::ray::Ray<f32>;
+/// A ray of `f64`s.
+pub type DRay = self::ray::Ray<f64>;
+/// A plane of `f32`s.
+pub type Plane = self::plane::Plane<f32>;
+/// A plane of `f64`s.
+pub type DPlane = self::plane::Plane<f64>;
+/// An axis-aligned bounding box of `f32`s.
+pub type Aabb = self::aabb::Aabb<f32>;
+/// An axis-aligned bounding box of `f64`s.
+pub type DAabb = self::aabb::Aabb<f64>;
+/// A sphere of `f32`s.
+pub type Sphere = self::sphere::Sphere<f32>;
+/// A sphere of `f64`s.
+pub type DSphere = self::sphere::Sphere<f64>;
+/// A triangle of `f32`s.
+pub type Triangle = self::triangle::Triangle<f32>;
+/// A triangle of `f64`s.
+pub type DTriangle = self::triangle::Triangle<f64>;
+/// An oriented bounding box of `f32`s.
+pub type Obb = self::obb::Obb<f32>;
+/// An oriented bounding box of `f64`s.
+pub type DObb = self::obb::Obb<f64>;
+/// A view frustum of `f32`s.
+pub type Frustum = self::frustum::Frustum<f32>;
+/// A view frustum of `f64`s.
+pub type DFrustum = self::frustum::Frustum<f64>;
+pub use self::frustum::Containment;
 
 pub use self::interpolate::Interpolate;
-pub use self::interpolator::Interpolator;
+/// An interpolator working with `f32`s.
+pub type Interpolator = self::interpolator::Interpolator<f32>;
+/// An interpolator working with `f64`s.
+pub type DInterpolator = self::interpolator::Interpolator<f64>;
 pub use self::interpolator::Behavior;
 
-pub use self::bezier::Bezier;
-pub use self::bezier::BezierPath;
+/// A Bézier curve of `f32` vectors.
+pub type Bezier = self::bezier::Bezier<f32>;
+/// A Bézier curve of `f64` vectors.
+pub type DBezier = self::bezier::Bezier<f64>;
+/// A Bézier path of `f32` vectors.
+pub type BezierPath = self::bezier::BezierPath<f32>;
+/// A Bézier path of `f64` vectors.
+pub type DBezierPath = self::bezier::BezierPath<f64>;
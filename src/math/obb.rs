@@ -0,0 +1,389 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::aabb::Aabb;
+use super::hit;
+use super::hit::Hit;
+use super::matrix::Matrix;
+use super::plane::Plane;
+use super::quaternion::Quaternion;
+use super::vector::Vector;
+
+/// An oriented bounding box `struct`: a box rotated around its center. `Obb` holds `f32`s and
+/// `DObb` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Obb, Quaternion, Vector};
+/// # use std::f32::consts;
+/// let rotation = Quaternion::new_rot(Vector::up(), consts::PI / 4.0);
+/// let b1 = Obb::new(Vector::zero(), rotation, Vector::one());
+/// let b2 = Obb::new(Vector::new(2.2, 0.0, 0.0), Quaternion::ident(), Vector::one());
+///
+/// assert!(b1.hit_obb(b2).is_some());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb<T = f32> {
+    /// `Vector<T>` center
+    pub center: Vector<T>,
+    /// `Quaternion<T>` unit rotation of the box's axes
+    pub rotation: Quaternion<T>,
+    /// `Vector<T>` half-sizes of the box along its axes
+    pub half_extents: Vector<T>
+}
+
+impl<T: Scalar> Obb<T> {
+    /// Creates a box using a center, a rotation and half-sizes along the rotated axes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Vector};
+    /// let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(b.half_extents, Vector::one());
+    /// ```
+    pub fn new(center: Vector<T>, rotation: Quaternion<T>, half_extents: Vector<T>) -> Obb<T> {
+        Obb {
+            center: center,
+            rotation: rotation,
+            half_extents: half_extents
+        }
+    }
+
+    /// Computes the normalized, rotated *x*, *y* and *z* axes of a box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Vector};
+    /// let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(b.axes()[1], Vector::up());
+    /// ```
+    pub fn axes(&self) -> [Vector<T>; 3] {
+        let m = self.rotation.to_matrix().array;
+
+        [
+            Vector::new(m[0], m[1], m[2]),
+            Vector::new(m[4], m[5], m[6]),
+            Vector::new(m[8], m[9], m[10])
+        ]
+    }
+
+    /// Checks whether a point is inside a box or on its surface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Vector};
+    /// # use std::f32::consts;
+    /// let rotation = Quaternion::new_rot(Vector::up(), consts::PI / 4.0);
+    /// let b = Obb::new(Vector::zero(), rotation, Vector::one());
+    ///
+    /// assert!(b.contains(Vector::new(1.3, 0.0, 0.0)));
+    /// assert!(!b.contains(Vector::new(1.0, 0.0, 1.0)));
+    /// ```
+    pub fn contains(&self, point: Vector<T>) -> bool {
+        self.local_aabb().contains(self.to_local(point))
+    }
+
+    /// Computes the point inside a box closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Vector};
+    /// let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(b.closest_point(Vector::new(0.5, 3.0, -2.0)), Vector::new(0.5, 1.0, -1.0));
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        self.to_world(self.local_aabb().closest_point(self.to_local(point)))
+    }
+
+    /// Checks whether two boxes overlap by testing the separating axes. The `Hit` separates them
+    /// along the axis on which they overlap the least.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Obb, Quaternion, Vector};
+    /// let b1 = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    /// let b2 = Obb::new(Vector::new(1.5, 0.0, 0.0), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(b1.hit_obb(b2), Some(Hit::new(0.5, Vector::right())));
+    /// ```
+    pub fn hit_obb(&self, other: Obb<T>) -> Option<Hit<T>> {
+        let axes = self.axes();
+        let other_axes = other.axes();
+        let to_other = other.center - self.center;
+
+        let mut candidates = Vec::with_capacity(15);
+
+        candidates.extend_from_slice(&axes);
+        candidates.extend_from_slice(&other_axes);
+
+        for axis in axes.iter() {
+            for other_axis in other_axes.iter() {
+                let cross = axis.cross(*other_axis);
+
+                // Parallel edges are already covered by the face axes.
+                if cross.len() > T::from_f64(1e-6) {
+                    candidates.push(cross.norm());
+                }
+            }
+        }
+
+        let mut result: Option<Hit<T>> = None;
+
+        for axis in candidates {
+            let dist = to_other.dot(axis);
+            let overlap = self.radius(axis) + other.radius(axis) - dist.abs();
+
+            if overlap <= T::zero() {
+                return None;
+            }
+
+            if result.is_none_or(|hit| overlap < hit.distance) {
+                let normal = if dist > T::zero() { -axis } else { axis };
+
+                result = Some(Hit::new(overlap, normal));
+            }
+        }
+
+        result
+    }
+
+    /// Checks whether a box overlaps an axis-aligned box. The `Hit` separates them along the axis
+    /// on which they overlap the least.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Obb, Quaternion, Vector};
+    /// let b1 = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    /// let b2 = Aabb::new(Vector::new(2.0, 0.0, 0.0), Vector::new(3.0, 1.0, 1.0));
+    ///
+    /// assert!(b1.hit_aabb(b2).is_none());
+    /// ```
+    pub fn hit_aabb(&self, aabb: Aabb<T>) -> Option<Hit<T>> {
+        self.hit_obb(Obb::from(aabb))
+    }
+
+    /// Checks whether a box crosses a plane. The `Hit` moves the box to the side of the plane its
+    /// center is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Obb, Plane, Quaternion, Vector};
+    /// let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    /// let p = Plane::new(Vector::up(), 0.5);
+    ///
+    /// assert_eq!(b.hit_plane(p), Some(Hit::new(0.5, Vector::down())));
+    /// ```
+    pub fn hit_plane(&self, plane: Plane<T>) -> Option<Hit<T>> {
+        hit::hit_plane(plane, self.center, self.radius(plane.normal))
+    }
+
+    /// Converts a box to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DObb, Obb};
+    /// # use anima_engine::math::{DQuaternion, DVector, Quaternion, Vector};
+    /// let b = DObb::new(DVector::zero(), DQuaternion::ident(), DVector::one());
+    ///
+    /// assert_eq!(b.cast::<f32>(), Obb::new(Vector::zero(), Quaternion::ident(), Vector::one()));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Obb<U> {
+        Obb {
+            center: self.center.cast(),
+            rotation: self.rotation.cast(),
+            half_extents: self.half_extents.cast()
+        }
+    }
+
+    /// Converts a point to box space, where the box is axis-aligned and centered at the origin.
+    pub fn to_local(self, point: Vector<T>) -> Vector<T> {
+        let axes = self.axes();
+        let point = point - self.center;
+
+        Vector::new(point.dot(axes[0]), point.dot(axes[1]), point.dot(axes[2]))
+    }
+
+    /// Converts a point from box space.
+    pub fn to_world(self, point: Vector<T>) -> Vector<T> {
+        let axes = self.axes();
+
+        self.center + axes[0] * point.x + axes[1] * point.y + axes[2] * point.z
+    }
+
+    /// Returns the box itself in box space.
+    pub fn local_aabb(&self) -> Aabb<T> {
+        Aabb::new(-self.half_extents, self.half_extents)
+    }
+
+    fn radius(&self, direction: Vector<T>) -> T {
+        let axes = self.axes();
+
+        self.half_extents.x * axes[0].dot(direction).abs() +
+        self.half_extents.y * axes[1].dot(direction).abs() +
+        self.half_extents.z * axes[2].dot(direction).abs()
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+use super::sphere::Sphere;
+
+/// Transforms a box. Shearing is dropped.
+impl<T: Scalar> Mul<Obb<T>> for Matrix<T> {
+    type Output = Obb<T>;
+
+    fn mul(self, obb: Obb<T>) -> Obb<T> {
+        let center = self * obb.center;
+        let axes = obb.axes();
+
+        let x = self * (obb.center + axes[0]) - center;
+        let y = self * (obb.center + axes[1]) - center;
+        let z = self * (obb.center + axes[2]) - center;
+
+        let half_extents = Vector::new(obb.half_extents.x * x.len(),
+                                       obb.half_extents.y * y.len(),
+                                       obb.half_extents.z * z.len());
+
+        let x = x.norm();
+        let y = (y - x * x.dot(y)).norm();
+        let z = x.cross(y);
+
+        let zero = T::zero();
+
+        let rotation = Matrix::new([
+            x.x,  x.y,  x.z,  zero,
+            y.x,  y.y,  y.z,  zero,
+            z.x,  z.y,  z.z,  zero,
+            zero, zero, zero, T::one()
+        ]);
+
+        Obb::new(center, Quaternion::from_matrix(rotation), half_extents)
+    }
+}
+
+impl<T: Scalar> From<Aabb<T>> for Obb<T> {
+    fn from(aabb: Aabb<T>) -> Obb<T> {
+        Obb::new(aabb.center(), Quaternion::ident(), aabb.half_extents())
+    }
+}
+
+impl From<Obb<f32>> for Obb<f64> {
+    fn from(obb: Obb<f32>) -> Obb<f64> {
+        obb.cast()
+    }
+}
+
+mrusty_class!(Obb, {
+    def!("initialize", |center: Vector, rotation: Quaternion, half_extents: Vector| {
+        Obb::new((*center).clone(), (*rotation).clone(), (*half_extents).clone())
+    });
+
+    def_self!("from_aabb", |mruby, _slf: Value, aabb: Aabb| {
+        mruby.obj(Obb::from((*aabb).clone()))
+    });
+
+    def!("center", |mruby, slf: Obb| {
+        mruby.obj(slf.center)
+    });
+
+    def!("rotation", |mruby, slf: Obb| {
+        mruby.obj(slf.rotation)
+    });
+
+    def!("half_extents", |mruby, slf: Obb| {
+        mruby.obj(slf.half_extents)
+    });
+
+    def!("axes", |mruby, slf: Obb| {
+        mruby.array(slf.axes().iter().map(|&axis| mruby.obj(axis)).collect())
+    });
+
+    def!("contains?", |mruby, slf: Obb, point: Vector| {
+        mruby.bool(slf.contains((*point).clone()))
+    });
+
+    def!("closest_point", |mruby, slf: Obb, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("hit", |mruby, slf: Obb, shape: Value| {
+        if let Ok(other) = shape.to_obj::<Obb>() {
+            hit::to_value(&mruby, slf.hit_obb((*other).clone()))
+        } else if let Ok(aabb) = shape.to_obj::<Aabb>() {
+            hit::to_value(&mruby, slf.hit_aabb((*aabb).clone()))
+        } else if let Ok(plane) = shape.to_obj::<Plane>() {
+            hit::to_value(&mruby, slf.hit_plane((*plane).clone()))
+        } else if let Ok(sphere) = shape.to_obj::<Sphere>() {
+            hit::to_value(&mruby, sphere.hit_obb((*slf).clone()).map(|hit| {
+                Hit::new(hit.distance, -hit.normal)
+            }))
+        } else {
+            mruby.raise("ArgumentError", "expecting Obb, Aabb, Plane or Sphere")
+        }
+    });
+
+    def!("transform", |mruby, slf: Obb, matrix: Matrix| {
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Obb;
+    use super::super::{Aabb, Matrix, Plane, Quaternion, Sphere, Vector};
+
+    describe!(Obb, (Aabb, Matrix, Plane, Quaternion, Sphere, Vector), "
+      context 'when rotated' do
+        subject { Obb.new Vector.zero, Quaternion.rotation(Vector.up, Math::PI / 4), Vector.one }
+
+        it 'returns center on #center' do
+          expect(subject.center).to eql Vector.zero
+        end
+
+        it 'returns half extents on #half_extents' do
+          expect(subject.half_extents).to eql Vector.one
+        end
+
+        it 'returns axes on #axes' do
+          expect(subject.axes.size).to eql 3
+        end
+
+        it 'checks points on #contains?' do
+          expect(subject.contains? Vector.new(1.3, 0.0, 0.0)).to be true
+          expect(subject.contains? Vector.new(1.0, 0.0, 1.0)).to be false
+        end
+
+        it 'hits overlapping shapes on #hit' do
+          expect(subject.hit Obb.from_aabb(Aabb.new(Vector.new(1.3, 0.0, 0.0), Vector.one)))
+            .not_to be_nil
+          expect(subject.hit Plane.new(Vector.up, 2.0)).to be_nil
+          expect(subject.hit Sphere.new(Vector.new(0.0, 1.5, 0.0), 1.0)).not_to be_nil
+        end
+
+        it 'transforms on #transform' do
+          expect(subject.transform(Matrix.identity.trans(Vector.up)).center).to eql Vector.up
+        end
+      end
+    ");
+}
@@ -0,0 +1,228 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::matrix::Matrix;
+use super::vector::Vector;
+
+/// A `struct` representing the plane of the points `p` for which `normal.dot(p) == distance`.
+/// `Plane` holds `f32`s and `DPlane` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Plane, Vector};
+/// let p = Plane::new_point(Vector::up(), Vector::new(5.0, 2.0, 1.0));
+///
+/// assert_eq!(p.dist(Vector::new(0.0, 3.0, 0.0)), 1.0);
+/// assert_eq!(p.closest_point(Vector::new(1.0, 3.0, 1.0)), Vector::new(1.0, 2.0, 1.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane<T = f32> {
+    /// `Vector<T>` normalized normal
+    pub normal: Vector<T>,
+    /// `T` signed distance from the origin along `normal`
+    pub distance: T
+}
+
+impl<T: Scalar> Plane<T> {
+    /// Creates a plane using a normal and the signed distance from the origin along it. The normal
+    /// is normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Plane, Vector};
+    /// let p = Plane::new(Vector::new(0.0, 2.0, 0.0), 4.0);
+    ///
+    /// assert_eq!(p, Plane { normal: Vector::up(), distance: 2.0 });
+    /// ```
+    pub fn new(normal: Vector<T>, distance: T) -> Plane<T> {
+        let len = normal.len();
+
+        Plane {
+            normal: normal * (T::one() / len),
+            distance: distance / len
+        }
+    }
+
+    /// Creates a plane using a normal and a point on the plane.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Plane, Vector};
+    /// let p = Plane::new_point(Vector::up(), Vector::new(1.0, 2.0, 3.0));
+    ///
+    /// assert_eq!(p, Plane::new(Vector::up(), 2.0));
+    /// ```
+    pub fn new_point(normal: Vector<T>, point: Vector<T>) -> Plane<T> {
+        let normal = normal.norm();
+
+        Plane::new(normal, normal.dot(point))
+    }
+
+    /// Creates a plane passing through 3 points. Seen from the side the normal points to, the
+    /// points are counter-clockwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Plane, Vector};
+    /// let p = Plane::new_tri(Vector::zero(), Vector::new(0.0, 0.0, 1.0),
+    ///                        Vector::new(1.0, 0.0, 0.0));
+    ///
+    /// assert_eq!(p, Plane::new(Vector::up(), 0.0));
+    /// ```
+    pub fn new_tri(a: Vector<T>, b: Vector<T>, c: Vector<T>) -> Plane<T> {
+        Plane::new_point((b - a).cross(c - a), a)
+    }
+
+    /// Computes the signed distance from a plane to a point; positive on the side the normal
+    /// points to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Plane, Vector};
+    /// let p = Plane::new(Vector::up(), 1.0);
+    ///
+    /// assert_eq!(p.dist(Vector::zero()), -1.0);
+    /// ```
+    pub fn dist(&self, point: Vector<T>) -> T {
+        self.normal.dot(point) - self.distance
+    }
+
+    /// Computes the point on a plane closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Plane, Vector};
+    /// let p = Plane::new(Vector::up(), 1.0);
+    ///
+    /// assert_eq!(p.closest_point(Vector::new(2.0, 5.0, 3.0)), Vector::new(2.0, 1.0, 3.0));
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        point - self.normal * self.dist(point)
+    }
+
+    /// Converts a plane to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DPlane, Plane, Vector};
+    /// let p = DPlane::new(Vector::up().cast(), 0.5);
+    ///
+    /// assert_eq!(p.cast::<f32>(), Plane::new(Vector::up(), 0.5));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Plane<U> {
+        Plane {
+            normal: self.normal.cast(),
+            distance: U::from_f64(self.distance.to_f64())
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+/// Transforms a plane. Panics if the matrix is not invertible.
+impl<T: Scalar> Mul<Plane<T>> for Matrix<T> {
+    type Output = Plane<T>;
+
+    fn mul(self, plane: Plane<T>) -> Plane<T> {
+        let normal = match self.normal() {
+            Some(normal) => normal * plane.normal,
+            None         => panic!("Matrix {:?} is not invertable.", self)
+        };
+
+        Plane::new_point(normal, self * (plane.normal * plane.distance))
+    }
+}
+
+impl From<Plane<f32>> for Plane<f64> {
+    fn from(plane: Plane<f32>) -> Plane<f64> {
+        plane.cast()
+    }
+}
+
+mrusty_class!(Plane, {
+    def!("initialize", |normal: Vector, distance: f64| {
+        Plane::new((*normal).clone(), distance as f32)
+    });
+
+    def_self!("from_point", |mruby, _slf: Value, normal: Vector, point: Vector| {
+        mruby.obj(Plane::new_point((*normal).clone(), (*point).clone()))
+    });
+
+    def_self!("from_triangle", |mruby, _slf: Value, a: Vector, b: Vector, c: Vector| {
+        mruby.obj(Plane::new_tri((*a).clone(), (*b).clone(), (*c).clone()))
+    });
+
+    def!("normal", |mruby, slf: Plane| {
+        mruby.obj(slf.normal)
+    });
+
+    def!("distance", |mruby, slf: Plane| {
+        mruby.float(slf.distance as f64)
+    });
+
+    def!("dist", |mruby, slf: Plane, point: Vector| {
+        mruby.float(slf.dist((*point).clone()) as f64)
+    });
+
+    def!("closest_point", |mruby, slf: Plane, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("transform", |mruby, slf: Plane, matrix: Matrix| {
+        if matrix.try_inv().is_none() {
+            return mruby.raise("ArgumentError", "matrix is not invertible");
+        }
+
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Plane;
+    use super::super::{Matrix, Vector};
+
+    describe!(Plane, (Matrix, Vector), "
+      context 'when horizontal' do
+        subject { Plane.new Vector.up, 1.0 }
+
+        it 'returns normal on #normal' do
+          expect(subject.normal).to eql Vector.up
+        end
+
+        it 'returns distance on #distance' do
+          expect(subject.distance).to eql 1.0
+        end
+
+        it 'computes signed distance on #dist' do
+          expect(subject.dist Vector.zero).to eql -1.0
+        end
+
+        it 'computes closest point on #closest_point' do
+          expect(subject.closest_point Vector.zero).to eql Vector.up
+        end
+
+        it 'transforms on #transform' do
+          moved = subject.transform(Matrix.identity.trans(Vector.up))
+
+          expect(moved.distance).to eql 2.0
+        end
+      end
+    ");
+}
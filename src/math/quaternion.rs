@@ -5,9 +5,12 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use math::Vector;
+use math::Scalar;
+use super::matrix::Matrix;
+use super::vector::Vector;
 
-/// A simple quaterion `struct` tailored specifically for graphics.
+/// A simple quaterion `struct` tailored specifically for graphics. `Quaternion` holds `f32`s and
+/// `DQuaternion` holds `f64`s.
 ///
 /// # Examples
 ///
@@ -29,18 +32,50 @@ use math::Vector;
 /// assert!((q3.w - q2.w).abs() < EPSILON);
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Quaternion {
-    /// `f32` imaginary *i* value
-    pub x: f32,
-    /// `f32` imaginary *j* value
-    pub y: f32,
-    /// `f32` imaginary *k* value
-    pub z: f32,
-    /// `f32` real value
-    pub w: f32
+pub struct Quaternion<T = f32> {
+    /// `T` imaginary *i* value
+    pub x: T,
+    /// `T` imaginary *j* value
+    pub y: T,
+    /// `T` imaginary *k* value
+    pub z: T,
+    /// `T` real value
+    pub w: T
 }
 
-impl Quaternion {
+/// An `enum` containing the orders in which Euler angles can be applied. Rotations are made around
+/// the fixed world axes, e.g. `Xyz` rotates around *x* first and around *z* last, which is the
+/// same as rotating around the local *z*, *y*, then *x* axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EulerOrder {
+    /// *x*, *y*, then *z*
+    Xyz,
+    /// *x*, *z*, then *y*
+    Xzy,
+    /// *y*, *x*, then *z*
+    Yxz,
+    /// *y*, *z*, then *x*
+    Yzx,
+    /// *z*, *x*, then *y*
+    Zxy,
+    /// *z*, *y*, then *x*
+    Zyx
+}
+
+impl EulerOrder {
+    fn axes(&self) -> (usize, usize, usize) {
+        match *self {
+            EulerOrder::Xyz => (0, 1, 2),
+            EulerOrder::Xzy => (0, 2, 1),
+            EulerOrder::Yxz => (1, 0, 2),
+            EulerOrder::Yzx => (1, 2, 0),
+            EulerOrder::Zxy => (2, 0, 1),
+            EulerOrder::Zyx => (2, 1, 0)
+        }
+    }
+}
+
+impl<T: Scalar> Quaternion<T> {
     /// Creates a quaternion using 4 values.
     ///
     /// # Examples
@@ -51,7 +86,7 @@ impl Quaternion {
     ///
     /// assert_eq!(q, Quaternion { x: 0.0, y: 1.0, z: 2.0, w: 3.0 });
     /// ```
-    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Quaternion {
+    pub fn new(x: T, y: T, z: T, w: T) -> Quaternion<T> {
         Quaternion { x: x, y: y, z: z, w: w }
     }
 
@@ -74,15 +109,15 @@ impl Quaternion {
     /// assert!((q1.z - q2.z).abs() < EPSILON);
     /// assert!((q1.w - q2.w).abs() < EPSILON);
     /// ```
-    pub fn new_rot(direction: Vector, angle: f32) -> Quaternion {
+    pub fn new_rot(direction: Vector<T>, angle: T) -> Quaternion<T> {
         let direction = direction.norm();
-        let sin = (angle / 2.0).sin();
+        let sin = (angle / T::from_f64(2.0)).sin();
 
         Quaternion {
             x: direction.x * sin,
             y: direction.y * sin,
             z: direction.z * sin,
-            w: (angle / 2.0).cos()
+            w: (angle / T::from_f64(2.0)).cos()
         }
     }
 
@@ -100,13 +135,118 @@ impl Quaternion {
     /// const EPSILON: f32 = 0.00001;
     ///
     /// assert!((v.rot(q) - Vector::new_unf(-0.8164966)).len() < EPSILON);
-    pub fn new_sph_rot(start: Vector, finish: Vector) -> Quaternion {
+    pub fn new_sph_rot(start: Vector<T>, finish: Vector<T>) -> Quaternion<T> {
         let direction = finish.cross(start);
         let angle = start.angle(finish);
 
         Quaternion::new_rot(direction, angle)
     }
 
+    /// Creates a quaternion equivalent to rotating around the *x*, *y* and *z* axes by the
+    /// components of `angles`, in `order`. The rotations are measured in radians.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{EulerOrder, Matrix, Quaternion, Vector};
+    /// let x = Quaternion::new_rot(Vector::new(1.0, 0.0, 0.0), 0.5);
+    /// let z = Quaternion::new_rot(Vector::new(0.0, 0.0, 1.0), 0.25);
+    ///
+    /// let q = Quaternion::new_euler(Vector::new(0.5, 0.0, 0.25), EulerOrder::Xyz);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((q.dot(x * z) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn new_euler(angles: Vector<T>, order: EulerOrder) -> Quaternion<T> {
+        let (i, j, k) = order.axes();
+        let angles = [angles.x, angles.y, angles.z];
+
+        let rot = |axis: usize| {
+            let mut direction = [T::zero(); 3];
+            direction[axis] = T::one();
+
+            Quaternion::new_rot(Vector::new(direction[0], direction[1], direction[2]),
+                                angles[axis])
+        };
+
+        rot(i) * rot(j) * rot(k)
+    }
+
+    /// Creates a quaternion equivalent to the rotation that turns `Vector::forward()` towards
+    /// `forward` and `Vector::up()` as close as possible to `up`. `forward` and `up` should not
+    /// be parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Vector};
+    /// let q = Quaternion::new_look_rot(Vector::new(1.0, 0.0, 0.0), Vector::up());
+    /// let m = Matrix::ident().rot(q);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((m * Vector::forward() - Vector::new(1.0, 0.0, 0.0)).len() < EPSILON);
+    /// assert!((m * Vector::up() - Vector::up()).len() < EPSILON);
+    /// ```
+    pub fn new_look_rot(forward: Vector<T>, up: Vector<T>) -> Quaternion<T> {
+        let z = forward.norm();
+        let x = up.cross(z).norm();
+        let y = z.cross(x);
+
+        Quaternion::rotation(x, y, z)
+    }
+
+    /// Creates a quaternion equivalent to the rotation of a matrix. Scaling is removed from the
+    /// matrix, but it must not mirror.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Vector};
+    /// let q = Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 1.0);
+    /// let m = Matrix::ident().scale(Vector::new_unf(2.0)).rot(q);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((Quaternion::from_matrix(m).dot(q) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn from_matrix(matrix: Matrix<T>) -> Quaternion<T> {
+        let m = matrix.array;
+
+        let x = Vector::new(m[0], m[1], m[2]).norm();
+        let y = Vector::new(m[4], m[5], m[6]).norm();
+        let z = Vector::new(m[8], m[9], m[10]).norm();
+
+        Quaternion::rotation(x, y, z)
+    }
+
+    /// Creates the control quaternion of `current` used by `squad` to pass smoothly from the
+    /// rotation `prev` through `current` to `next`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Vector};
+    /// let q1 = Quaternion::new_rot(Vector::up(), 0.0);
+    /// let q2 = Quaternion::new_rot(Vector::up(), 1.0);
+    /// let q3 = Quaternion::new_rot(Vector::up(), 2.0);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// // Rotations around a single axis need no correction.
+    /// assert!((Quaternion::new_squad_ctrl(q1, q2, q3).dot(q2) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn new_squad_ctrl(prev: Quaternion<T>, current: Quaternion<T>,
+                          next: Quaternion<T>) -> Quaternion<T> {
+        let inv = current.conj();
+
+        let to_prev = (inv * current.align(prev)).ln();
+        let to_next = (inv * current.align(next)).ln();
+
+        current * Quaternion::exp((to_prev + to_next) * (-T::one() / T::from_f64(4.0)))
+    }
+
     /// Creates an identity (0.0, 0.0, 0.0, 1.0) quaternion.
     ///
     /// # Examples
@@ -115,8 +255,8 @@ impl Quaternion {
     /// # use anima_engine::math::Quaternion;
     /// assert_eq!(Quaternion::ident(), Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 });
     /// ```
-    pub fn ident() -> Quaternion {
-        Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    pub fn ident() -> Quaternion<T> {
+        Quaternion { x: T::zero(), y: T::zero(), z: T::zero(), w: T::one() }
     }
 
     /// Computes the conjugate of a quaternion.
@@ -129,7 +269,7 @@ impl Quaternion {
     ///
     /// assert_eq!(q.conj(), Quaternion { x: -1.0, y: -1.0, z: -1.0, w: 1.0 });
     /// ```
-    pub fn conj(&self) -> Quaternion {
+    pub fn conj(&self) -> Quaternion<T> {
         Quaternion { x: -self.x, y: -self.y, z: -self.z, w: self.w }
     }
 
@@ -146,7 +286,7 @@ impl Quaternion {
     ///
     /// assert_eq!(result.x, identity.x);
     /// ```
-    pub fn inv(&self) -> Quaternion {
+    pub fn inv(&self) -> Quaternion<T> {
         let norm = self.x.powi(2) +
                    self.y.powi(2) +
                    self.z.powi(2) +
@@ -171,7 +311,7 @@ impl Quaternion {
     ///
     /// assert_eq!(q1.dot(q2), 12.0);
     /// ```
-    pub fn dot(&self, other: Quaternion) -> f32 {
+    pub fn dot(&self, other: Quaternion<T>) -> T {
         self.x * other.x +
         self.y * other.y +
         self.z * other.z +
@@ -190,8 +330,294 @@ impl Quaternion {
     ///
     /// assert_eq!(Quaternion::ident().angle(q), consts::PI / 2.0);
     /// ```
-    pub fn angle(&self, other: Quaternion) -> f32 {
-        self.dot(other).acos() * 2.0
+    pub fn angle(&self, other: Quaternion<T>) -> T {
+        self.dot(other).acos() * T::from_f64(2.0)
+    }
+
+    /// Computes the length of a quaternion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Quaternion;
+    /// let q = Quaternion::new(1.0, 1.0, 1.0, 1.0);
+    ///
+    /// assert_eq!(q.len(), 2.0);
+    /// ```
+    pub fn len(&self) -> T {
+        self.dot(*self).sqrt()
+    }
+
+    /// Computes the normalized version of a quaternion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Quaternion;
+    /// let q = Quaternion::new(1.0, 1.0, 1.0, 1.0);
+    ///
+    /// assert_eq!(q.norm(), Quaternion::new(0.5, 0.5, 0.5, 0.5));
+    /// ```
+    pub fn norm(&self) -> Quaternion<T> {
+        let len = self.len();
+
+        Quaternion {
+            x: self.x / len,
+            y: self.y / len,
+            z: self.z / len,
+            w: self.w / len
+        }
+    }
+
+    /// Computes the normalized direction and the angle in radians of the rotation equivalent to
+    /// a unit quaternion. The identity returns `Vector::new(1.0, 0.0, 0.0)` and `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Vector};
+    /// let q = Quaternion::new_rot(Vector::new(0.0, 2.0, 0.0), 1.5);
+    /// let (direction, angle) = q.axis_angle();
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((direction - Vector::up()).len() < EPSILON);
+    /// assert!((angle - 1.5).abs() < EPSILON);
+    /// ```
+    pub fn axis_angle(&self) -> (Vector<T>, T) {
+        let w = if self.w > T::one() {
+            T::one()
+        } else if self.w < -T::one() {
+            -T::one()
+        } else {
+            self.w
+        };
+
+        let sin = (T::one() - w * w).sqrt();
+
+        if sin < T::from_f64(1e-6) {
+            (Vector::new(T::one(), T::zero(), T::zero()), T::zero())
+        } else {
+            let direction = Vector::new(self.x / sin, self.y / sin, self.z / sin);
+
+            (direction, w.acos() * T::from_f64(2.0))
+        }
+    }
+
+    /// Computes the angles in radians of the rotations around the *x*, *y* and *z* axes that,
+    /// applied in `order`, are equivalent to a unit quaternion. The angle of the second axis
+    /// lies between -π/2 and π/2. When it reaches either bound, the rotation around the third
+    /// axis is set to `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{EulerOrder, Quaternion, Vector};
+    /// let angles = Vector::new(0.1, 0.2, 0.3);
+    /// let q = Quaternion::new_euler(angles, EulerOrder::Zxy);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((q.to_euler(EulerOrder::Zxy) - angles).len() < EPSILON);
+    /// ```
+    pub fn to_euler(self, order: EulerOrder) -> Vector<T> {
+        let (i, j, k) = order.axes();
+        let m = self.to_matrix().array;
+        let r = |row: usize, column: usize| m[column * 4 + row];
+
+        // Cyclic orders flip the signs of the off-diagonal elements.
+        let sign = if j == (i + 1) % 3 { T::one() } else { -T::one() };
+        let sin = -sign * r(k, i);
+
+        let mut angles = [T::zero(); 3];
+
+        if sin.abs() < T::from_f64(1.0 - 1e-6) {
+            angles[i] = (sign * r(k, j)).atan2(r(k, k));
+            angles[j] = sin.asin();
+            angles[k] = (sign * r(j, i)).atan2(r(i, i));
+        } else {
+            let half_pi = T::pi() / T::from_f64(2.0);
+
+            angles[i] = (-sign * r(j, k)).atan2(r(j, j));
+            angles[j] = if sin > T::zero() { half_pi } else { -half_pi };
+        }
+
+        Vector::new(angles[0], angles[1], angles[2])
+    }
+
+    /// Computes the rotation matrix equivalent to a unit quaternion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Vector};
+    /// let q = Quaternion::new_rot(Vector::up(), 1.0);
+    ///
+    /// assert_eq!(q.to_matrix(), Matrix::ident().rot(q));
+    /// ```
+    pub fn to_matrix(self) -> Matrix<T> {
+        Matrix::ident().rot(self)
+    }
+
+    /// Spherically interpolates between two unit quaternions along the shortest path, at constant
+    /// speed. `ratio` should be between `0.0` and `1.0`. Nearly identical rotations fall back to
+    /// `nlerp`. This is also how quaternions `interpolate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Vector};
+    /// let q1 = Quaternion::new_rot(Vector::up(), 0.5);
+    /// let q2 = Quaternion::new_rot(Vector::up(), 1.5);
+    /// let q3 = Quaternion::new_rot(Vector::up(), 1.0);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((q1.slerp(q2, 0.5).dot(q3) - 1.0).abs() < EPSILON);
+    /// assert_eq!(q1.slerp(q1, 0.5), q1);
+    /// ```
+    pub fn slerp(&self, other: Quaternion<T>, ratio: T) -> Quaternion<T> {
+        let other = self.align(other);
+        let cos = self.dot(other);
+
+        if cos > T::from_f64(0.9995) {
+            return self.nlerp(other, ratio);
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+
+        self.blend(((T::one() - ratio) * angle).sin() / sin, other, (ratio * angle).sin() / sin)
+    }
+
+    /// Linearly interpolates between two unit quaternions along the shortest path and normalizes
+    /// the result. Cheaper than `slerp`, but faster around the middle of the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Vector};
+    /// let q1 = Quaternion::new_rot(Vector::up(), 0.5);
+    /// let q2 = Quaternion::new_rot(Vector::up(), 1.5);
+    /// let q3 = Quaternion::new_rot(Vector::up(), 1.0);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((q1.nlerp(q2, 0.5).dot(q3) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn nlerp(&self, other: Quaternion<T>, ratio: T) -> Quaternion<T> {
+        self.blend(T::one() - ratio, self.align(other), ratio).norm()
+    }
+
+    /// Interpolates between two unit quaternions with spherical quadrangle interpolation, using
+    /// the control quaternions created with `new_squad_ctrl`. Consecutive interpolations sharing
+    /// a quaternion and its control join smoothly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Vector};
+    /// let q1 = Quaternion::new_rot(Vector::up(), 0.0);
+    /// let q2 = Quaternion::new_rot(Vector::right(), 1.0);
+    ///
+    /// let c1 = Quaternion::new_squad_ctrl(q1, q1, q2);
+    /// let c2 = Quaternion::new_squad_ctrl(q1, q2, q2);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((q1.squad(c1, c2, q2, 0.0).dot(q1) - 1.0).abs() < EPSILON);
+    /// assert!((q1.squad(c1, c2, q2, 1.0).dot(q2) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn squad(&self, self_ctrl: Quaternion<T>, other_ctrl: Quaternion<T>, other: Quaternion<T>,
+                 ratio: T) -> Quaternion<T> {
+        let path = self.slerp(other, ratio);
+        let ctrl = self_ctrl.slerp(other_ctrl, ratio);
+
+        path.slerp(ctrl, T::from_f64(2.0) * ratio * (T::one() - ratio))
+    }
+
+    /// Converts a quaternion to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DQuaternion, Quaternion};
+    /// let q = DQuaternion::new(0.1, 0.2, 0.3, 0.4);
+    ///
+    /// assert_eq!(q.cast::<f32>(), Quaternion::new(0.1, 0.2, 0.3, 0.4));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Quaternion<U> {
+        Quaternion {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+            w: U::from_f64(self.w.to_f64())
+        }
+    }
+
+    fn align(&self, other: Quaternion<T>) -> Quaternion<T> {
+        if self.dot(other) < T::zero() {
+            Quaternion::new(-other.x, -other.y, -other.z, -other.w)
+        } else {
+            other
+        }
+    }
+
+    fn blend(&self, ratio: T, other: Quaternion<T>, other_ratio: T) -> Quaternion<T> {
+        Quaternion {
+            x: self.x * ratio + other.x * other_ratio,
+            y: self.y * ratio + other.y * other_ratio,
+            z: self.z * ratio + other.z * other_ratio,
+            w: self.w * ratio + other.w * other_ratio
+        }
+    }
+
+    fn ln(&self) -> Vector<T> {
+        let v = Vector::new(self.x, self.y, self.z);
+        let sin = v.len();
+
+        if sin < T::from_f64(1e-6) {
+            v
+        } else {
+            v * (sin.atan2(self.w) / sin)
+        }
+    }
+
+    fn exp(v: Vector<T>) -> Quaternion<T> {
+        let angle = v.len();
+
+        if angle < T::from_f64(1e-6) {
+            Quaternion::new(v.x, v.y, v.z, T::one()).norm()
+        } else {
+            let v = v * (angle.sin() / angle);
+
+            Quaternion::new(v.x, v.y, v.z, angle.cos())
+        }
+    }
+
+    fn rotation(x: Vector<T>, y: Vector<T>, z: Vector<T>) -> Quaternion<T> {
+        let one = T::one();
+        let two = T::from_f64(2.0);
+        let four = T::from_f64(4.0);
+        let trace = x.x + y.y + z.z;
+
+        if trace > T::zero() {
+            let s = (trace + one).sqrt() * two;
+
+            Quaternion::new((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s, s / four)
+        } else if x.x > y.y && x.x > z.z {
+            let s = (one + x.x - y.y - z.z).sqrt() * two;
+
+            Quaternion::new(s / four, (y.x + x.y) / s, (z.x + x.z) / s, (y.z - z.y) / s)
+        } else if y.y > z.z {
+            let s = (one + y.y - x.x - z.z).sqrt() * two;
+
+            Quaternion::new((y.x + x.y) / s, s / four, (z.y + y.z) / s, (z.x - x.z) / s)
+        } else {
+            let s = (one + z.z - x.x - y.y).sqrt() * two;
+
+            Quaternion::new((z.x + x.z) / s, (z.y + y.z) / s, s / four, (x.y - y.x) / s)
+        }
     }
 }
 
@@ -201,10 +627,10 @@ use mrusty::*;
 
 use math::Interpolate;
 
-impl Mul for Quaternion {
-    type Output = Quaternion;
+impl<T: Scalar> Mul for Quaternion<T> {
+    type Output = Quaternion<T>;
 
-    fn mul(self, other: Quaternion) -> Quaternion {
+    fn mul(self, other: Quaternion<T>) -> Quaternion<T> {
         Quaternion {
             x: other.w * self.x + other.x * self.w + other.y * self.z - other.z * self.y,
             y: other.w * self.y - other.x * self.z + other.y * self.w + other.z * self.x,
@@ -214,23 +640,36 @@ impl Mul for Quaternion {
     }
 }
 
-impl Interpolate for Quaternion {
-    fn interpolate(&self, other: Quaternion, ratio: f32) -> Quaternion {
-        let cos_htheta = self.dot(other);
-        let htheta = cos_htheta.acos();
-        let sin_htheta = htheta.sin();
-
-        if sin_htheta == 0.0 { panic!("Cannot interpolate between two opposing rotations."); }
+impl From<Quaternion<f32>> for Quaternion<f64> {
+    fn from(quaternion: Quaternion<f32>) -> Quaternion<f64> {
+        quaternion.cast()
+    }
+}
 
-        let ratio1 = ((1.0 - ratio) * htheta).sin() / sin_htheta;
-        let ratio2 = (ratio * htheta).sin() / sin_htheta;
+impl<T: Scalar> Interpolate<T> for Quaternion<T> {
+    fn interpolate(&self, other: Quaternion<T>, ratio: T) -> Quaternion<T> {
+        self.slerp(other, ratio)
+    }
+}
 
-        Quaternion {
-            x: self.x * ratio1 + other.x * ratio2,
-            y: self.y * ratio1 + other.y * ratio2,
-            z: self.z * ratio1 + other.z * ratio2,
-            w: self.w * ratio1 + other.w * ratio2
+fn euler_order(mruby: MrubyType, args: &[Value]) -> Result<EulerOrder, Value> {
+    match args.len() {
+        0 => Ok(EulerOrder::Xyz),
+        1 => {
+            match args[0].to_str() {
+                Ok("xyz") => Ok(EulerOrder::Xyz),
+                Ok("xzy") => Ok(EulerOrder::Xzy),
+                Ok("yxz") => Ok(EulerOrder::Yxz),
+                Ok("yzx") => Ok(EulerOrder::Yzx),
+                Ok("zxy") => Ok(EulerOrder::Zxy),
+                Ok("zyx") => Ok(EulerOrder::Zyx),
+                _         => {
+                    Err(mruby.raise("ArgumentError",
+                                    "order must be one of :xyz, :xzy, :yxz, :yzx, :zxy, :zyx"))
+                }
+            }
         }
+        _ => Err(mruby.raise("ArgumentError", "wrong number of arguments"))
     }
 }
 
@@ -251,8 +690,25 @@ mrusty_class!(Quaternion, {
         mruby.obj(quaternion)
     });
 
+    def_self!("euler", |mruby, _slf: Value, angles: Vector; args| {
+        let order = match euler_order(mruby.clone(), &args) {
+            Ok(order)  => order,
+            Err(error) => return error
+        };
+
+        mruby.obj(Quaternion::new_euler((*angles).clone(), order))
+    });
+
+    def_self!("look_rotation", |mruby, _slf: Value, forward: Vector, up: Vector| {
+        mruby.obj(Quaternion::new_look_rot((*forward).clone(), (*up).clone()))
+    });
+
+    def_self!("from_matrix", |mruby, _slf: Value, matrix: Matrix| {
+        mruby.obj(Quaternion::from_matrix((*matrix).clone()))
+    });
+
     def_self!("identity", |mruby, _slf: Value| {
-        mruby.obj(Quaternion::ident())
+        mruby.obj(Quaternion::<f32>::ident())
     });
 
     def!("x", |mruby, slf: Quaternion| {
@@ -307,19 +763,187 @@ mrusty_class!(Quaternion, {
         mruby.float(slf.angle((*other).clone()) as f64)
     });
 
+    def!("len", |mruby, slf: Quaternion| {
+        mruby.float(slf.len() as f64)
+    });
+
+    def!("norm", |mruby, slf: Quaternion| {
+        mruby.obj(slf.norm())
+    });
+
+    def!("axis_angle", |mruby, slf: Quaternion| {
+        let (direction, angle) = slf.axis_angle();
+
+        mruby.array(vec![mruby.obj(direction), mruby.float(angle as f64)])
+    });
+
+    def!("to_euler", |mruby, slf: Quaternion; args| {
+        let order = match euler_order(mruby.clone(), &args) {
+            Ok(order)  => order,
+            Err(error) => return error
+        };
+
+        mruby.obj(slf.to_euler(order))
+    });
+
+    def!("to_matrix", |mruby, slf: Quaternion| {
+        mruby.obj(slf.to_matrix())
+    });
+
+    def_self!("squad_control", |mruby, _slf: Value, prev: Quaternion, current: Quaternion,
+                                next: Quaternion| {
+        let ctrl = Quaternion::new_squad_ctrl((*prev).clone(), (*current).clone(),
+                                              (*next).clone());
+
+        mruby.obj(ctrl)
+    });
+
+    def!("slerp", |mruby, slf: Quaternion, other: Quaternion, ratio: f64| {
+        mruby.obj(slf.slerp((*other).clone(), ratio as f32))
+    });
+
+    def!("nlerp", |mruby, slf: Quaternion, other: Quaternion, ratio: f64| {
+        mruby.obj(slf.nlerp((*other).clone(), ratio as f32))
+    });
+
+    def!("squad", |mruby, slf: Quaternion, slf_ctrl: Quaternion, other_ctrl: Quaternion,
+                   other: Quaternion, ratio: f64| {
+        let result = slf.squad((*slf_ctrl).clone(), (*other_ctrl).clone(), (*other).clone(),
+                               ratio as f32);
+
+        mruby.obj(result)
+    });
+
     def!("interpolate", |mruby, slf: Quaternion, other: Quaternion, ratio: f64| {
         mruby.obj(slf.interpolate((*other).clone(), ratio as f32))
     });
 });
 
+/// A `struct` useful for smoothly interpolating through evenly spaced rotations, e.g. the
+/// orientations along a camera path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquadPath<T = f32> {
+    /// `Vec<Quaternion<T>>` of normalized keys; each on the same side as the previous one
+    pub keys: Vec<Quaternion<T>>,
+    /// `Vec<Quaternion<T>>` containing the control quaternions of the keys with the same indices
+    pub ctrls: Vec<Quaternion<T>>
+}
+
+impl<T: Scalar> SquadPath<T> {
+    /// Creates a squad path passing through `keys`. Panics if `keys` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, SquadPath};
+    /// let q1 = Quaternion::new(0.0, 0.0, 0.0, 2.0);
+    /// let q2 = Quaternion::new(0.0, 0.0, 0.0, -2.0);
+    /// let p = SquadPath::new(vec![q1, q2]);
+    ///
+    /// assert_eq!(p.keys, vec![Quaternion::ident(), Quaternion::ident()]);
+    /// ```
+    pub fn new(keys: Vec<Quaternion<T>>) -> SquadPath<T> {
+        if keys.is_empty() { panic!("Squad path cannot be empty."); }
+
+        let mut aligned: Vec<Quaternion<T>> = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let key = match aligned.last() {
+                Some(last) => last.align(key.norm()),
+                None       => key.norm()
+            };
+
+            aligned.push(key);
+        }
+
+        let last = aligned.len().saturating_sub(1);
+        let ctrls = (0..aligned.len()).map(|i| {
+            let prev = aligned[i.saturating_sub(1)];
+            let next = aligned[if i < last { i + 1 } else { last }];
+
+            Quaternion::new_squad_ctrl(prev, aligned[i], next)
+        }).collect();
+
+        SquadPath {
+            keys: aligned,
+            ctrls: ctrls
+        }
+    }
+
+    /// Computes the rotation on a squad path correspoding to a `ratio` (clamped between `0.0` and
+    /// `1.0`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, SquadPath, Vector};
+    /// let p = SquadPath::new(vec![
+    ///     Quaternion::new_rot(Vector::up(), 0.0),
+    ///     Quaternion::new_rot(Vector::up(), 1.0),
+    ///     Quaternion::new_rot(Vector::up(), 2.0)
+    /// ]);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((p.interpolate(0.5).dot(p.keys[1]) - 1.0).abs() < EPSILON);
+    /// assert!((p.interpolate(1.5).dot(p.keys[2]) - 1.0).abs() < EPSILON);
+    /// ```
+    pub fn interpolate(&self, ratio: T) -> Quaternion<T> {
+        let segments = match self.keys.len() {
+            0 => panic!("Cannot interpolate an empty path."),
+            1 => return self.keys[0],
+            len => len - 1
+        };
+
+        let ratio = if ratio < T::zero() {
+            T::zero()
+        } else if ratio > T::one() {
+            T::one()
+        } else {
+            ratio
+        };
+
+        let position = ratio * T::from_f64(segments as f64);
+        let i = (position.to_f64() as usize).min(segments - 1);
+        let ratio = position - T::from_f64(i as f64);
+
+        self.keys[i].squad(self.ctrls[i], self.ctrls[i + 1], self.keys[i + 1], ratio)
+    }
+}
+
+mrusty_class!(SquadPath, {
+    def!("initialize", |mruby, keys: Vec| {
+        if keys.is_empty() {
+            return mruby.raise("ArgumentError", "path should have at least one Quaternion");
+        }
+
+        let mut quaternions = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            match key.to_obj::<Quaternion>() {
+                Ok(quaternion) => quaternions.push((*quaternion).clone()),
+                Err(_)         => {
+                    return mruby.raise("ArgumentError", "Array should contain Quaternion")
+                }
+            }
+        }
+
+        SquadPath::new(quaternions)
+    });
+
+    def!("interpolate", |mruby, slf: SquadPath, ratio: f64| {
+        mruby.obj(slf.interpolate(ratio as f32))
+    });
+});
+
 #[cfg(test)]
 mod tests {
     use mrusty::*;
 
     use super::Quaternion;
-    use super::super::Vector;
+    use super::super::{Matrix, Vector};
 
-    describe!(Quaternion, (Vector), "
+    describe!(Quaternion, (Vector, Matrix), "
       context 'when roation' do
         subject { Quaternion.rotation(Vector.up, Math::PI / 2) }
         let(:second) { Quaternion.sph_rotation(Vector.forward, Vector.right) }
@@ -353,6 +977,53 @@ mod tests {
           expect(interpolated.z).to be_within(0.001).of correct.z
           expect(interpolated.w).to be_within(0.001).of correct.w
         end
+
+        it 'converts to axis and angle on #axis_angle' do
+          direction, angle = subject.axis_angle
+
+          expect((direction - Vector.up).len).to be_within(0.001).of 0.0
+          expect(angle).to be_within(0.001).of Math::PI / 2
+        end
+
+        it 'converts to Euler angles on #to_euler' do
+          expect((subject.to_euler(:xzy) - Vector.new(0.0, Math::PI / 2, 0.0)).len)
+            .to be_within(0.001).of 0.0
+        end
+
+        it 'converts from Euler angles on .euler' do
+          euler = Quaternion.euler(Vector.new(0.0, Math::PI / 2, 0.0), :yxz)
+
+          expect(euler.dot subject).to be_within(0.001).of 1.0
+        end
+
+        it 'converts from look rotation on .look_rotation' do
+          look = Quaternion.look_rotation(Vector.new(1.0, 0.0, 0.0), Vector.up)
+
+          expect(look.dot subject).to be_within(0.001).of 1.0
+        end
+
+        it 'interpolates with itself on #slerp' do
+          expect(subject.slerp(subject, 0.5).dot subject).to be_within(0.001).of 1.0
+        end
+
+        it 'interpolates on the shortest path on #nlerp' do
+          opposite = Quaternion.new(-subject.x, -subject.y, -subject.z, -subject.w)
+
+          expect(subject.nlerp(opposite, 0.5).dot subject).to be_within(0.001).of 1.0
+        end
+
+        it 'interpolates through control quaternions on #squad' do
+          first = Quaternion.identity
+          ctrl = Quaternion.squad_control(first, first, subject)
+          subject_ctrl = Quaternion.squad_control(first, subject, subject)
+
+          expect(first.squad(ctrl, subject_ctrl, subject, 1.0).dot subject)
+            .to be_within(0.001).of 1.0
+        end
+
+        it 'converts to and from Matrix on #to_matrix and .from_matrix' do
+          expect(Quaternion.from_matrix(subject.to_matrix).dot subject).to be_within(0.001).of 1.0
+        end
       end
 
       context 'when unit' do
@@ -393,6 +1064,46 @@ mod tests {
         it 'multiplies quaternion on #*' do
           expect(subject * Quaternion.identity).to eql subject
         end
+
+        it 'computes length on #len' do
+          expect(subject.len).to eql 2.0
+        end
+
+        it 'normalizes on #norm' do
+          expect(subject.norm).to eql Quaternion.new 0.5, 0.5, 0.5, 0.5
+        end
+      end
+    ");
+}
+
+#[cfg(test)]
+mod test_squad_path {
+    use mrusty::*;
+
+    use super::Quaternion;
+    use super::SquadPath;
+    use super::super::Vector;
+
+    describe!(SquadPath, (Quaternion, Vector), "
+      context 'when formed of three keys' do
+        subject do
+          SquadPath.new [
+            Quaternion.rotation(Vector.up, 0.0),
+            Quaternion.rotation(Vector.up, 1.0),
+            Quaternion.rotation(Vector.up, 2.0)
+          ]
+        end
+
+        it 'interpolates Quaternions on #interpolate' do
+          expect(subject.interpolate(0.25).dot Quaternion.rotation(Vector.up, 0.5))
+            .to be_within(0.001).of 1.0
+        end
+      end
+
+      context 'when empty' do
+        it 'raises an ArgumentError on .new' do
+          expect { SquadPath.new [] }.to raise_error ArgumentError
+        end
       end
     ");
 }
@@ -0,0 +1,423 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::aabb::Aabb;
+use super::hit::Hit;
+use super::matrix::Matrix;
+use super::obb::Obb;
+use super::plane::Plane;
+use super::sphere::Sphere;
+use super::triangle::Triangle;
+use super::vector::Vector;
+
+/// A half-line `struct` useful for picking and line of sight tests. `Ray` holds `f32`s and `DRay`
+/// holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Aabb, Ray, Vector};
+/// let ray = Ray::new(Vector::new(0.0, 2.0, 0.0), Vector::new(2.0, 0.0, 0.0));
+/// let aabb = Aabb::new(Vector::new(2.0, 0.0, -1.0), Vector::new(4.0, 4.0, 1.0));
+///
+/// let hit = ray.hit_aabb(aabb).unwrap();
+///
+/// assert_eq!(ray.point(hit.distance), Vector::new(2.0, 2.0, 0.0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray<T = f32> {
+    /// `Vector<T>` starting point
+    pub origin: Vector<T>,
+    /// `Vector<T>` normalized direction
+    pub direction: Vector<T>
+}
+
+impl<T: Scalar> Ray<T> {
+    /// Creates a ray using an origin and a direction. The direction is normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Ray, Vector};
+    /// let ray = Ray::new(Vector::zero(), Vector::new(0.0, 2.0, 0.0));
+    ///
+    /// assert_eq!(ray, Ray { origin: Vector::zero(), direction: Vector::up() });
+    /// ```
+    pub fn new(origin: Vector<T>, direction: Vector<T>) -> Ray<T> {
+        Ray {
+            origin: origin,
+            direction: direction.norm()
+        }
+    }
+
+    /// Computes the point at `distance` along a ray.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Ray, Vector};
+    /// let ray = Ray::new(Vector::one(), Vector::up());
+    ///
+    /// assert_eq!(ray.point(2.0), Vector::new(1.0, 3.0, 1.0));
+    /// ```
+    pub fn point(&self, distance: T) -> Vector<T> {
+        self.origin + self.direction * distance
+    }
+
+    /// Computes the point on a ray closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Ray, Vector};
+    /// let ray = Ray::new(Vector::zero(), Vector::up());
+    ///
+    /// assert_eq!(ray.closest_point(Vector::new(1.0, 2.0, 0.0)), Vector::new(0.0, 2.0, 0.0));
+    /// assert_eq!(ray.closest_point(Vector::new(1.0, -2.0, 0.0)), Vector::zero());
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        self.point((point - self.origin).dot(self.direction).max(T::zero()))
+    }
+
+    /// Computes where a ray hits a plane. The normal faces the ray's origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Plane, Ray, Vector};
+    /// let ray = Ray::new(Vector::zero(), Vector::up());
+    ///
+    /// let p = Plane::new(Vector::down(), -2.0);
+    ///
+    /// assert_eq!(ray.hit_plane(p), Some(Hit::new(2.0, Vector::down())));
+    /// assert_eq!(ray.hit_plane(Plane::new(Vector::up(), -2.0)), None);
+    /// ```
+    pub fn hit_plane(&self, plane: Plane<T>) -> Option<Hit<T>> {
+        let cos = plane.normal.dot(self.direction);
+
+        if cos.abs() < T::from_f64(1e-6) {
+            return None;
+        }
+
+        let distance = -plane.dist(self.origin) / cos;
+
+        if distance < T::zero() {
+            None
+        } else if cos < T::zero() {
+            Some(Hit::new(distance, plane.normal))
+        } else {
+            Some(Hit::new(distance, -plane.normal))
+        }
+    }
+
+    /// Computes where a ray hits a sphere. A ray starting inside the sphere hits it on the way
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Ray, Sphere, Vector};
+    /// let ray = Ray::new(Vector::zero(), Vector::up());
+    ///
+    /// let outside = Sphere::new(Vector::new(0.0, 5.0, 0.0), 1.0);
+    /// let inside = Sphere::new(Vector::zero(), 1.0);
+    ///
+    /// assert_eq!(ray.hit_sphere(outside), Some(Hit::new(4.0, Vector::down())));
+    /// assert_eq!(ray.hit_sphere(inside), Some(Hit::new(1.0, Vector::up())));
+    /// ```
+    pub fn hit_sphere(&self, sphere: Sphere<T>) -> Option<Hit<T>> {
+        let to_origin = self.origin - sphere.center;
+
+        let b = to_origin.dot(self.direction);
+        let c = to_origin.dot(to_origin) - sphere.radius * sphere.radius;
+        let discriminant = b * b - c;
+
+        if discriminant < T::zero() {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let distance = if -b - root >= T::zero() { -b - root } else { -b + root };
+
+        if distance < T::zero() {
+            None
+        } else {
+            let normal = (self.point(distance) - sphere.center) * (T::one() / sphere.radius);
+
+            Some(Hit::new(distance, normal))
+        }
+    }
+
+    /// Computes where a ray hits an axis-aligned box. A ray starting inside the box hits it on the
+    /// way out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Hit, Ray, Vector};
+    /// let ray = Ray::new(Vector::new(0.5, 5.0, 0.5), Vector::down());
+    /// let aabb = Aabb::new(Vector::zero(), Vector::one());
+    ///
+    /// assert_eq!(ray.hit_aabb(aabb), Some(Hit::new(4.0, Vector::up())));
+    /// ```
+    pub fn hit_aabb(&self, aabb: Aabb<T>) -> Option<Hit<T>> {
+        let origin = [self.origin.x, self.origin.y, self.origin.z];
+        let direction = [self.direction.x, self.direction.y, self.direction.z];
+        let min = [aabb.min.x, aabb.min.y, aabb.min.z];
+        let max = [aabb.max.x, aabb.max.y, aabb.max.z];
+
+        let infinity = T::from_f64(f64::INFINITY);
+
+        // Slabs entered last and left first, with the outward normals of their faces.
+        let mut enter = (-infinity, 0, T::zero());
+        let mut exit = (infinity, 0, T::zero());
+
+        for i in 0..3 {
+            if direction[i].abs() < T::from_f64(1e-6) {
+                if origin[i] < min[i] || origin[i] > max[i] {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let to_min = (min[i] - origin[i]) / direction[i];
+            let to_max = (max[i] - origin[i]) / direction[i];
+
+            let (near, far, sign) = if direction[i] > T::zero() {
+                (to_min, to_max, -T::one())
+            } else {
+                (to_max, to_min, T::one())
+            };
+
+            if near > enter.0 {
+                enter = (near, i, sign);
+            }
+
+            if far < exit.0 {
+                exit = (far, i, -sign);
+            }
+        }
+
+        if enter.0 > exit.0 || exit.0 < T::zero() {
+            return None;
+        }
+
+        let (distance, axis, sign) = if enter.0 >= T::zero() { enter } else { exit };
+
+        let mut normal = [T::zero(); 3];
+        normal[axis] = sign;
+
+        Some(Hit::new(distance, Vector::new(normal[0], normal[1], normal[2])))
+    }
+
+    /// Computes where a ray hits an oriented box. A ray starting inside the box hits it on the way
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Ray, Vector};
+    /// # use std::f32::consts;
+    /// let ray = Ray::new(Vector::new(5.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0));
+    /// let rotation = Quaternion::new_rot(Vector::up(), consts::PI / 4.0);
+    /// let obb = Obb::new(Vector::zero(), rotation, Vector::one());
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!((ray.hit_obb(obb).unwrap().distance - (5.0 - 2.0f32.sqrt())).abs() < EPSILON);
+    /// ```
+    pub fn hit_obb(&self, obb: Obb<T>) -> Option<Hit<T>> {
+        let origin = obb.to_local(self.origin);
+        let local = Ray::new(origin, obb.to_local(self.origin + self.direction) - origin);
+
+        local.hit_aabb(obb.local_aabb()).map(|hit| {
+            Hit::new(hit.distance, obb.to_world(hit.normal) - obb.center)
+        })
+    }
+
+    /// Computes where a ray hits a triangle, from either side. The normal faces the ray's origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Ray, Triangle, Vector};
+    /// let ray = Ray::new(Vector::new(0.5, 5.0, 0.5), Vector::down());
+    /// let t = Triangle::new(Vector::zero(), Vector::new(0.0, 0.0, 2.0),
+    ///                       Vector::new(2.0, 0.0, 0.0));
+    ///
+    /// assert_eq!(ray.hit_triangle(t), Some(Hit::new(5.0, Vector::up())));
+    /// ```
+    pub fn hit_triangle(&self, triangle: Triangle<T>) -> Option<Hit<T>> {
+        let ab = triangle.b - triangle.a;
+        let ac = triangle.c - triangle.a;
+
+        let p = self.direction.cross(ac);
+        let det = ab.dot(p);
+
+        if det.abs() < T::from_f64(1e-9) {
+            return None;
+        }
+
+        let inv = T::one() / det;
+        let s = self.origin - triangle.a;
+        let u = s.dot(p) * inv;
+
+        if u < T::zero() || u > T::one() {
+            return None;
+        }
+
+        let q = s.cross(ab);
+        let v = self.direction.dot(q) * inv;
+
+        if v < T::zero() || u + v > T::one() {
+            return None;
+        }
+
+        let distance = ac.dot(q) * inv;
+
+        if distance < T::zero() {
+            return None;
+        }
+
+        let normal = triangle.normal();
+
+        if normal.dot(self.direction) > T::zero() {
+            Some(Hit::new(distance, -normal))
+        } else {
+            Some(Hit::new(distance, normal))
+        }
+    }
+
+    /// Converts a ray to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DRay, DVector, Ray, Vector};
+    /// let ray = DRay::new(DVector::zero(), DVector::up());
+    ///
+    /// assert_eq!(ray.cast::<f32>(), Ray::new(Vector::zero(), Vector::up()));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Ray<U> {
+        Ray {
+            origin: self.origin.cast(),
+            direction: self.direction.cast()
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+use super::hit;
+
+/// Transforms a ray. The direction is normalized again, so distances along the ray are measured
+/// after the transformation.
+impl<T: Scalar> Mul<Ray<T>> for Matrix<T> {
+    type Output = Ray<T>;
+
+    fn mul(self, ray: Ray<T>) -> Ray<T> {
+        let origin = self * ray.origin;
+
+        Ray::new(origin, self * (ray.origin + ray.direction) - origin)
+    }
+}
+
+impl From<Ray<f32>> for Ray<f64> {
+    fn from(ray: Ray<f32>) -> Ray<f64> {
+        ray.cast()
+    }
+}
+
+mrusty_class!(Ray, {
+    def!("initialize", |origin: Vector, direction: Vector| {
+        Ray::new((*origin).clone(), (*direction).clone())
+    });
+
+    def!("origin", |mruby, slf: Ray| {
+        mruby.obj(slf.origin)
+    });
+
+    def!("direction", |mruby, slf: Ray| {
+        mruby.obj(slf.direction)
+    });
+
+    def!("point", |mruby, slf: Ray, distance: f64| {
+        mruby.obj(slf.point(distance as f32))
+    });
+
+    def!("closest_point", |mruby, slf: Ray, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("hit", |mruby, slf: Ray, shape: Value| {
+        if let Ok(plane) = shape.to_obj::<Plane>() {
+            hit::to_value(&mruby, slf.hit_plane((*plane).clone()))
+        } else if let Ok(sphere) = shape.to_obj::<Sphere>() {
+            hit::to_value(&mruby, slf.hit_sphere((*sphere).clone()))
+        } else if let Ok(aabb) = shape.to_obj::<Aabb>() {
+            hit::to_value(&mruby, slf.hit_aabb((*aabb).clone()))
+        } else if let Ok(obb) = shape.to_obj::<Obb>() {
+            hit::to_value(&mruby, slf.hit_obb((*obb).clone()))
+        } else if let Ok(triangle) = shape.to_obj::<Triangle>() {
+            hit::to_value(&mruby, slf.hit_triangle((*triangle).clone()))
+        } else {
+            mruby.raise("ArgumentError", "expecting Plane, Sphere, Aabb, Obb or Triangle")
+        }
+    });
+
+    def!("transform", |mruby, slf: Ray, matrix: Matrix| {
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Ray;
+    use super::super::{Aabb, Matrix, Obb, Plane, Quaternion, Sphere, Vector};
+
+    describe!(Ray, (Aabb, Matrix, Obb, Plane, Quaternion, Sphere, Vector), "
+      context 'when pointing up' do
+        subject { Ray.new Vector.zero, Vector.new(0.0, 2.0, 0.0) }
+
+        it 'returns origin on #origin' do
+          expect(subject.origin).to eql Vector.zero
+        end
+
+        it 'returns normalized direction on #direction' do
+          expect(subject.direction).to eql Vector.up
+        end
+
+        it 'computes points on #point' do
+          expect(subject.point 2.0).to eql Vector.new(0.0, 2.0, 0.0)
+        end
+
+        it 'computes closest point on #closest_point' do
+          expect(subject.closest_point Vector.new(1.0, 2.0, 0.0)).to eql Vector.new(0.0, 2.0, 0.0)
+        end
+
+        it 'hits shapes on #hit' do
+          distance, normal = subject.hit(Sphere.new(Vector.new(0.0, 5.0, 0.0), 1.0))
+
+          expect(distance).to eql 4.0
+          expect(normal).to eql Vector.down
+          expect(subject.hit Plane.new(Vector.up, -1.0)).to be_nil
+          expect(subject.hit Aabb.new(Vector.uniform(-1.0), Vector.one)).not_to be_nil
+          expect(subject.hit Obb.new(Vector.zero, Quaternion.identity, Vector.one)).not_to be_nil
+        end
+
+        it 'transforms on #transform' do
+          expect(subject.transform(Matrix.identity.trans(Vector.up)).origin).to eql Vector.up
+        end
+      end
+    ");
+}
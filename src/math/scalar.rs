@@ -0,0 +1,112 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::simd::Kernels;
+
+/// A `trait` implemented by the floating point types math constructs can be built upon, namely
+/// `f32` and `f64`.
+///
+/// `Matrix` products, inverses and batch transforms go through kernels picked per type. `f32`
+/// uses SIMD versions on x86, which give the same products and transforms bit for bit as the
+/// portable ones and the same inverses up to rounding.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{DVector, Scalar, Vector};
+/// fn half<T: Scalar>(value: T) -> T {
+///     value / T::from_f64(2.0)
+/// }
+///
+/// assert_eq!(half(1.0f32), 0.5);
+/// assert_eq!(half(1.0f64), 0.5);
+///
+/// let v = DVector::from(Vector::new(1.0, 2.0, 3.0));
+///
+/// assert_eq!(v.cast::<f32>(), Vector::new(1.0, 2.0, 3.0));
+/// ```
+pub trait Scalar: Copy + Debug + PartialEq + PartialOrd + 'static +
+                  Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> +
+                  Div<Output = Self> + Neg<Output = Self> + Kernels {
+    /// Returns `0.0`.
+    fn zero() -> Self;
+    /// Returns `1.0`.
+    fn one() -> Self;
+    /// Returns π.
+    fn pi() -> Self;
+    /// Converts from `f64`, rounding if needed.
+    fn from_f64(value: f64) -> Self;
+    /// Converts to `f64` losslessly.
+    fn to_f64(self) -> f64;
+
+    /// Returns the absolute value.
+    fn abs(self) -> Self;
+    /// Returns the smaller of two values.
+    fn min(self, other: Self) -> Self;
+    /// Returns the larger of two values.
+    fn max(self, other: Self) -> Self;
+    /// Returns the square root.
+    fn sqrt(self) -> Self;
+    /// Raises to the integer power `n`.
+    fn powi(self, n: i32) -> Self;
+    /// Returns the sine of an angle in radians.
+    fn sin(self) -> Self;
+    /// Returns the cosine of an angle in radians.
+    fn cos(self) -> Self;
+    /// Returns the tangent of an angle in radians.
+    fn tan(self) -> Self;
+    /// Returns the arcsine in radians, in [-π/2, π/2].
+    fn asin(self) -> Self;
+    /// Returns the arccosine in radians, in [0, π].
+    fn acos(self) -> Self;
+    /// Returns the four quadrant arctangent of `self` (*y*) and `other` (*x*) in radians, in
+    /// [-π, π].
+    fn atan2(self, other: Self) -> Self;
+}
+
+impl Scalar for f32 {
+    fn zero() -> f32 { 0.0 }
+    fn one() -> f32 { 1.0 }
+    fn pi() -> f32 { ::std::f32::consts::PI }
+    fn from_f64(value: f64) -> f32 { value as f32 }
+    fn to_f64(self) -> f64 { self as f64 }
+
+    fn abs(self) -> f32 { self.abs() }
+    fn min(self, other: f32) -> f32 { self.min(other) }
+    fn max(self, other: f32) -> f32 { self.max(other) }
+    fn sqrt(self) -> f32 { self.sqrt() }
+    fn powi(self, n: i32) -> f32 { self.powi(n) }
+    fn sin(self) -> f32 { self.sin() }
+    fn cos(self) -> f32 { self.cos() }
+    fn tan(self) -> f32 { self.tan() }
+    fn asin(self) -> f32 { self.asin() }
+    fn acos(self) -> f32 { self.acos() }
+    fn atan2(self, other: f32) -> f32 { self.atan2(other) }
+}
+
+impl Scalar for f64 {
+    fn zero() -> f64 { 0.0 }
+    fn one() -> f64 { 1.0 }
+    fn pi() -> f64 { ::std::f64::consts::PI }
+    fn from_f64(value: f64) -> f64 { value }
+    fn to_f64(self) -> f64 { self }
+
+    fn abs(self) -> f64 { self.abs() }
+    fn min(self, other: f64) -> f64 { self.min(other) }
+    fn max(self, other: f64) -> f64 { self.max(other) }
+    fn sqrt(self) -> f64 { self.sqrt() }
+    fn powi(self, n: i32) -> f64 { self.powi(n) }
+    fn sin(self) -> f64 { self.sin() }
+    fn cos(self) -> f64 { self.cos() }
+    fn tan(self) -> f64 { self.tan() }
+    fn asin(self) -> f64 { self.asin() }
+    fn acos(self) -> f64 { self.acos() }
+    fn atan2(self, other: f64) -> f64 { self.atan2(other) }
+}
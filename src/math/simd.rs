@@ -0,0 +1,430 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Kernels behind `Matrix` products, inverses and batch transforms. Matrices are column-major
+//! `[T; 16]` arrays. The functions in this module are the portable versions; `packed` holds the
+//! `f32` ones, which use SSE on x86 and fall back to the portable versions elsewhere.
+//!
+//! Products and transforms add up their terms in the same order in both versions, so they agree
+//! bit for bit. Inverses only agree up to rounding.
+
+use math::Scalar;
+use super::vector::Vector;
+
+pub fn mul<T: Scalar>(l: &[T; 16], r: &[T; 16]) -> [T; 16] {
+    let mut result = [T::zero(); 16];
+
+    for i in 0..4 {
+        let column = mul_vec(l, [r[i * 4], r[i * 4 + 1], r[i * 4 + 2], r[i * 4 + 3]]);
+
+        result[i * 4..i * 4 + 4].copy_from_slice(&column);
+    }
+
+    result
+}
+
+pub fn mul_vec<T: Scalar>(m: &[T; 16], v: [T; 4]) -> [T; 4] {
+    [
+        m[0] * v[0] + m[4] * v[1] + m[8]  * v[2] + m[12] * v[3],
+        m[1] * v[0] + m[5] * v[1] + m[9]  * v[2] + m[13] * v[3],
+        m[2] * v[0] + m[6] * v[1] + m[10] * v[2] + m[14] * v[3],
+        m[3] * v[0] + m[7] * v[1] + m[11] * v[2] + m[15] * v[3]
+    ]
+}
+
+pub fn inv<T: Scalar>(m: &[T; 16]) -> Option<[T; 16]> {
+    let (s, c) = minors(m);
+    let det = det_minors(&s, &c);
+
+    if det == T::zero() { return None; }
+
+    let [s0, s1, s2, s3, s4, s5] = s;
+    let [c0, c1, c2, c3, c4, c5] = c;
+
+    let inv_det = T::one() / det;
+
+    Some([
+        ( m[5] * c5 - m[9]  * c4 + m[13] * c3) * inv_det,
+        (-m[1] * c5 + m[9]  * c2 - m[13] * c1) * inv_det,
+        ( m[1] * c4 - m[5]  * c2 + m[13] * c0) * inv_det,
+        (-m[1] * c3 + m[5]  * c1 - m[9]  * c0) * inv_det,
+        (-m[4] * c5 + m[8]  * c4 - m[12] * c3) * inv_det,
+        ( m[0] * c5 - m[8]  * c2 + m[12] * c1) * inv_det,
+        (-m[0] * c4 + m[4]  * c2 - m[12] * c0) * inv_det,
+        ( m[0] * c3 - m[4]  * c1 + m[8]  * c0) * inv_det,
+        ( m[7] * s5 - m[11] * s4 + m[15] * s3) * inv_det,
+        (-m[3] * s5 + m[11] * s2 - m[15] * s1) * inv_det,
+        ( m[3] * s4 - m[7]  * s2 + m[15] * s0) * inv_det,
+        (-m[3] * s3 + m[7]  * s1 - m[11] * s0) * inv_det,
+        (-m[6] * s5 + m[10] * s4 - m[14] * s3) * inv_det,
+        ( m[2] * s5 - m[10] * s2 + m[14] * s1) * inv_det,
+        (-m[2] * s4 + m[6]  * s2 - m[14] * s0) * inv_det,
+        ( m[2] * s3 - m[6]  * s1 + m[10] * s0) * inv_det
+    ])
+}
+
+pub fn det<T: Scalar>(m: &[T; 16]) -> T {
+    let (s, c) = minors(m);
+
+    det_minors(&s, &c)
+}
+
+pub fn trans_points<T: Scalar>(m: &[T; 16], points: &mut [Vector<T>]) {
+    for point in points.iter_mut() {
+        let result = mul_vec(m, [point.x, point.y, point.z, T::one()]);
+
+        *point = Vector::new(result[0] / result[3], result[1] / result[3], result[2] / result[3]);
+    }
+}
+
+pub fn trans_dirs<T: Scalar>(m: &[T; 16], dirs: &mut [Vector<T>]) {
+    for dir in dirs.iter_mut() {
+        let result = mul_vec(m, [dir.x, dir.y, dir.z, T::zero()]);
+
+        *dir = Vector::new(result[0], result[1], result[2]);
+    }
+}
+
+/// A `trait` picking the kernels `Matrix` uses for a `Scalar`. `f64` uses the portable ones and
+/// `f32` the `packed` ones.
+pub trait Kernels: Sized {
+    /// Multiplies two column-major 4x4 matrices. Backs `Matrix * Matrix`.
+    fn mul_mat(l: &[Self; 16], r: &[Self; 16]) -> [Self; 16];
+    /// Multiplies a column-major 4x4 matrix by a 4 component vector. Backs `Matrix * Vector`.
+    fn mul_mat_vec(m: &[Self; 16], v: [Self; 4]) -> [Self; 4];
+    /// Inverts a column-major 4x4 matrix, returning `None` if it is not invertible. Backs
+    /// `Matrix::try_inv`.
+    fn inv_mat(m: &[Self; 16]) -> Option<[Self; 16]>;
+    /// Transforms points by a column-major 4x4 matrix in place. Backs `Matrix::trans_points`.
+    fn trans_points(m: &[Self; 16], points: &mut [Vector<Self>]);
+    /// Transforms directions by a column-major 4x4 matrix in place. Backs `Matrix::trans_dirs`.
+    fn trans_dirs(m: &[Self; 16], dirs: &mut [Vector<Self>]);
+}
+
+impl Kernels for f32 {
+    fn mul_mat(l: &[f32; 16], r: &[f32; 16]) -> [f32; 16] {
+        packed::mul(l, r)
+    }
+    fn mul_mat_vec(m: &[f32; 16], v: [f32; 4]) -> [f32; 4] {
+        packed::mul_vec(m, v)
+    }
+    fn inv_mat(m: &[f32; 16]) -> Option<[f32; 16]> {
+        packed::inv(m)
+    }
+    fn trans_points(m: &[f32; 16], points: &mut [Vector<f32>]) {
+        packed::trans_points(m, points)
+    }
+    fn trans_dirs(m: &[f32; 16], dirs: &mut [Vector<f32>]) {
+        packed::trans_dirs(m, dirs)
+    }
+}
+
+impl Kernels for f64 {
+    fn mul_mat(l: &[f64; 16], r: &[f64; 16]) -> [f64; 16] {
+        mul(l, r)
+    }
+    fn mul_mat_vec(m: &[f64; 16], v: [f64; 4]) -> [f64; 4] {
+        mul_vec(m, v)
+    }
+    fn inv_mat(m: &[f64; 16]) -> Option<[f64; 16]> {
+        inv(m)
+    }
+    fn trans_points(m: &[f64; 16], points: &mut [Vector<f64>]) {
+        self::trans_points(m, points)
+    }
+    fn trans_dirs(m: &[f64; 16], dirs: &mut [Vector<f64>]) {
+        self::trans_dirs(m, dirs)
+    }
+}
+
+// 2x2 minors of the top (s) and bottom (c) halves of the matrix.
+fn minors<T: Scalar>(m: &[T; 16]) -> ([T; 6], [T; 6]) {
+    let s = [
+        m[0] * m[5]  - m[1] * m[4],
+        m[0] * m[9]  - m[1] * m[8],
+        m[0] * m[13] - m[1] * m[12],
+        m[4] * m[9]  - m[5] * m[8],
+        m[4] * m[13] - m[5] * m[12],
+        m[8] * m[13] - m[9] * m[12]
+    ];
+
+    let c = [
+        m[2]  * m[7]  - m[3]  * m[6],
+        m[2]  * m[11] - m[3]  * m[10],
+        m[2]  * m[15] - m[3]  * m[14],
+        m[6]  * m[11] - m[7]  * m[10],
+        m[6]  * m[15] - m[7]  * m[14],
+        m[10] * m[15] - m[11] * m[14]
+    ];
+
+    (s, c)
+}
+
+fn det_minors<T: Scalar>(s: &[T; 6], c: &[T; 6]) -> T {
+    s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+}
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse"))]
+pub mod packed {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::super::vector::Vector;
+
+    // _mm_shuffle_ps taking lanes x and y from a and lanes z and w from b.
+    macro_rules! shuffle {
+        ( $a:expr, $b:expr, $x:expr, $y:expr, $z:expr, $w:expr ) => {
+            _mm_shuffle_ps($a, $b, $x | $y << 2 | $z << 4 | $w << 6)
+        };
+    }
+
+    macro_rules! swizzle {
+        ( $a:expr, $x:expr, $y:expr, $z:expr, $w:expr ) => {
+            shuffle!($a, $a, $x, $y, $z, $w)
+        };
+    }
+
+    pub fn mul(l: &[f32; 16], r: &[f32; 16]) -> [f32; 16] {
+        let mut result = [0.0; 16];
+
+        unsafe {
+            let columns = load(l);
+
+            for i in 0..4 {
+                let column = combine(&columns, _mm_set1_ps(r[i * 4]), _mm_set1_ps(r[i * 4 + 1]),
+                                     _mm_set1_ps(r[i * 4 + 2]), _mm_set1_ps(r[i * 4 + 3]));
+
+                _mm_storeu_ps(result[i * 4..].as_mut_ptr(), column);
+            }
+        }
+
+        result
+    }
+
+    pub fn mul_vec(m: &[f32; 16], v: [f32; 4]) -> [f32; 4] {
+        let mut result = [0.0; 4];
+
+        unsafe {
+            let column = combine(&load(m), _mm_set1_ps(v[0]), _mm_set1_ps(v[1]),
+                                 _mm_set1_ps(v[2]), _mm_set1_ps(v[3]));
+
+            _mm_storeu_ps(result.as_mut_ptr(), column);
+        }
+
+        result
+    }
+
+    // Inverts the matrix blockwise, with 2x2 blocks A, B, C and D stored in one register each.
+    // The same code works on columns and rows since inv(transpose(M)) = transpose(inv(M)).
+    pub fn inv(m: &[f32; 16]) -> Option<[f32; 16]> {
+        let mut result = [0.0; 16];
+
+        unsafe {
+            let [c0, c1, c2, c3] = load(m);
+
+            let a = _mm_movelh_ps(c0, c1);
+            let b = _mm_movehl_ps(c1, c0);
+            let c = _mm_movelh_ps(c2, c3);
+            let d = _mm_movehl_ps(c3, c2);
+
+            // |A|, |B|, |C| and |D|.
+            let dets = _mm_sub_ps(
+                _mm_mul_ps(shuffle!(c0, c2, 0, 2, 0, 2), shuffle!(c1, c3, 1, 3, 1, 3)),
+                _mm_mul_ps(shuffle!(c0, c2, 1, 3, 1, 3), shuffle!(c1, c3, 0, 2, 0, 2))
+            );
+
+            let det_a = swizzle!(dets, 0, 0, 0, 0);
+            let det_b = swizzle!(dets, 1, 1, 1, 1);
+            let det_c = swizzle!(dets, 2, 2, 2, 2);
+            let det_d = swizzle!(dets, 3, 3, 3, 3);
+
+            let d_c = adj_mul(d, c);
+            let a_b = adj_mul(a, b);
+
+            let x = _mm_sub_ps(_mm_mul_ps(det_d, a), mul2(b, d_c));
+            let w = _mm_sub_ps(_mm_mul_ps(det_a, d), mul2(c, a_b));
+            let y = _mm_sub_ps(_mm_mul_ps(det_b, c), mul_adj(d, a_b));
+            let z = _mm_sub_ps(_mm_mul_ps(det_c, b), mul_adj(a, d_c));
+
+            // |M| = |A| |D| + |B| |C| - tr((A# B) (D# C))
+            let tr = _mm_mul_ps(a_b, swizzle!(d_c, 0, 2, 1, 3));
+            let tr = _mm_add_ps(tr, _mm_movehl_ps(tr, tr));
+            let tr = _mm_add_ss(tr, swizzle!(tr, 1, 1, 1, 1));
+
+            let det = _mm_sub_ss(_mm_add_ss(_mm_mul_ss(det_a, det_d), _mm_mul_ss(det_b, det_c)),
+                                 tr);
+
+            if _mm_cvtss_f32(det) == 0.0 {
+                return None;
+            }
+
+            let inv_det = _mm_div_ps(_mm_setr_ps(1.0, -1.0, -1.0, 1.0), swizzle!(det, 0, 0, 0, 0));
+
+            let x = _mm_mul_ps(x, inv_det);
+            let y = _mm_mul_ps(y, inv_det);
+            let z = _mm_mul_ps(z, inv_det);
+            let w = _mm_mul_ps(w, inv_det);
+
+            _mm_storeu_ps(result[0..].as_mut_ptr(), shuffle!(x, y, 3, 1, 3, 1));
+            _mm_storeu_ps(result[4..].as_mut_ptr(), shuffle!(x, y, 2, 0, 2, 0));
+            _mm_storeu_ps(result[8..].as_mut_ptr(), shuffle!(z, w, 3, 1, 3, 1));
+            _mm_storeu_ps(result[12..].as_mut_ptr(), shuffle!(z, w, 2, 0, 2, 0));
+        }
+
+        Some(result)
+    }
+
+    // Batches use AVX when the CPU supports it, 8 vectors at a time. With SSE alone, reordering
+    // the vectors costs as much as the portable loop, which LLVM vectorizes anyway.
+    pub fn trans_points(m: &[f32; 16], points: &mut [Vector<f32>]) {
+        let done = if is_x86_feature_detected!("avx") {
+            unsafe { trans_avx(m, points, 1.0, true) }
+        } else {
+            0
+        };
+
+        super::trans_points(m, &mut points[done..]);
+    }
+
+    pub fn trans_dirs(m: &[f32; 16], dirs: &mut [Vector<f32>]) {
+        let done = if is_x86_feature_detected!("avx") {
+            unsafe { trans_avx(m, dirs, 0.0, false) }
+        } else {
+            0
+        };
+
+        super::trans_dirs(m, &mut dirs[done..]);
+    }
+
+    #[inline(always)]
+    unsafe fn load(m: &[f32; 16]) -> [__m128; 4] {
+        [
+            _mm_loadu_ps(m[0..].as_ptr()),
+            _mm_loadu_ps(m[4..].as_ptr()),
+            _mm_loadu_ps(m[8..].as_ptr()),
+            _mm_loadu_ps(m[12..].as_ptr())
+        ]
+    }
+
+    // Transforms as many vectors as possible 8 at a time, with `w` as their 4th coordinate,
+    // dividing by the resulting one if `project`. Returns how many vectors it transformed.
+    // Relies on `Vector` being `repr(C)`.
+    #[target_feature(enable = "avx")]
+    unsafe fn trans_avx(m: &[f32; 16], vectors: &mut [Vector<f32>], w: f32,
+                        project: bool) -> usize {
+        // Loads or stores 4 floats at offset and 4 more 12 floats later, in two register halves.
+        macro_rules! load {
+            ( $ptr:expr, $offset:expr ) => {
+                _mm256_insertf128_ps(_mm256_castps128_ps256(_mm_loadu_ps($ptr.offset($offset))),
+                                     _mm_loadu_ps($ptr.offset($offset + 12)), 1)
+            };
+        }
+
+        macro_rules! store {
+            ( $ptr:expr, $offset:expr, $v:expr ) => {
+                _mm_storeu_ps($ptr.offset($offset), _mm256_castps256_ps128($v));
+                _mm_storeu_ps($ptr.offset($offset + 12), _mm256_extractf128_ps($v, 1));
+            };
+        }
+
+        macro_rules! shuffle {
+            ( $a:expr, $b:expr, $x:expr, $y:expr, $z:expr, $w:expr ) => {
+                _mm256_shuffle_ps($a, $b, $x | $y << 2 | $z << 4 | $w << 6)
+            };
+        }
+
+        let mut elements = [_mm256_setzero_ps(); 16];
+
+        for (element, &value) in elements.iter_mut().zip(m.iter()) {
+            *element = _mm256_set1_ps(value);
+        }
+
+        let w = _mm256_set1_ps(w);
+        let count = vectors.len() / 8 * 8;
+
+        for chunk in vectors[..count].chunks_mut(8) {
+            let ptr = chunk.as_mut_ptr() as *mut f32;
+
+            // Each half holds 4 vectors, [x0 y0 z0 x1] [y1 z1 x2 y2] [z2 x3 y3 z3], reordered as
+            // [x0 x1 x2 x3], etc.
+            let a = load!(ptr, 0);
+            let b = load!(ptr, 4);
+            let c = load!(ptr, 8);
+
+            let x = shuffle!(a, shuffle!(b, c, 2, 2, 1, 1), 0, 3, 0, 2);
+            let y = shuffle!(shuffle!(a, b, 1, 1, 0, 0), shuffle!(b, c, 3, 3, 2, 2), 0, 2, 0, 2);
+            let z = shuffle!(shuffle!(a, b, 2, 2, 1, 1), c, 0, 2, 0, 3);
+
+            // Added up left to right like the portable version.
+            let mut result = [_mm256_setzero_ps(); 4];
+
+            for (i, row) in result.iter_mut().enumerate() {
+                let sum = _mm256_add_ps(_mm256_mul_ps(elements[i], x),
+                                        _mm256_mul_ps(elements[i + 4], y));
+                let sum = _mm256_add_ps(sum, _mm256_mul_ps(elements[i + 8], z));
+
+                *row = _mm256_add_ps(sum, _mm256_mul_ps(elements[i + 12], w));
+            }
+
+            let (mut x, mut y, mut z) = (result[0], result[1], result[2]);
+
+            if project {
+                x = _mm256_div_ps(x, result[3]);
+                y = _mm256_div_ps(y, result[3]);
+                z = _mm256_div_ps(z, result[3]);
+            }
+
+            let a = shuffle!(shuffle!(x, y, 0, 0, 0, 0), shuffle!(z, x, 0, 0, 1, 1), 0, 2, 0, 2);
+            let b = shuffle!(shuffle!(y, z, 1, 1, 1, 1), shuffle!(x, y, 2, 2, 2, 2), 0, 2, 0, 2);
+            let c = shuffle!(shuffle!(z, x, 2, 2, 3, 3), shuffle!(y, z, 3, 3, 3, 3), 0, 2, 0, 2);
+
+            store!(ptr, 0, a);
+            store!(ptr, 4, b);
+            store!(ptr, 8, c);
+        }
+
+        count
+    }
+
+    // Linear combination of the columns, added up left to right like the portable version.
+    #[inline(always)]
+    unsafe fn combine(columns: &[__m128; 4], x: __m128, y: __m128, z: __m128,
+                      w: __m128) -> __m128 {
+        let result = _mm_add_ps(_mm_mul_ps(columns[0], x), _mm_mul_ps(columns[1], y));
+        let result = _mm_add_ps(result, _mm_mul_ps(columns[2], z));
+
+        _mm_add_ps(result, _mm_mul_ps(columns[3], w))
+    }
+
+    // 2x2 block product A B.
+    #[inline(always)]
+    unsafe fn mul2(a: __m128, b: __m128) -> __m128 {
+        _mm_add_ps(_mm_mul_ps(a, swizzle!(b, 0, 3, 0, 3)),
+                   _mm_mul_ps(swizzle!(a, 1, 0, 3, 2), swizzle!(b, 2, 1, 2, 1)))
+    }
+
+    // 2x2 block product A# B, A# being the adjugate of A.
+    #[inline(always)]
+    unsafe fn adj_mul(a: __m128, b: __m128) -> __m128 {
+        _mm_sub_ps(_mm_mul_ps(swizzle!(a, 3, 3, 0, 0), b),
+                   _mm_mul_ps(swizzle!(a, 1, 1, 2, 2), swizzle!(b, 2, 3, 0, 1)))
+    }
+
+    // 2x2 block product A B#.
+    #[inline(always)]
+    unsafe fn mul_adj(a: __m128, b: __m128) -> __m128 {
+        _mm_sub_ps(_mm_mul_ps(a, swizzle!(b, 3, 0, 3, 0)),
+                   _mm_mul_ps(swizzle!(a, 1, 0, 3, 2), swizzle!(b, 2, 1, 2, 1)))
+    }
+}
+
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse")))]
+pub mod packed {
+    pub use super::{inv, mul, mul_vec, trans_dirs, trans_points};
+}
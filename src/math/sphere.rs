@@ -0,0 +1,353 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::aabb::Aabb;
+use super::hit;
+use super::hit::Hit;
+use super::matrix::Matrix;
+use super::obb::Obb;
+use super::plane::Plane;
+use super::triangle::Triangle;
+use super::vector::Vector;
+
+/// A simple sphere `struct`. `Sphere` holds `f32`s and `DSphere` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Hit, Sphere, Vector};
+/// let s1 = Sphere::new(Vector::zero(), 1.0);
+/// let s2 = Sphere::new(Vector::new(1.5, 0.0, 0.0), 1.0);
+///
+/// assert_eq!(s1.hit_sphere(s2), Some(Hit::new(0.5, Vector::right())));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere<T = f32> {
+    /// `Vector<T>` center
+    pub center: Vector<T>,
+    /// `T` radius
+    pub radius: T
+}
+
+impl<T: Scalar> Sphere<T> {
+    /// Creates a sphere using a center and a radius.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Sphere, Vector};
+    /// let s = Sphere::new(Vector::zero(), 1.0);
+    ///
+    /// assert_eq!(s, Sphere { center: Vector::zero(), radius: 1.0 });
+    /// ```
+    pub fn new(center: Vector<T>, radius: T) -> Sphere<T> {
+        Sphere {
+            center: center,
+            radius: radius
+        }
+    }
+
+    /// Checks whether a point is inside a sphere or on its surface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Sphere, Vector};
+    /// let s = Sphere::new(Vector::zero(), 1.0);
+    ///
+    /// assert!(s.contains(Vector::up()));
+    /// assert!(!s.contains(Vector::one()));
+    /// ```
+    pub fn contains(&self, point: Vector<T>) -> bool {
+        point.dist(self.center) <= self.radius
+    }
+
+    /// Computes the point inside a sphere closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Sphere, Vector};
+    /// let s = Sphere::new(Vector::zero(), 1.0);
+    ///
+    /// assert_eq!(s.closest_point(Vector::new(0.0, 3.0, 0.0)), Vector::up());
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        if self.contains(point) {
+            point
+        } else {
+            self.center + (point - self.center).norm() * self.radius
+        }
+    }
+
+    /// Checks whether two spheres overlap. The `Hit` separates them along the line between their
+    /// centers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Sphere, Vector};
+    /// let s1 = Sphere::new(Vector::zero(), 1.0);
+    /// let s2 = Sphere::new(Vector::new(3.0, 0.0, 0.0), 1.0);
+    ///
+    /// assert_eq!(s1.hit_sphere(s2), None);
+    /// ```
+    pub fn hit_sphere(&self, other: Sphere<T>) -> Option<Hit<T>> {
+        let radius = self.radius + other.radius;
+        let direction = self.center - other.center;
+        let dist = direction.len();
+
+        if dist >= radius {
+            None
+        } else if dist > T::from_f64(1e-6) {
+            Some(Hit::new(radius - dist, direction * (T::one() / dist)))
+        } else {
+            Some(Hit::new(radius - dist, Vector::up()))
+        }
+    }
+
+    /// Checks whether a sphere crosses a plane. The `Hit` moves the sphere to the side of the
+    /// plane its center is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Plane, Sphere, Vector};
+    /// let s = Sphere::new(Vector::zero(), 1.0);
+    /// let p = Plane::new(Vector::up(), -0.5);
+    ///
+    /// assert_eq!(s.hit_plane(p), Some(Hit::new(0.5, Vector::up())));
+    /// ```
+    pub fn hit_plane(&self, plane: Plane<T>) -> Option<Hit<T>> {
+        hit::hit_plane(plane, self.center, self.radius)
+    }
+
+    /// Checks whether a sphere overlaps an axis-aligned box. The `Hit` pushes the sphere out of
+    /// the box through the closest face if its center is inside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Aabb, Hit, Sphere, Vector};
+    /// let s = Sphere::new(Vector::new(0.5, 1.5, 0.5), 1.0);
+    /// let b = Aabb::new(Vector::zero(), Vector::one());
+    ///
+    /// assert_eq!(s.hit_aabb(b), Some(Hit::new(0.5, Vector::up())));
+    /// ```
+    pub fn hit_aabb(&self, aabb: Aabb<T>) -> Option<Hit<T>> {
+        let closest = aabb.closest_point(self.center);
+
+        if closest != self.center {
+            return self.hit_point(closest);
+        }
+
+        let below = self.center - aabb.min;
+        let above = aabb.max - self.center;
+
+        let faces = [
+            (below.x, Vector::new(-T::one(), T::zero(), T::zero())),
+            (above.x, Vector::new(T::one(), T::zero(), T::zero())),
+            (below.y, Vector::new(T::zero(), -T::one(), T::zero())),
+            (above.y, Vector::new(T::zero(), T::one(), T::zero())),
+            (below.z, Vector::new(T::zero(), T::zero(), -T::one())),
+            (above.z, Vector::new(T::zero(), T::zero(), T::one()))
+        ];
+
+        let (dist, normal) = faces.iter().fold(faces[0], |closest, &face| {
+            if face.0 < closest.0 { face } else { closest }
+        });
+
+        Some(Hit::new(self.radius + dist, normal))
+    }
+
+    /// Checks whether a sphere overlaps an oriented box. The `Hit` pushes the sphere out of the
+    /// box through the closest face if its center is inside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Obb, Quaternion, Sphere, Vector};
+    /// let s = Sphere::new(Vector::new(0.0, 1.5, 0.0), 1.0);
+    /// let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(s.hit_obb(b).unwrap().distance, 0.5);
+    /// ```
+    pub fn hit_obb(&self, obb: Obb<T>) -> Option<Hit<T>> {
+        let local = Sphere::new(obb.to_local(self.center), self.radius);
+
+        local.hit_aabb(obb.local_aabb()).map(|hit| {
+            let normal = obb.to_world(hit.normal) - obb.center;
+
+            Hit::new(hit.distance, normal)
+        })
+    }
+
+    /// Checks whether a sphere overlaps a triangle. The `Hit` pushes the sphere away from the
+    /// closest point on the triangle, or along the normal if its center is on the triangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Hit, Sphere, Triangle, Vector};
+    /// let s = Sphere::new(Vector::new(0.5, 0.5, 0.5), 1.0);
+    /// let t = Triangle::new(Vector::zero(), Vector::new(0.0, 0.0, 2.0),
+    ///                       Vector::new(2.0, 0.0, 0.0));
+    ///
+    /// assert_eq!(s.hit_triangle(t), Some(Hit::new(0.5, Vector::up())));
+    /// ```
+    pub fn hit_triangle(&self, triangle: Triangle<T>) -> Option<Hit<T>> {
+        let closest = triangle.closest_point(self.center);
+
+        if closest != self.center {
+            self.hit_point(closest)
+        } else {
+            Some(Hit::new(self.radius, triangle.normal()))
+        }
+    }
+
+    /// Converts a sphere to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DSphere, DVector, Sphere, Vector};
+    /// let s = DSphere::new(DVector::zero(), 0.5);
+    ///
+    /// assert_eq!(s.cast::<f32>(), Sphere::new(Vector::zero(), 0.5));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Sphere<U> {
+        Sphere {
+            center: self.center.cast(),
+            radius: U::from_f64(self.radius.to_f64())
+        }
+    }
+
+    fn hit_point(&self, point: Vector<T>) -> Option<Hit<T>> {
+        let direction = self.center - point;
+        let dist = direction.len();
+
+        if dist >= self.radius {
+            None
+        } else {
+            Some(Hit::new(self.radius - dist, direction * (T::one() / dist)))
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+/// Transforms a sphere. The radius grows by the largest scale.
+impl<T: Scalar> Mul<Sphere<T>> for Matrix<T> {
+    type Output = Sphere<T>;
+
+    fn mul(self, sphere: Sphere<T>) -> Sphere<T> {
+        let m = self.array;
+
+        let scale = Vector::new(m[0], m[1], m[2]).len()
+                        .max(Vector::new(m[4], m[5], m[6]).len())
+                        .max(Vector::new(m[8], m[9], m[10]).len());
+
+        Sphere::new(self * sphere.center, sphere.radius * scale)
+    }
+}
+
+impl From<Sphere<f32>> for Sphere<f64> {
+    fn from(sphere: Sphere<f32>) -> Sphere<f64> {
+        sphere.cast()
+    }
+}
+
+mrusty_class!(Sphere, {
+    def!("initialize", |center: Vector, radius: f64| {
+        Sphere::new((*center).clone(), radius as f32)
+    });
+
+    def!("center", |mruby, slf: Sphere| {
+        mruby.obj(slf.center)
+    });
+
+    def!("radius", |mruby, slf: Sphere| {
+        mruby.float(slf.radius as f64)
+    });
+
+    def!("contains?", |mruby, slf: Sphere, point: Vector| {
+        mruby.bool(slf.contains((*point).clone()))
+    });
+
+    def!("closest_point", |mruby, slf: Sphere, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("hit", |mruby, slf: Sphere, shape: Value| {
+        if let Ok(other) = shape.to_obj::<Sphere>() {
+            hit::to_value(&mruby, slf.hit_sphere((*other).clone()))
+        } else if let Ok(plane) = shape.to_obj::<Plane>() {
+            hit::to_value(&mruby, slf.hit_plane((*plane).clone()))
+        } else if let Ok(aabb) = shape.to_obj::<Aabb>() {
+            hit::to_value(&mruby, slf.hit_aabb((*aabb).clone()))
+        } else if let Ok(obb) = shape.to_obj::<Obb>() {
+            hit::to_value(&mruby, slf.hit_obb((*obb).clone()))
+        } else if let Ok(triangle) = shape.to_obj::<Triangle>() {
+            hit::to_value(&mruby, slf.hit_triangle((*triangle).clone()))
+        } else {
+            mruby.raise("ArgumentError", "expecting Sphere, Plane, Aabb, Obb or Triangle")
+        }
+    });
+
+    def!("transform", |mruby, slf: Sphere, matrix: Matrix| {
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Sphere;
+    use super::super::{Aabb, Matrix, Obb, Plane, Quaternion, Triangle, Vector};
+
+    describe!(Sphere, (Aabb, Matrix, Obb, Plane, Quaternion, Triangle, Vector), "
+      context 'when unit' do
+        subject { Sphere.new Vector.zero, 1.0 }
+
+        it 'returns center on #center' do
+          expect(subject.center).to eql Vector.zero
+        end
+
+        it 'returns radius on #radius' do
+          expect(subject.radius).to eql 1.0
+        end
+
+        it 'checks points on #contains?' do
+          expect(subject.contains? Vector.up).to be true
+          expect(subject.contains? Vector.one).to be false
+        end
+
+        it 'computes closest point on #closest_point' do
+          expect(subject.closest_point Vector.new(0.0, 3.0, 0.0)).to eql Vector.up
+        end
+
+        it 'hits overlapping shapes on #hit' do
+          depth, normal = subject.hit(Sphere.new(Vector.new(1.5, 0.0, 0.0), 1.0))
+
+          expect(depth).to eql 0.5
+          expect(normal).to eql Vector.right
+          expect(subject.hit Plane.new(Vector.up, 2.0)).to be_nil
+          expect(subject.hit Aabb.new(Vector.new(0.5, 0.0, 0.0), Vector.one)).not_to be_nil
+          expect(subject.hit Obb.new(Vector.up, Quaternion.identity, Vector.one)).not_to be_nil
+          expect(subject.hit Triangle.new(Vector.zero, Vector.up, Vector.one)).not_to be_nil
+        end
+
+        it 'transforms on #transform' do
+          expect(subject.transform(Matrix.identity.scale(Vector.uniform(2.0))).radius).to eql 2.0
+        end
+      end
+    ");
+}
@@ -0,0 +1,331 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::matrix::Matrix;
+use super::quaternion::Quaternion;
+use super::vector::Vector;
+
+/// A `struct` that scales, then rotates, then translates, like the matrix created by
+/// `Matrix::compose`, while being cheaper to store, combine and interpolate. `Transform` holds
+/// `f32`s and `DTransform` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Matrix, Quaternion, Transform, Vector};
+/// let rotation = Quaternion::new_rot(Vector::up(), 1.0);
+/// let t = Transform::new(Vector::one(), rotation, Vector::new_unf(2.0));
+///
+/// let m = Matrix::compose(Vector::one(), rotation, Vector::new_unf(2.0));
+/// let point = Vector::new(1.0, 2.0, 3.0);
+///
+/// const EPSILON: f32 = 0.00001;
+///
+/// assert!(t.trans_point(point).dist(m * point) < EPSILON);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform<T = f32> {
+    /// `Vector<T>` translation
+    pub translation: Vector<T>,
+    /// `Quaternion<T>` unit rotation
+    pub rotation: Quaternion<T>,
+    /// `Vector<T>` scale on every axis
+    pub scale: Vector<T>
+}
+
+impl<T: Scalar> Transform<T> {
+    /// Creates a transform using a translation, a rotation and a scale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Transform, Vector};
+    /// let t = Transform::new(Vector::zero(), Quaternion::ident(), Vector::one());
+    ///
+    /// assert_eq!(t, Transform::ident());
+    /// ```
+    pub fn new(translation: Vector<T>, rotation: Quaternion<T>, scale: Vector<T>) -> Transform<T> {
+        Transform {
+            translation: translation,
+            rotation: rotation,
+            scale: scale
+        }
+    }
+
+    /// Creates an identity transform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Transform, Vector};
+    /// let v = Vector::new(1.0, 2.0, 3.0);
+    ///
+    /// assert_eq!(Transform::ident().trans_point(v), v);
+    /// ```
+    pub fn ident() -> Transform<T> {
+        Transform::new(Vector::zero(), Quaternion::ident(), Vector::one())
+    }
+
+    /// Creates a transform by decomposing a matrix with `Matrix::decompose`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Transform, Vector};
+    /// let m = Matrix::ident().scale(Vector::new_unf(2.0)).trans(Vector::one());
+    /// let t = Transform::from_matrix(m);
+    ///
+    /// assert_eq!(t.translation, Vector::one());
+    /// assert_eq!(t.scale, Vector::new_unf(2.0));
+    /// ```
+    pub fn from_matrix(matrix: Matrix<T>) -> Transform<T> {
+        let (translation, rotation, scale) = matrix.decompose();
+
+        Transform::new(translation, rotation, scale)
+    }
+
+    /// Computes the matrix equivalent to a transform.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Matrix, Quaternion, Transform, Vector};
+    /// let t = Transform::new(Vector::one(), Quaternion::ident(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(t.to_matrix(), Matrix::ident().scale(Vector::new_unf(2.0))
+    ///                                          .trans(Vector::one()));
+    /// ```
+    pub fn to_matrix(self) -> Matrix<T> {
+        Matrix::compose(self.translation, self.rotation, self.scale)
+    }
+
+    /// Transforms a point by scaling, rotating and translating it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Transform, Vector};
+    /// let t = Transform::new(Vector::one(), Quaternion::ident(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(t.trans_point(Vector::one()), Vector::new_unf(3.0));
+    /// ```
+    pub fn trans_point(&self, point: Vector<T>) -> Vector<T> {
+        self.trans_dir(point) + self.translation
+    }
+
+    /// Transforms a direction by scaling and rotating it. The result is not normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Transform, Vector};
+    /// let t = Transform::new(Vector::one(), Quaternion::ident(), Vector::new_unf(2.0));
+    ///
+    /// assert_eq!(t.trans_dir(Vector::one()), Vector::new_unf(2.0));
+    /// ```
+    pub fn trans_dir(&self, direction: Vector<T>) -> Vector<T> {
+        // `Vector::rot` turns the other way around than `Matrix::rot`.
+        (direction * self.scale).rot(self.rotation.conj())
+    }
+
+    /// Computes the inverse of a transform. The result is exact as long as the scale is uniform;
+    /// otherwise, the undone scale is applied along the transformed axes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Quaternion, Transform, Vector};
+    /// let rotation = Quaternion::new_rot(Vector::up(), 1.0);
+    /// let t = Transform::new(Vector::one(), rotation, Vector::new_unf(2.0));
+    ///
+    /// let point = Vector::new(1.0, 2.0, 3.0);
+    ///
+    /// const EPSILON: f32 = 0.00001;
+    ///
+    /// assert!(t.inv().trans_point(t.trans_point(point)).dist(point) < EPSILON);
+    /// ```
+    pub fn inv(&self) -> Transform<T> {
+        let one = T::one();
+
+        let scale = Vector::new(one / self.scale.x, one / self.scale.y, one / self.scale.z);
+        let mut inv = Transform::new(Vector::zero(), self.rotation.conj(), scale);
+
+        inv.translation = -inv.trans_dir(self.translation);
+
+        inv
+    }
+
+    /// Converts a transform to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DTransform, Transform};
+    /// let t = DTransform::ident();
+    ///
+    /// assert_eq!(t.cast::<f32>(), Transform::ident());
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Transform<U> {
+        Transform {
+            translation: self.translation.cast(),
+            rotation: self.rotation.cast(),
+            scale: self.scale.cast()
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+use math::Interpolate;
+
+/// Combines two transforms so that `self` is applied after `other`, like with matrices. The result
+/// is exact as long as the scale of `self` is uniform.
+impl<T: Scalar> Mul for Transform<T> {
+    type Output = Transform<T>;
+
+    fn mul(self, other: Transform<T>) -> Transform<T> {
+        Transform {
+            translation: self.trans_point(other.translation),
+            rotation: other.rotation * self.rotation,
+            scale: self.scale * other.scale
+        }
+    }
+}
+
+impl From<Transform<f32>> for Transform<f64> {
+    fn from(transform: Transform<f32>) -> Transform<f64> {
+        transform.cast()
+    }
+}
+
+impl<T: Scalar> Interpolate<T> for Transform<T> {
+    fn interpolate(&self, other: Transform<T>, ratio: T) -> Transform<T> {
+        Transform {
+            translation: self.translation.interpolate(other.translation, ratio),
+            rotation: self.rotation.slerp(other.rotation, ratio),
+            scale: self.scale.interpolate(other.scale, ratio)
+        }
+    }
+}
+
+mrusty_class!(Transform, {
+    def!("initialize", |translation: Vector, rotation: Quaternion, scale: Vector| {
+        Transform::new((*translation).clone(), (*rotation).clone(), (*scale).clone())
+    });
+
+    def_self!("identity", |mruby, _slf: Value| {
+        mruby.obj(Transform::<f32>::ident())
+    });
+
+    def_self!("from_matrix", |mruby, _slf: Value, matrix: Matrix| {
+        mruby.obj(Transform::from_matrix((*matrix).clone()))
+    });
+
+    def!("translation", |mruby, slf: Transform| {
+        mruby.obj(slf.translation)
+    });
+
+    def!("rotation", |mruby, slf: Transform| {
+        mruby.obj(slf.rotation)
+    });
+
+    def!("scale", |mruby, slf: Transform| {
+        mruby.obj(slf.scale)
+    });
+
+    def!("==", |mruby, slf: Transform, other: Transform| {
+        mruby.bool(*slf == *other)
+    });
+
+    def!("to_s", |mruby, slf: Transform| {
+        let t = slf.translation;
+        let r = slf.rotation;
+        let s = slf.scale;
+
+        let string = format!("<Transform: @translation=({}, {}, {}) @rotation=({}, {}, {}, {}) \
+                              @scale=({}, {}, {})>",
+                             t.x, t.y, t.z, r.x, r.y, r.z, r.w, s.x, s.y, s.z);
+
+        mruby.string(&string)
+    });
+
+    def!("*", |mruby, slf: Transform, other: Transform| {
+        mruby.obj((*slf).clone() * (*other).clone())
+    });
+
+    def!("inv", |mruby, slf: Transform| {
+        mruby.obj(slf.inv())
+    });
+
+    def!("transform_point", |mruby, slf: Transform, point: Vector| {
+        mruby.obj(slf.trans_point((*point).clone()))
+    });
+
+    def!("transform_direction", |mruby, slf: Transform, direction: Vector| {
+        mruby.obj(slf.trans_dir((*direction).clone()))
+    });
+
+    def!("to_matrix", |mruby, slf: Transform| {
+        mruby.obj(slf.to_matrix())
+    });
+
+    def!("interpolate", |mruby, slf: Transform, other: Transform, ratio: f64| {
+        mruby.obj(slf.interpolate((*other).clone(), ratio as f32))
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Transform;
+    use super::super::{Matrix, Quaternion, Vector};
+
+    describe!(Transform, (Matrix, Quaternion, Vector), "
+      context 'when scaled and translated' do
+        subject { Transform.new Vector.one, Quaternion.identity, Vector.uniform(2.0) }
+
+        it 'returns translation on #translation' do
+          expect(subject.translation).to eql Vector.one
+        end
+
+        it 'returns rotation on #rotation' do
+          expect(subject.rotation).to eql Quaternion.identity
+        end
+
+        it 'returns scale on #scale' do
+          expect(subject.scale).to eql Vector.uniform(2.0)
+        end
+
+        it 'transforms points on #transform_point' do
+          expect(subject.transform_point Vector.one).to eql Vector.uniform(3.0)
+        end
+
+        it 'transforms directions on #transform_direction' do
+          expect(subject.transform_direction Vector.one).to eql Vector.uniform(2.0)
+        end
+
+        it 'returns inverse on #inv' do
+          expect(subject * subject.inv).to eql Transform.identity
+        end
+
+        it 'converts to and from Matrix on #to_matrix and .from_matrix' do
+          expect(Transform.from_matrix subject.to_matrix).to eql subject
+        end
+
+        it 'interpolates on #interpolate' do
+          interpolated = Transform.identity.interpolate(subject, 0.5)
+
+          expect(interpolated.translation).to eql Vector.uniform(0.5)
+          expect(interpolated.scale).to eql Vector.uniform(1.5)
+        end
+      end
+    ");
+}
@@ -0,0 +1,227 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::matrix::Matrix;
+use super::vector::Vector;
+
+/// A simple triangle `struct`. Seen from its front, the vertices are counter-clockwise.
+/// `Triangle` holds `f32`s and `DTriangle` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Triangle, Vector};
+/// let t = Triangle::new(Vector::zero(), Vector::new(0.0, 0.0, 1.0), Vector::new(1.0, 0.0, 0.0));
+///
+/// assert_eq!(t.normal(), Vector::up());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Triangle<T = f32> {
+    /// `Vector<T>` first vertex
+    pub a: Vector<T>,
+    /// `Vector<T>` second vertex
+    pub b: Vector<T>,
+    /// `Vector<T>` third vertex
+    pub c: Vector<T>
+}
+
+impl<T: Scalar> Triangle<T> {
+    /// Creates a triangle using 3 vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Triangle, Vector};
+    /// let t = Triangle::new(Vector::zero(), Vector::up(), Vector::one());
+    ///
+    /// assert_eq!(t, Triangle { a: Vector::zero(), b: Vector::up(), c: Vector::one() });
+    /// ```
+    pub fn new(a: Vector<T>, b: Vector<T>, c: Vector<T>) -> Triangle<T> {
+        Triangle { a: a, b: b, c: c }
+    }
+
+    /// Computes the normalized normal of a triangle, pointing towards its front.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Triangle, Vector};
+    /// let t = Triangle::new(Vector::zero(), Vector::new(1.0, 0.0, 0.0),
+    ///                       Vector::new(0.0, 0.0, 1.0));
+    ///
+    /// assert_eq!(t.normal(), Vector::down());
+    /// ```
+    pub fn normal(&self) -> Vector<T> {
+        (self.b - self.a).cross(self.c - self.a).norm()
+    }
+
+    /// Computes the point on a triangle closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Triangle, Vector};
+    /// let t = Triangle::new(Vector::zero(), Vector::new(0.0, 0.0, 2.0),
+    ///                       Vector::new(2.0, 0.0, 0.0));
+    ///
+    /// assert_eq!(t.closest_point(Vector::new(0.5, 3.0, 0.5)), Vector::new(0.5, 0.0, 0.5));
+    /// assert_eq!(t.closest_point(Vector::new(-1.0, 0.0, -1.0)), Vector::zero());
+    /// assert_eq!(t.closest_point(Vector::new(2.0, 0.0, 2.0)), Vector::new(1.0, 0.0, 1.0));
+    /// ```
+    pub fn closest_point(&self, point: Vector<T>) -> Vector<T> {
+        let zero = T::zero();
+
+        let ab = self.b - self.a;
+        let ac = self.c - self.a;
+
+        // Checks the Voronoi regions of the vertices and edges before the face.
+        let ap = point - self.a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+
+        if d1 <= zero && d2 <= zero {
+            return self.a;
+        }
+
+        let bp = point - self.b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+
+        if d3 >= zero && d4 <= d3 {
+            return self.b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+
+        if vc <= zero && d1 >= zero && d3 <= zero {
+            return self.a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = point - self.c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+
+        if d6 >= zero && d5 <= d6 {
+            return self.c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+
+        if vb <= zero && d2 >= zero && d6 <= zero {
+            return self.a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+
+        if va <= zero && d4 - d3 >= zero && d5 - d6 >= zero {
+            return self.b + (self.c - self.b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = T::one() / (va + vb + vc);
+
+        self.a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    /// Converts a triangle to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DTriangle, DVector, Triangle, Vector};
+    /// let t = DTriangle::new(DVector::zero(), DVector::up(), DVector::one());
+    ///
+    /// assert_eq!(t.cast::<f32>(), Triangle::new(Vector::zero(), Vector::up(), Vector::one()));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Triangle<U> {
+        Triangle {
+            a: self.a.cast(),
+            b: self.b.cast(),
+            c: self.c.cast()
+        }
+    }
+}
+
+use std::ops::Mul;
+
+use mrusty::*;
+
+impl<T: Scalar> Mul<Triangle<T>> for Matrix<T> {
+    type Output = Triangle<T>;
+
+    fn mul(self, triangle: Triangle<T>) -> Triangle<T> {
+        Triangle::new(self * triangle.a, self * triangle.b, self * triangle.c)
+    }
+}
+
+impl From<Triangle<f32>> for Triangle<f64> {
+    fn from(triangle: Triangle<f32>) -> Triangle<f64> {
+        triangle.cast()
+    }
+}
+
+mrusty_class!(Triangle, {
+    def!("initialize", |a: Vector, b: Vector, c: Vector| {
+        Triangle::new((*a).clone(), (*b).clone(), (*c).clone())
+    });
+
+    def!("a", |mruby, slf: Triangle| {
+        mruby.obj(slf.a)
+    });
+
+    def!("b", |mruby, slf: Triangle| {
+        mruby.obj(slf.b)
+    });
+
+    def!("c", |mruby, slf: Triangle| {
+        mruby.obj(slf.c)
+    });
+
+    def!("normal", |mruby, slf: Triangle| {
+        mruby.obj(slf.normal())
+    });
+
+    def!("closest_point", |mruby, slf: Triangle, point: Vector| {
+        mruby.obj(slf.closest_point((*point).clone()))
+    });
+
+    def!("transform", |mruby, slf: Triangle, matrix: Matrix| {
+        mruby.obj((*matrix).clone() * (*slf).clone())
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Triangle;
+    use super::super::{Matrix, Vector};
+
+    describe!(Triangle, (Matrix, Vector), "
+      context 'when horizontal' do
+        subject { Triangle.new Vector.zero, Vector.new(0.0, 0.0, 2.0), Vector.new(2.0, 0.0, 0.0) }
+
+        it 'returns vertices on #a, #b and #c' do
+          expect(subject.a).to eql Vector.zero
+          expect(subject.b).to eql Vector.new(0.0, 0.0, 2.0)
+          expect(subject.c).to eql Vector.new(2.0, 0.0, 0.0)
+        end
+
+        it 'computes normal on #normal' do
+          expect(subject.normal).to eql Vector.up
+        end
+
+        it 'computes closest point on #closest_point' do
+          expect(subject.closest_point Vector.new(0.5, 3.0, 0.5)).to eql Vector.new(0.5, 0.0, 0.5)
+        end
+
+        it 'transforms on #transform' do
+          expect(subject.transform(Matrix.identity.trans(Vector.up)).a).to eql Vector.up
+        end
+      end
+    ");
+}
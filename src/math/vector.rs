@@ -5,9 +5,13 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use math::Quaternion;
+use math::Scalar;
+use super::quaternion::Quaternion;
+use super::vector2::Vector2;
+use super::vector4::Vector4;
 
-/// A simple vector `struct` tailored specifically for graphics.
+/// A simple vector `struct` tailored specifically for graphics. `Vector` holds `f32`s and
+/// `DVector` holds `f64`s.
 ///
 /// # Examples
 ///
@@ -25,16 +29,17 @@ use math::Quaternion;
 /// assert_eq!(v1.dot(v2), 0.0);
 /// assert_eq!((v3 + Vector::one() * 2.0).dot(v2), 6.0);
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Vector {
-    /// `f32` *x* coordinate value
-    pub x: f32,
-    /// `f32` *y* coordinate value
-    pub y: f32,
-    /// `f32` *z* coordinate value
-    pub z: f32
+#[repr(C)]
+pub struct Vector<T = f32> {
+    /// `T` *x* coordinate value
+    pub x: T,
+    /// `T` *y* coordinate value
+    pub y: T,
+    /// `T` *z* coordinate value
+    pub z: T
 }
 
-impl Vector {
+impl<T: Scalar> Vector<T> {
     /// Creates a vector using 3 values.
     ///
     /// # Examples
@@ -45,7 +50,7 @@ impl Vector {
     ///
     /// assert_eq!(v, Vector { x: 0.0, y: 1.0, z: 2.0 });
     /// ```
-    pub fn new(x: f32, y: f32, z: f32) -> Vector {
+    pub fn new(x: T, y: T, z: T) -> Vector<T> {
         Vector { x: x, y: y, z: z }
     }
 
@@ -59,7 +64,7 @@ impl Vector {
     ///
     /// assert_eq!(v, Vector { x: 0.0, y: 1.0, z: 2.0 });
     /// ```
-    pub fn new_arr(array: [f32; 3]) -> Vector {
+    pub fn new_arr(array: [T; 3]) -> Vector<T> {
         Vector { x: array[0], y: array[1], z: array[2] }
     }
 
@@ -73,7 +78,7 @@ impl Vector {
     ///
     /// assert_eq!(v, Vector { x: 1.0, y: 1.0, z: 1.0 });
     /// ```
-    pub fn new_unf(v: f32) -> Vector {
+    pub fn new_unf(v: T) -> Vector<T> {
         Vector { x: v, y: v, z: v }
     }
 
@@ -85,8 +90,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::zero(), Vector { x: 0.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn zero() -> Vector {
-        Vector { x: 0.0, y: 0.0, z: 0.0 }
+    pub fn zero() -> Vector<T> {
+        Vector { x: T::zero(), y: T::zero(), z: T::zero() }
     }
 
     /// Creates a one (1.0, 1.0, 1.0) Vector.
@@ -97,8 +102,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::one(), Vector { x: 1.0, y: 1.0, z: 1.0 });
     /// ```
-    pub fn one() -> Vector {
-        Vector { x: 1.0, y: 1.0, z: 1.0 }
+    pub fn one() -> Vector<T> {
+        Vector { x: T::one(), y: T::one(), z: T::one() }
     }
 
     /// Creates a back (0.0, 0.0, -1.0) Vector.
@@ -109,8 +114,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::back(), Vector { x: 0.0, y: 0.0, z: -1.0 });
     /// ```
-    pub fn back() -> Vector {
-        Vector { x: 0.0, y: 0.0, z: -1.0 }
+    pub fn back() -> Vector<T> {
+        Vector { x: T::zero(), y: T::zero(), z: -T::one() }
     }
 
     /// Creates a down (0.0, -1.0, 0.0) Vector.
@@ -121,8 +126,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::down(), Vector { x: 0.0, y: -1.0, z: 0.0 });
     /// ```
-    pub fn down() -> Vector {
-        Vector { x: 0.0, y: -1.0, z: 0.0 }
+    pub fn down() -> Vector<T> {
+        Vector { x: T::zero(), y: -T::one(), z: T::zero() }
     }
 
     /// Creates a forward (0.0, 0.0, 1.0) Vector.
@@ -133,8 +138,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::forward(), Vector { x: 0.0, y: 0.0, z: 1.0 });
     /// ```
-    pub fn forward() -> Vector {
-        Vector { x: 0.0, y: 0.0, z: 1.0 }
+    pub fn forward() -> Vector<T> {
+        Vector { x: T::zero(), y: T::zero(), z: T::one() }
     }
 
     /// Creates a left (-1.0, 0.0, 0.0) Vector.
@@ -145,8 +150,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::left(), Vector { x: 1.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn left() -> Vector {
-        Vector { x: 1.0, y: 0.0, z: 0.0 }
+    pub fn left() -> Vector<T> {
+        Vector { x: T::one(), y: T::zero(), z: T::zero() }
     }
 
     /// Creates a right (1.0, 0.0, 0.0) Vector.
@@ -157,8 +162,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::right(), Vector { x: -1.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn right() -> Vector {
-        Vector { x: -1.0, y: 0.0, z: 0.0 }
+    pub fn right() -> Vector<T> {
+        Vector { x: -T::one(), y: T::zero(), z: T::zero() }
     }
 
     /// Creates an up (0.0, 1.0, 0.0) Vector.
@@ -169,8 +174,8 @@ impl Vector {
     /// # use anima_engine::math::Vector;
     /// assert_eq!(Vector::up(), Vector { x: 0.0, y: 1.0, z: 0.0 });
     /// ```
-    pub fn up() -> Vector {
-        Vector { x: 0.0, y: 1.0, z: 0.0 }
+    pub fn up() -> Vector<T> {
+        Vector { x: T::zero(), y: T::one(), z: T::zero() }
     }
 
     /// Computes the length of a vector.
@@ -183,7 +188,7 @@ impl Vector {
     ///
     /// assert_eq!(v.len(), 3.0);
     /// ```
-    pub fn len(&self) -> f32 {
+    pub fn len(&self) -> T {
         (self.x.powi(2) + self.y.powi(2) + self.z.powi(2)).sqrt()
     }
 
@@ -198,7 +203,7 @@ impl Vector {
     ///
     /// assert_eq!(n.len(), 1.0); // Keep precision in mind when comparing floats.
     /// ```
-    pub fn norm(&self) -> Vector {
+    pub fn norm(&self) -> Vector<T> {
         let length = self.len();
 
         Vector {
@@ -219,7 +224,7 @@ impl Vector {
     ///
     /// assert_eq!(v1.dot(v2), 11.0);
     /// ```
-    pub fn dot(&self, other: Vector) -> f32 {
+    pub fn dot(&self, other: Vector<T>) -> T {
         self.x * other.x +
         self.y * other.y +
         self.z * other.z
@@ -236,7 +241,7 @@ impl Vector {
     ///
     /// assert_eq!(v1.cross(v2), Vector { x: -4.0, y: 5.0, z: -3.0 });
     /// ```
-    pub fn cross(&self, other: Vector) -> Vector {
+    pub fn cross(&self, other: Vector<T>) -> Vector<T> {
         Vector {
             x: self.y * other.z - self.z * other.y,
             y: self.z * other.x - self.x * other.z,
@@ -256,9 +261,9 @@ impl Vector {
     ///
     /// assert_eq!(v.rot(q), Vector { x: -1.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn rot(&self, quaternion: Quaternion) -> Vector {
+    pub fn rot(&self, quaternion: Quaternion<T>) -> Vector<T> {
         let result = quaternion *
-                     Quaternion::new(self.x, self.y, self.z, 0.0) *
+                     Quaternion::new(self.x, self.y, self.z, T::zero()) *
                      quaternion.conj();
 
         Vector { x: result.x, y: result.y, z: result.z }
@@ -277,7 +282,7 @@ impl Vector {
     ///
     /// assert_eq!(v.rot_around(q, p), Vector { x: 3.0, y: 0.0, z: 0.0 });
     /// ```
-    pub fn rot_around(self, quaternion: Quaternion, point: Vector) -> Vector {
+    pub fn rot_around(self, quaternion: Quaternion<T>, point: Vector<T>) -> Vector<T> {
         (self - point).rot(quaternion) + point
     }
 
@@ -293,7 +298,7 @@ impl Vector {
     ///
     /// assert_eq!(v1.angle(v2), consts::PI / 2.0);
     /// ```
-    pub fn angle(&self, other: Vector) -> f32 {
+    pub fn angle(&self, other: Vector<T>) -> T {
         self.norm().dot(other.norm()).acos()
     }
 
@@ -308,9 +313,55 @@ impl Vector {
     ///
     /// assert_eq!(v1.dist(v2), 1.0);
     /// ```
-    pub fn dist(self, other: Vector) -> f32 {
+    pub fn dist(self, other: Vector<T>) -> T {
         (self - other).len()
     }
+
+    /// Converts a vector to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DVector, Vector};
+    /// let v = DVector::new(0.1, 0.2, 0.3);
+    ///
+    /// assert_eq!(v.cast::<f32>(), Vector::new(0.1, 0.2, 0.3));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Vector<U> {
+        Vector {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64())
+        }
+    }
+
+    /// Drops *z*.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector, Vector2};
+    /// let v = Vector::new(1.0, 2.0, 3.0);
+    ///
+    /// assert_eq!(v.xy(), Vector2::new(1.0, 2.0));
+    /// ```
+    pub fn xy(&self) -> Vector2<T> {
+        Vector2::new(self.x, self.y)
+    }
+
+    /// Adds a *w* coordinate. Use 1.0 for points and 0.0 for directions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector, Vector4};
+    /// let v = Vector::new(1.0, 2.0, 3.0);
+    ///
+    /// assert_eq!(v.extend(1.0), Vector4::new(1.0, 2.0, 3.0, 1.0));
+    /// ```
+    pub fn extend(&self, w: T) -> Vector4<T> {
+        Vector4::new(self.x, self.y, self.z, w)
+    }
 }
 
 use std::ops::Add;
@@ -322,10 +373,10 @@ use math::Interpolate;
 
 use mrusty::*;
 
-impl Add for Vector {
-    type Output = Vector;
+impl<T: Scalar> Add for Vector<T> {
+    type Output = Vector<T>;
 
-    fn add(self, other: Vector) -> Vector {
+    fn add(self, other: Vector<T>) -> Vector<T> {
         Vector {
             x: self.x + other.x,
             y: self.y + other.y,
@@ -334,10 +385,10 @@ impl Add for Vector {
     }
 }
 
-impl Sub for Vector {
-    type Output = Vector;
+impl<T: Scalar> Sub for Vector<T> {
+    type Output = Vector<T>;
 
-    fn sub(self, other: Vector) -> Vector {
+    fn sub(self, other: Vector<T>) -> Vector<T> {
         Vector {
             x: self.x - other.x,
             y: self.y - other.y,
@@ -346,10 +397,10 @@ impl Sub for Vector {
     }
 }
 
-impl Mul<Vector> for Vector {
-    type Output = Vector;
+impl<T: Scalar> Mul<Vector<T>> for Vector<T> {
+    type Output = Vector<T>;
 
-    fn mul(self, other: Vector) -> Vector {
+    fn mul(self, other: Vector<T>) -> Vector<T> {
         Vector {
             x: self.x * other.x,
             y: self.y * other.y,
@@ -358,10 +409,10 @@ impl Mul<Vector> for Vector {
     }
 }
 
-impl Mul<f32> for Vector {
-    type Output = Vector;
+impl<T: Scalar> Mul<T> for Vector<T> {
+    type Output = Vector<T>;
 
-    fn mul(self, scalar: f32) -> Vector {
+    fn mul(self, scalar: T) -> Vector<T> {
         Vector {
             x: self.x * scalar,
             y: self.y * scalar,
@@ -370,22 +421,26 @@ impl Mul<f32> for Vector {
     }
 }
 
-impl Mul<Vector> for f32 {
-    type Output = Vector;
+impl Mul<Vector<f32>> for f32 {
+    type Output = Vector<f32>;
 
-    fn mul(self, vector: Vector) -> Vector {
-        Vector {
-            x: vector.x * self,
-            y: vector.y * self,
-            z: vector.z * self
-        }
+    fn mul(self, vector: Vector<f32>) -> Vector<f32> {
+        vector * self
     }
 }
 
-impl Neg for Vector {
-    type Output = Vector;
+impl Mul<Vector<f64>> for f64 {
+    type Output = Vector<f64>;
 
-    fn neg(self) -> Vector {
+    fn mul(self, vector: Vector<f64>) -> Vector<f64> {
+        vector * self
+    }
+}
+
+impl<T: Scalar> Neg for Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(self) -> Vector<T> {
         Vector {
             x: -self.x,
             y: -self.y,
@@ -394,18 +449,24 @@ impl Neg for Vector {
     }
 }
 
-impl PartialOrd for Vector {
+impl From<Vector<f32>> for Vector<f64> {
+    fn from(vector: Vector<f32>) -> Vector<f64> {
+        vector.cast()
+    }
+}
+
+impl<T: Scalar> PartialOrd for Vector<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.len().partial_cmp(&other.len())
     }
 }
 
-impl Interpolate for Vector {
-    fn interpolate(&self, other: Vector, ratio: f32) -> Vector {
+impl<T: Scalar> Interpolate<T> for Vector<T> {
+    fn interpolate(&self, other: Vector<T>, ratio: T) -> Vector<T> {
         Vector {
-            x: self.x * (1.0 - ratio) + other.x * ratio,
-            y: self.y * (1.0 - ratio) + other.y * ratio,
-            z: self.z * (1.0 - ratio) + other.z * ratio
+            x: self.x * (T::one() - ratio) + other.x * ratio,
+            y: self.y * (T::one() - ratio) + other.y * ratio,
+            z: self.z * (T::one() - ratio) + other.z * ratio
         }
     }
 }
@@ -433,35 +494,35 @@ mrusty_class!(Vector, {
     });
 
     def_self!("zero", |mruby, _slf: Value| {
-        mruby.obj(Vector::zero())
+        mruby.obj(Vector::<f32>::zero())
     });
 
     def_self!("one", |mruby, _slf: Value| {
-        mruby.obj(Vector::one())
+        mruby.obj(Vector::<f32>::one())
     });
 
     def_self!("back", |mruby, _slf: Value| {
-        mruby.obj(Vector::back())
+        mruby.obj(Vector::<f32>::back())
     });
 
     def_self!("down", |mruby, _slf: Value| {
-        mruby.obj(Vector::down())
+        mruby.obj(Vector::<f32>::down())
     });
 
     def_self!("forward", |mruby, _slf: Value| {
-        mruby.obj(Vector::forward())
+        mruby.obj(Vector::<f32>::forward())
     });
 
     def_self!("left", |mruby, _slf: Value| {
-        mruby.obj(Vector::left())
+        mruby.obj(Vector::<f32>::left())
     });
 
     def_self!("right", |mruby, _slf: Value| {
-        mruby.obj(Vector::right())
+        mruby.obj(Vector::<f32>::right())
     });
 
     def_self!("up", |mruby, _slf: Value| {
-        mruby.obj(Vector::up())
+        mruby.obj(Vector::<f32>::up())
     });
 
     def!("==", |mruby, slf: Vector, other: Vector| {
@@ -550,6 +611,14 @@ mrusty_class!(Vector, {
         mruby.float(slf.dist((*other).clone()) as f64)
     });
 
+    def!("xy", |mruby, slf: Vector| {
+        mruby.obj(slf.xy())
+    });
+
+    def!("extend", |mruby, slf: Vector, w: f64| {
+        mruby.obj(slf.extend(w as f32))
+    });
+
     def!("<=>", |mruby, slf: Vector, other: Vector| {
         mruby.float((slf.len() - other.len()) as f64)
     });
@@ -564,9 +633,9 @@ mod tests {
     use mrusty::*;
 
     use super::Vector;
-    use super::super::Quaternion;
+    use super::super::{Quaternion, Vector2, Vector4};
 
-    describe!(Vector, (Quaternion), "
+    describe!(Vector, (Quaternion, Vector2, Vector4), "
       context 'when default' do
         it 'creates zero vector' do
           expect(Vector.zero).to eql Vector.uniform 0.0
@@ -665,6 +734,14 @@ mod tests {
           expect(subject.dist(Vector.new 1.0, -1.0, 1.0)).to eql 2.0
         end
 
+        it 'drops z on #xy' do
+          expect(subject.xy).to eql Vector2.uniform 1.0
+        end
+
+        it 'adds w on #extend' do
+          expect(subject.extend 0.0).to eql Vector4.new 1.0, 1.0, 1.0, 0.0
+        end
+
         it 'adds vectors on #+' do
           expect(subject + Vector.new(1.0, 2.0, 3.0)).to eql Vector.new 2.0, 3.0, 4.0
         end
@@ -0,0 +1,690 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::vector::Vector;
+
+/// A simple 2D vector `struct` tailored specifically for UI and sprites. `Vector2` holds `f32`s
+/// and `DVector2` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::Vector2;
+/// let v1 = Vector2::zero();
+/// let v2 = Vector2::one();
+///
+/// assert_eq!(v1 + v2, Vector2::one());
+/// assert_eq!(v1 * v2, Vector2::zero());
+/// assert_eq!(v1.dot(v2), 0.0);
+/// assert_eq!((v1 + Vector2::one() * 2.0).dot(v2), 4.0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vector2<T = f32> {
+    /// `T` *x* coordinate value
+    pub x: T,
+    /// `T` *y* coordinate value
+    pub y: T
+}
+
+impl<T: Scalar> Vector2<T> {
+    /// Creates a vector using 2 values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new(0.0, 1.0);
+    ///
+    /// assert_eq!(v, Vector2 { x: 0.0, y: 1.0 });
+    /// ```
+    pub fn new(x: T, y: T) -> Vector2<T> {
+        Vector2 { x: x, y: y }
+    }
+
+    /// Creates a vector using an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new_arr([0.0, 1.0]);
+    ///
+    /// assert_eq!(v, Vector2 { x: 0.0, y: 1.0 });
+    /// ```
+    pub fn new_arr(array: [T; 2]) -> Vector2<T> {
+        Vector2 { x: array[0], y: array[1] }
+    }
+
+    /// Creates a uniform vector using 1 value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new_unf(1.0);
+    ///
+    /// assert_eq!(v, Vector2 { x: 1.0, y: 1.0 });
+    /// ```
+    pub fn new_unf(v: T) -> Vector2<T> {
+        Vector2 { x: v, y: v }
+    }
+
+    /// Creates a zero (0.0, 0.0) Vector2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::zero(), Vector2 { x: 0.0, y: 0.0 });
+    /// ```
+    pub fn zero() -> Vector2<T> {
+        Vector2 { x: T::zero(), y: T::zero() }
+    }
+
+    /// Creates a one (1.0, 1.0) Vector2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::one(), Vector2 { x: 1.0, y: 1.0 });
+    /// ```
+    pub fn one() -> Vector2<T> {
+        Vector2 { x: T::one(), y: T::one() }
+    }
+
+    /// Creates a down (0.0, -1.0) Vector2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::down(), Vector2 { x: 0.0, y: -1.0 });
+    /// ```
+    pub fn down() -> Vector2<T> {
+        Vector2 { x: T::zero(), y: -T::one() }
+    }
+
+    /// Creates a left (1.0, 0.0) Vector2, matching `Vector::left`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::left(), Vector2 { x: 1.0, y: 0.0 });
+    /// ```
+    pub fn left() -> Vector2<T> {
+        Vector2 { x: T::one(), y: T::zero() }
+    }
+
+    /// Creates a right (-1.0, 0.0) Vector2, matching `Vector::right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::right(), Vector2 { x: -1.0, y: 0.0 });
+    /// ```
+    pub fn right() -> Vector2<T> {
+        Vector2 { x: -T::one(), y: T::zero() }
+    }
+
+    /// Creates an up (0.0, 1.0) Vector2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// assert_eq!(Vector2::up(), Vector2 { x: 0.0, y: 1.0 });
+    /// ```
+    pub fn up() -> Vector2<T> {
+        Vector2 { x: T::zero(), y: T::one() }
+    }
+
+    /// Computes the length of a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new(3.0, 4.0);
+    ///
+    /// assert_eq!(v.len(), 5.0);
+    /// ```
+    pub fn len(&self) -> T {
+        (self.x.powi(2) + self.y.powi(2)).sqrt()
+    }
+
+    /// Computes the normalized version of a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new(3.0, 4.0);
+    /// let n = v.norm();
+    ///
+    /// assert_eq!(n.len(), 1.0); // Keep precision in mind when comparing floats.
+    /// ```
+    pub fn norm(&self) -> Vector2<T> {
+        let length = self.len();
+
+        Vector2 {
+            x: self.x / length,
+            y: self.y / length
+        }
+    }
+
+    /// Computes the dot product between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v1 = Vector2::new(1.0, 2.0);
+    /// let v2 = Vector2::new(3.0, 3.0);
+    ///
+    /// assert_eq!(v1.dot(v2), 9.0);
+    /// ```
+    pub fn dot(&self, other: Vector2<T>) -> T {
+        self.x * other.x +
+        self.y * other.y
+    }
+
+    /// Computes the *z* of the cross product between two vectors extended with a zero *z*,
+    /// which is positive when `other` is counter-clockwise from `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v1 = Vector2::new(1.0, 0.0);
+    /// let v2 = Vector2::new(0.0, 2.0);
+    ///
+    /// assert_eq!(v1.cross(v2), 2.0);
+    /// assert_eq!(v2.cross(v1), -2.0);
+    /// ```
+    pub fn cross(&self, other: Vector2<T>) -> T {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotates a vector counter-clockwise by `angle` radians.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::f32::consts;
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new(1.0, 0.0).rot(consts::PI / 2.0);
+    ///
+    /// assert!((v - Vector2::new(0.0, 1.0)).len() < 0.00001);
+    /// ```
+    pub fn rot(&self, angle: T) -> Vector2<T> {
+        let (sin, cos) = (angle.sin(), angle.cos());
+
+        Vector2 {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos
+        }
+    }
+
+    /// Rotates a vector counter-clockwise by `angle` radians around a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::f32::consts;
+    /// # use anima_engine::math::Vector2;
+    /// let v = Vector2::new(1.0, 0.0);
+    /// let p = Vector2::new(2.0, 0.0);
+    ///
+    /// assert!((v.rot_around(consts::PI, p) - Vector2::new(3.0, 0.0)).len() < 0.00001);
+    /// ```
+    pub fn rot_around(self, angle: T, point: Vector2<T>) -> Vector2<T> {
+        (self - point).rot(angle) + point
+    }
+
+    /// Computes the angle in radians between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// # use std::f32::consts;
+    /// let v1 = Vector2::new(1.0, 0.0);
+    /// let v2 = Vector2::new(0.0, 2.0);
+    ///
+    /// assert_eq!(v1.angle(v2), consts::PI / 2.0);
+    /// ```
+    pub fn angle(&self, other: Vector2<T>) -> T {
+        self.norm().dot(other.norm()).acos()
+    }
+
+    /// Computes the distance between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector2;
+    /// let v1 = Vector2::new(0.0, 0.0);
+    /// let v2 = Vector2::new(0.0, 1.0);
+    ///
+    /// assert_eq!(v1.dist(v2), 1.0);
+    /// ```
+    pub fn dist(self, other: Vector2<T>) -> T {
+        (self - other).len()
+    }
+
+    /// Extends a vector to a `Vector` with `z`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector, Vector2};
+    /// let v = Vector2::new(1.0, 2.0);
+    ///
+    /// assert_eq!(v.extend(3.0), Vector::new(1.0, 2.0, 3.0));
+    /// ```
+    pub fn extend(&self, z: T) -> Vector<T> {
+        Vector::new(self.x, self.y, z)
+    }
+
+    /// Converts a vector to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DVector2, Vector2};
+    /// let v = DVector2::new(0.1, 0.2);
+    ///
+    /// assert_eq!(v.cast::<f32>(), Vector2::new(0.1, 0.2));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Vector2<U> {
+        Vector2 {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64())
+        }
+    }
+}
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::cmp::Ordering;
+use math::Interpolate;
+
+use mrusty::*;
+
+impl<T: Scalar> Add for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn add(self, other: Vector2<T>) -> Vector2<T> {
+        Vector2 {
+            x: self.x + other.x,
+            y: self.y + other.y
+        }
+    }
+}
+
+impl<T: Scalar> Sub for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn sub(self, other: Vector2<T>) -> Vector2<T> {
+        Vector2 {
+            x: self.x - other.x,
+            y: self.y - other.y
+        }
+    }
+}
+
+impl<T: Scalar> Mul<Vector2<T>> for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn mul(self, other: Vector2<T>) -> Vector2<T> {
+        Vector2 {
+            x: self.x * other.x,
+            y: self.y * other.y
+        }
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn mul(self, scalar: T) -> Vector2<T> {
+        Vector2 {
+            x: self.x * scalar,
+            y: self.y * scalar
+        }
+    }
+}
+
+impl Mul<Vector2<f32>> for f32 {
+    type Output = Vector2<f32>;
+
+    fn mul(self, vector: Vector2<f32>) -> Vector2<f32> {
+        vector * self
+    }
+}
+
+impl Mul<Vector2<f64>> for f64 {
+    type Output = Vector2<f64>;
+
+    fn mul(self, vector: Vector2<f64>) -> Vector2<f64> {
+        vector * self
+    }
+}
+
+impl<T: Scalar> Neg for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn neg(self) -> Vector2<T> {
+        Vector2 {
+            x: -self.x,
+            y: -self.y
+        }
+    }
+}
+
+impl From<Vector2<f32>> for Vector2<f64> {
+    fn from(vector: Vector2<f32>) -> Vector2<f64> {
+        vector.cast()
+    }
+}
+
+impl<T: Scalar> PartialOrd for Vector2<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.len().partial_cmp(&other.len())
+    }
+}
+
+impl<T: Scalar> Interpolate<T> for Vector2<T> {
+    fn interpolate(&self, other: Vector2<T>, ratio: T) -> Vector2<T> {
+        Vector2 {
+            x: self.x * (T::one() - ratio) + other.x * ratio,
+            y: self.y * (T::one() - ratio) + other.y * ratio
+        }
+    }
+}
+
+mrusty_class!(Vector2, {
+    def!("initialize", |x: f64, y: f64| {
+        Vector2::new(x as f32, y as f32)
+    });
+
+    def_self!("from_a", |mruby, _slf: Value, array: Vec| {
+        let x = array[0].to_f64().unwrap();
+        let y = array[1].to_f64().unwrap();
+
+        let vector = Vector2::new(x as f32, y as f32);
+
+        mruby.obj(vector)
+    });
+
+    def_self!("uniform", |mruby, _slf: Value, value: f64| {
+        let value = value as f32;
+        let vector = Vector2::new_unf(value);
+
+        mruby.obj(vector)
+    });
+
+    def_self!("zero", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::zero())
+    });
+
+    def_self!("one", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::one())
+    });
+
+    def_self!("down", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::down())
+    });
+
+    def_self!("left", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::left())
+    });
+
+    def_self!("right", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::right())
+    });
+
+    def_self!("up", |mruby, _slf: Value| {
+        mruby.obj(Vector2::<f32>::up())
+    });
+
+    def!("==", |mruby, slf: Vector2, other: Vector2| {
+        let result = slf.x == other.x &&
+                     slf.y == other.y;
+
+        mruby.bool(result)
+    });
+
+    def!("to_s", |mruby, slf: Vector2| {
+        let string = format!("<Vector2: @x={} @y={}>", slf.x, slf.y);
+
+        mruby.string(&string)
+    });
+
+    def!("+", |mruby, slf: Vector2, other: Vector2| {
+        mruby.obj((*slf).clone() + (*other).clone())
+    });
+
+    def!("-", |mruby, slf: Vector2, other: Vector2| {
+        mruby.obj((*slf).clone() - (*other).clone())
+    });
+
+    def!("*", |mruby, slf: Vector2, other: Value| {
+        match other.class().to_str() {
+            "Float" => {
+                let scalar = other.to_f64().unwrap();
+
+                mruby.obj((*slf).clone() * (scalar as f32))
+            }
+            "Vector2" => {
+                let vector = other.to_obj::<Vector2>().unwrap();
+
+                mruby.obj((*slf).clone() * (*vector).clone())
+            }
+            _ => mruby.raise("TypeError", "expecting Float or Vector2")
+        }
+    });
+
+    def!("-@", |mruby, slf: Vector2| {
+        mruby.obj(-(*slf).clone())
+    });
+
+    def!("x", |mruby, slf: Vector2| {
+        mruby.float(slf.x as f64)
+    });
+
+    def!("y", |mruby, slf: Vector2| {
+        mruby.float(slf.y as f64)
+    });
+
+    def!("len", |mruby, slf: Vector2| {
+        mruby.float(slf.len() as f64)
+    });
+
+    def!("norm", |mruby, slf: Vector2| {
+        mruby.obj(slf.norm())
+    });
+
+    def!("dot", |mruby, slf: Vector2, other: Vector2| {
+        mruby.float(slf.dot((*other).clone()) as f64)
+    });
+
+    def!("cross", |mruby, slf: Vector2, other: Vector2| {
+        mruby.float(slf.cross((*other).clone()) as f64)
+    });
+
+    def!("rot", |mruby, slf: Vector2, angle: f64| {
+        mruby.obj(slf.rot(angle as f32))
+    });
+
+    def!("rot_around", |mruby, slf: Vector2, angle: f64, point: Vector2| {
+        mruby.obj(slf.rot_around(angle as f32, (*point).clone()))
+    });
+
+    def!("angle", |mruby, slf: Vector2, other: Vector2| {
+        mruby.float(slf.angle((*other).clone()) as f64)
+    });
+
+    def!("dist", |mruby, slf: Vector2, other: Vector2| {
+        mruby.float(slf.dist((*other).clone()) as f64)
+    });
+
+    def!("extend", |mruby, slf: Vector2, z: f64| {
+        mruby.obj(slf.extend(z as f32))
+    });
+
+    def!("<=>", |mruby, slf: Vector2, other: Vector2| {
+        mruby.float((slf.len() - other.len()) as f64)
+    });
+
+    def!("interpolate", |mruby, slf: Vector2, other: Vector2, ratio: f64| {
+        mruby.obj(slf.interpolate((*other).clone(), ratio as f32))
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Vector2;
+    use super::super::Vector;
+
+    describe!(Vector2, (Vector), "
+      context 'when default' do
+        it 'creates zero vector' do
+          expect(Vector2.zero).to eql Vector2.uniform 0.0
+        end
+
+        it 'creates one vector' do
+          expect(Vector2.one).to eql Vector2.uniform 1.0
+        end
+
+        it 'creates down vector' do
+          expect(Vector2.down).to eql Vector2.new 0.0, -1.0
+        end
+
+        it 'creates left vector' do
+          expect(Vector2.left).to eql Vector2.new 1.0, 0.0
+        end
+
+        it 'creates right vector' do
+          expect(Vector2.right).to eql Vector2.new -1.0, 0.0
+        end
+
+        it 'creates up vector' do
+          expect(Vector2.up).to eql Vector2.new 0.0, 1.0
+        end
+      end
+
+      context 'when unit' do
+        subject { Vector2.uniform 1.0 }
+
+        it 'returns x on #x' do
+          expect(subject.x).to eql 1.0
+        end
+
+        it 'returns y on #y' do
+          expect(subject.y).to eql 1.0
+        end
+
+        it 'converts to String on #to_s' do
+          expect(subject.to_s).to eql '<Vector2: @x=1 @y=1>'
+        end
+
+        it 'returns vector length on #len' do
+          expect(subject.len).to be_within(0.000001).of 1.41421
+        end
+
+        it 'returns normalized vector on #norm' do
+          norm = subject.norm
+
+          expect(norm.x).to be_within(0.000001).of 0.70711
+          expect(norm.y).to be_within(0.000001).of 0.70711
+        end
+
+        it 'computes dot product on #dot' do
+          expect(subject.dot(Vector2.new 1.0, 2.0)).to eql 3.0
+        end
+
+        it 'computes cross product on #cross' do
+          expect(subject.cross(Vector2.new 1.0, 2.0)).to eql 1.0
+        end
+
+        it 'rotates on #rot' do
+          rotated = subject.rot Math::PI
+
+          expect(rotated.x).to be_within(0.000001).of -1.0
+          expect(rotated.y).to be_within(0.000001).of -1.0
+        end
+
+        it 'rotates around a point on #rot_around' do
+          rotated = subject.rot_around(Math::PI, subject)
+
+          expect(rotated.x).to be_within(0.000001).of 1.0
+          expect(rotated.y).to be_within(0.000001).of 1.0
+        end
+
+        it 'computes angle on #angle' do
+          expect(subject.angle(Vector2.new -1.0, -1.0)).to be_within(0.01).of 3.14
+        end
+
+        it 'computes distance on #dist' do
+          expect(subject.dist(Vector2.new 1.0, -1.0)).to eql 2.0
+        end
+
+        it 'extends to a Vector on #extend' do
+          expect(subject.extend 2.0).to eql Vector.new 1.0, 1.0, 2.0
+        end
+
+        it 'adds vectors on #+' do
+          expect(subject + Vector2.new(1.0, 2.0)).to eql Vector2.new 2.0, 3.0
+        end
+
+        it 'subtracts vectors on #-' do
+          expect(subject - Vector2.new(1.0, 2.0)).to eql Vector2.new 0.0, -1.0
+        end
+
+        it 'multiplies vectors on #*' do
+          expect(subject * Vector2.new(1.0, 2.0)).to eql Vector2.new 1.0, 2.0
+        end
+
+        it 'multiplies vector with a scalar on #*' do
+          expect(subject * 2.0).to eql Vector2.uniform 2.0
+        end
+
+        it 'returns the negative on #-@' do
+          expect(-subject).to eql Vector2.uniform -1.0
+        end
+
+        it 'interpolates on #interpolate' do
+          expect(subject.interpolate(Vector2.uniform(3.0), 0.5)).to eql Vector2.uniform 2.0
+        end
+      end
+
+      context 'when initialized from array' do
+        subject { Vector2.from_a [1.0, 2.0] }
+
+        it { is_expected.to eql Vector2.new 1.0, 2.0 }
+      end
+
+      context 'when in an array' do
+        it 'sorts vectors by their length' do
+          array = [Vector2.uniform(2.0), Vector2.uniform(3.0), Vector2.uniform(1.0)]
+          sorted = [Vector2.uniform(1.0), Vector2.uniform(2.0), Vector2.uniform(3.0)]
+
+          expect(array.sort).to eql sorted
+        end
+      end
+    ");
+}
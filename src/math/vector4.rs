@@ -0,0 +1,613 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use math::Scalar;
+use super::vector::Vector;
+use super::vector2::Vector2;
+
+/// A simple 4D vector `struct` tailored specifically for homogeneous coordinates. `Vector4` holds
+/// `f32`s and `DVector4` holds `f64`s.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::{Matrix, Vector, Vector4};
+/// let point = Vector::new(1.0, 2.0, 3.0).extend(1.0);
+/// let direction = Vector::new(1.0, 2.0, 3.0).extend(0.0);
+///
+/// let m = Matrix::ident().trans(Vector::one());
+///
+/// assert_eq!(m * point, Vector4::new(2.0, 3.0, 4.0, 1.0));
+/// assert_eq!(m * direction, direction);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vector4<T = f32> {
+    /// `T` *x* coordinate value
+    pub x: T,
+    /// `T` *y* coordinate value
+    pub y: T,
+    /// `T` *z* coordinate value
+    pub z: T,
+    /// `T` *w* coordinate value
+    pub w: T
+}
+
+impl<T: Scalar> Vector4<T> {
+    /// Creates a vector using 4 values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v = Vector4::new(0.0, 1.0, 2.0, 3.0);
+    ///
+    /// assert_eq!(v, Vector4 { x: 0.0, y: 1.0, z: 2.0, w: 3.0 });
+    /// ```
+    pub fn new(x: T, y: T, z: T, w: T) -> Vector4<T> {
+        Vector4 { x: x, y: y, z: z, w: w }
+    }
+
+    /// Creates a vector using an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v = Vector4::new_arr([0.0, 1.0, 2.0, 3.0]);
+    ///
+    /// assert_eq!(v, Vector4 { x: 0.0, y: 1.0, z: 2.0, w: 3.0 });
+    /// ```
+    pub fn new_arr(array: [T; 4]) -> Vector4<T> {
+        Vector4 { x: array[0], y: array[1], z: array[2], w: array[3] }
+    }
+
+    /// Creates a uniform vector using 1 value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v = Vector4::new_unf(1.0);
+    ///
+    /// assert_eq!(v, Vector4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 });
+    /// ```
+    pub fn new_unf(v: T) -> Vector4<T> {
+        Vector4 { x: v, y: v, z: v, w: v }
+    }
+
+    /// Creates a zero (0.0, 0.0, 0.0, 0.0) Vector4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// assert_eq!(Vector4::zero(), Vector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 });
+    /// ```
+    pub fn zero() -> Vector4<T> {
+        Vector4 { x: T::zero(), y: T::zero(), z: T::zero(), w: T::zero() }
+    }
+
+    /// Creates a one (1.0, 1.0, 1.0, 1.0) Vector4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// assert_eq!(Vector4::one(), Vector4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 });
+    /// ```
+    pub fn one() -> Vector4<T> {
+        Vector4 { x: T::one(), y: T::one(), z: T::one(), w: T::one() }
+    }
+
+    /// Computes the length of a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v = Vector4::new(1.0, 1.0, 1.0, 1.0);
+    ///
+    /// assert_eq!(v.len(), 2.0);
+    /// ```
+    pub fn len(&self) -> T {
+        (self.x.powi(2) + self.y.powi(2) + self.z.powi(2) + self.w.powi(2)).sqrt()
+    }
+
+    /// Computes the normalized version of a vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v = Vector4::new(1.0, 1.0, 1.0, 1.0);
+    /// let n = v.norm();
+    ///
+    /// assert_eq!(n.len(), 1.0); // Keep precision in mind when comparing floats.
+    /// ```
+    pub fn norm(&self) -> Vector4<T> {
+        let length = self.len();
+
+        Vector4 {
+            x: self.x / length,
+            y: self.y / length,
+            z: self.z / length,
+            w: self.w / length
+        }
+    }
+
+    /// Computes the dot product between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v1 = Vector4::new(1.0, 2.0, 2.0, 1.0);
+    /// let v2 = Vector4::new(3.0, 3.0, 1.0, 2.0);
+    ///
+    /// assert_eq!(v1.dot(v2), 13.0);
+    /// ```
+    pub fn dot(&self, other: Vector4<T>) -> T {
+        self.x * other.x +
+        self.y * other.y +
+        self.z * other.z +
+        self.w * other.w
+    }
+
+    /// Computes the angle in radians between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// # use std::f32::consts;
+    /// let v1 = Vector4::new(1.0, 0.0, 0.0, 0.0);
+    /// let v2 = Vector4::new(0.0, 0.0, 0.0, 2.0);
+    ///
+    /// assert_eq!(v1.angle(v2), consts::PI / 2.0);
+    /// ```
+    pub fn angle(&self, other: Vector4<T>) -> T {
+        self.norm().dot(other.norm()).acos()
+    }
+
+    /// Computes the distance between two vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector4;
+    /// let v1 = Vector4::new(0.0, 0.0, 0.0, 0.0);
+    /// let v2 = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    ///
+    /// assert_eq!(v1.dist(v2), 1.0);
+    /// ```
+    pub fn dist(self, other: Vector4<T>) -> T {
+        (self - other).len()
+    }
+
+    /// Drops *z* and *w*.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector2, Vector4};
+    /// let v = Vector4::new(1.0, 2.0, 3.0, 4.0);
+    ///
+    /// assert_eq!(v.xy(), Vector2::new(1.0, 2.0));
+    /// ```
+    pub fn xy(&self) -> Vector2<T> {
+        Vector2::new(self.x, self.y)
+    }
+
+    /// Drops *w*.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector, Vector4};
+    /// let v = Vector4::new(1.0, 2.0, 3.0, 4.0);
+    ///
+    /// assert_eq!(v.xyz(), Vector::new(1.0, 2.0, 3.0));
+    /// ```
+    pub fn xyz(&self) -> Vector<T> {
+        Vector::new(self.x, self.y, self.z)
+    }
+
+    /// Divides *x*, *y* and *z* by *w*, turning homogeneous coordinates into a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{Vector, Vector4};
+    /// let v = Vector4::new(2.0, 4.0, 6.0, 2.0);
+    ///
+    /// assert_eq!(v.project(), Vector::new(1.0, 2.0, 3.0));
+    /// ```
+    pub fn project(&self) -> Vector<T> {
+        Vector::new(self.x / self.w, self.y / self.w, self.z / self.w)
+    }
+
+    /// Converts a vector to another precision, rounding if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::{DVector4, Vector4};
+    /// let v = DVector4::new(0.1, 0.2, 0.3, 0.4);
+    ///
+    /// assert_eq!(v.cast::<f32>(), Vector4::new(0.1, 0.2, 0.3, 0.4));
+    /// ```
+    pub fn cast<U: Scalar>(&self) -> Vector4<U> {
+        Vector4 {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+            w: U::from_f64(self.w.to_f64())
+        }
+    }
+}
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::cmp::Ordering;
+use math::Interpolate;
+
+use mrusty::*;
+
+impl<T: Scalar> Add for Vector4<T> {
+    type Output = Vector4<T>;
+
+    fn add(self, other: Vector4<T>) -> Vector4<T> {
+        Vector4 {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+            w: self.w + other.w
+        }
+    }
+}
+
+impl<T: Scalar> Sub for Vector4<T> {
+    type Output = Vector4<T>;
+
+    fn sub(self, other: Vector4<T>) -> Vector4<T> {
+        Vector4 {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+            w: self.w - other.w
+        }
+    }
+}
+
+impl<T: Scalar> Mul<Vector4<T>> for Vector4<T> {
+    type Output = Vector4<T>;
+
+    fn mul(self, other: Vector4<T>) -> Vector4<T> {
+        Vector4 {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+            w: self.w * other.w
+        }
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vector4<T> {
+    type Output = Vector4<T>;
+
+    fn mul(self, scalar: T) -> Vector4<T> {
+        Vector4 {
+            x: self.x * scalar,
+            y: self.y * scalar,
+            z: self.z * scalar,
+            w: self.w * scalar
+        }
+    }
+}
+
+impl Mul<Vector4<f32>> for f32 {
+    type Output = Vector4<f32>;
+
+    fn mul(self, vector: Vector4<f32>) -> Vector4<f32> {
+        vector * self
+    }
+}
+
+impl Mul<Vector4<f64>> for f64 {
+    type Output = Vector4<f64>;
+
+    fn mul(self, vector: Vector4<f64>) -> Vector4<f64> {
+        vector * self
+    }
+}
+
+impl<T: Scalar> Neg for Vector4<T> {
+    type Output = Vector4<T>;
+
+    fn neg(self) -> Vector4<T> {
+        Vector4 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: -self.w
+        }
+    }
+}
+
+impl From<Vector4<f32>> for Vector4<f64> {
+    fn from(vector: Vector4<f32>) -> Vector4<f64> {
+        vector.cast()
+    }
+}
+
+impl<T: Scalar> PartialOrd for Vector4<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.len().partial_cmp(&other.len())
+    }
+}
+
+impl<T: Scalar> Interpolate<T> for Vector4<T> {
+    fn interpolate(&self, other: Vector4<T>, ratio: T) -> Vector4<T> {
+        Vector4 {
+            x: self.x * (T::one() - ratio) + other.x * ratio,
+            y: self.y * (T::one() - ratio) + other.y * ratio,
+            z: self.z * (T::one() - ratio) + other.z * ratio,
+            w: self.w * (T::one() - ratio) + other.w * ratio
+        }
+    }
+}
+
+mrusty_class!(Vector4, {
+    def!("initialize", |x: f64, y: f64, z: f64, w: f64| {
+        Vector4::new(x as f32, y as f32, z as f32, w as f32)
+    });
+
+    def_self!("from_a", |mruby, _slf: Value, array: Vec| {
+        let x = array[0].to_f64().unwrap();
+        let y = array[1].to_f64().unwrap();
+        let z = array[2].to_f64().unwrap();
+        let w = array[3].to_f64().unwrap();
+
+        let vector = Vector4::new(x as f32, y as f32, z as f32, w as f32);
+
+        mruby.obj(vector)
+    });
+
+    def_self!("uniform", |mruby, _slf: Value, value: f64| {
+        let value = value as f32;
+        let vector = Vector4::new_unf(value);
+
+        mruby.obj(vector)
+    });
+
+    def_self!("zero", |mruby, _slf: Value| {
+        mruby.obj(Vector4::<f32>::zero())
+    });
+
+    def_self!("one", |mruby, _slf: Value| {
+        mruby.obj(Vector4::<f32>::one())
+    });
+
+    def!("==", |mruby, slf: Vector4, other: Vector4| {
+        let result = slf.x == other.x &&
+                     slf.y == other.y &&
+                     slf.z == other.z &&
+                     slf.w == other.w;
+
+        mruby.bool(result)
+    });
+
+    def!("to_s", |mruby, slf: Vector4| {
+        let string = format!("<Vector4: @x={} @y={} @z={} @w={}>", slf.x, slf.y, slf.z, slf.w);
+
+        mruby.string(&string)
+    });
+
+    def!("+", |mruby, slf: Vector4, other: Vector4| {
+        mruby.obj((*slf).clone() + (*other).clone())
+    });
+
+    def!("-", |mruby, slf: Vector4, other: Vector4| {
+        mruby.obj((*slf).clone() - (*other).clone())
+    });
+
+    def!("*", |mruby, slf: Vector4, other: Value| {
+        match other.class().to_str() {
+            "Float" => {
+                let scalar = other.to_f64().unwrap();
+
+                mruby.obj((*slf).clone() * (scalar as f32))
+            }
+            "Vector4" => {
+                let vector = other.to_obj::<Vector4>().unwrap();
+
+                mruby.obj((*slf).clone() * (*vector).clone())
+            }
+            _ => mruby.raise("TypeError", "expecting Float or Vector4")
+        }
+    });
+
+    def!("-@", |mruby, slf: Vector4| {
+        mruby.obj(-(*slf).clone())
+    });
+
+    def!("x", |mruby, slf: Vector4| {
+        mruby.float(slf.x as f64)
+    });
+
+    def!("y", |mruby, slf: Vector4| {
+        mruby.float(slf.y as f64)
+    });
+
+    def!("z", |mruby, slf: Vector4| {
+        mruby.float(slf.z as f64)
+    });
+
+    def!("w", |mruby, slf: Vector4| {
+        mruby.float(slf.w as f64)
+    });
+
+    def!("len", |mruby, slf: Vector4| {
+        mruby.float(slf.len() as f64)
+    });
+
+    def!("norm", |mruby, slf: Vector4| {
+        mruby.obj(slf.norm())
+    });
+
+    def!("dot", |mruby, slf: Vector4, other: Vector4| {
+        mruby.float(slf.dot((*other).clone()) as f64)
+    });
+
+    def!("angle", |mruby, slf: Vector4, other: Vector4| {
+        mruby.float(slf.angle((*other).clone()) as f64)
+    });
+
+    def!("dist", |mruby, slf: Vector4, other: Vector4| {
+        mruby.float(slf.dist((*other).clone()) as f64)
+    });
+
+    def!("xy", |mruby, slf: Vector4| {
+        mruby.obj(slf.xy())
+    });
+
+    def!("xyz", |mruby, slf: Vector4| {
+        mruby.obj(slf.xyz())
+    });
+
+    def!("project", |mruby, slf: Vector4| {
+        mruby.obj(slf.project())
+    });
+
+    def!("<=>", |mruby, slf: Vector4, other: Vector4| {
+        mruby.float((slf.len() - other.len()) as f64)
+    });
+
+    def!("interpolate", |mruby, slf: Vector4, other: Vector4, ratio: f64| {
+        mruby.obj(slf.interpolate((*other).clone(), ratio as f32))
+    });
+});
+
+#[cfg(test)]
+mod tests {
+    use mrusty::*;
+
+    use super::Vector4;
+    use super::super::{Vector, Vector2};
+
+    describe!(Vector4, (Vector, Vector2), "
+      context 'when default' do
+        it 'creates zero vector' do
+          expect(Vector4.zero).to eql Vector4.uniform 0.0
+        end
+
+        it 'creates one vector' do
+          expect(Vector4.one).to eql Vector4.uniform 1.0
+        end
+      end
+
+      context 'when unit' do
+        subject { Vector4.uniform 1.0 }
+
+        it 'returns x on #x' do
+          expect(subject.x).to eql 1.0
+        end
+
+        it 'returns y on #y' do
+          expect(subject.y).to eql 1.0
+        end
+
+        it 'returns z on #z' do
+          expect(subject.z).to eql 1.0
+        end
+
+        it 'returns w on #w' do
+          expect(subject.w).to eql 1.0
+        end
+
+        it 'converts to String on #to_s' do
+          expect(subject.to_s).to eql '<Vector4: @x=1 @y=1 @z=1 @w=1>'
+        end
+
+        it 'returns vector length on #len' do
+          expect(subject.len).to eql 2.0
+        end
+
+        it 'returns normalized vector on #norm' do
+          expect(subject.norm).to eql Vector4.uniform 0.5
+        end
+
+        it 'computes dot product on #dot' do
+          expect(subject.dot(Vector4.new 1.0, 2.0, 3.0, 4.0)).to eql 10.0
+        end
+
+        it 'computes angle on #angle' do
+          expect(subject.angle(Vector4.uniform -1.0)).to be_within(0.01).of 3.14
+        end
+
+        it 'computes distance on #dist' do
+          expect(subject.dist(Vector4.new 1.0, -1.0, 1.0, 1.0)).to eql 2.0
+        end
+
+        it 'drops z and w on #xy' do
+          expect(subject.xy).to eql Vector2.uniform 1.0
+        end
+
+        it 'drops w on #xyz' do
+          expect(subject.xyz).to eql Vector.uniform 1.0
+        end
+
+        it 'divides by w on #project' do
+          expect((subject * 2.0).project).to eql Vector.uniform 1.0
+        end
+
+        it 'adds vectors on #+' do
+          expect(subject + Vector4.new(1.0, 2.0, 3.0, 4.0)).to eql Vector4.new 2.0, 3.0, 4.0, 5.0
+        end
+
+        it 'subtracts vectors on #-' do
+          expect(subject - Vector4.new(1.0, 2.0, 3.0, 4.0)).to eql(
+            Vector4.new 0.0, -1.0, -2.0, -3.0
+          )
+        end
+
+        it 'multiplies vectors on #*' do
+          expect(subject * Vector4.new(1.0, 2.0, 3.0, 4.0)).to eql Vector4.new 1.0, 2.0, 3.0, 4.0
+        end
+
+        it 'multiplies vector with a scalar on #*' do
+          expect(subject * 2.0).to eql Vector4.uniform 2.0
+        end
+
+        it 'returns the negative on #-@' do
+          expect(-subject).to eql Vector4.uniform -1.0
+        end
+
+        it 'interpolates on #interpolate' do
+          expect(subject.interpolate(Vector4.uniform(3.0), 0.5)).to eql Vector4.uniform 2.0
+        end
+      end
+
+      context 'when initialized from array' do
+        subject { Vector4.from_a [1.0, 2.0, 3.0, 4.0] }
+
+        it { is_expected.to eql Vector4.new 1.0, 2.0, 3.0, 4.0 }
+      end
+
+      context 'when in an array' do
+        it 'sorts vectors by their length' do
+          array = [Vector4.uniform(2.0), Vector4.uniform(3.0), Vector4.uniform(1.0)]
+          sorted = [Vector4.uniform(1.0), Vector4.uniform(2.0), Vector4.uniform(3.0)]
+
+          expect(array.sort).to eql sorted
+        end
+      end
+    ");
+}
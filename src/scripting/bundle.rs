@@ -0,0 +1,487 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::{Component, Path, PathBuf};
+use std::ptr;
+use std::slice;
+
+use mrusty::*;
+
+use super::bundle_error::BundleError;
+use super::exception::ScriptException;
+use super::ffi::{self, Proc, State};
+use super::loader::Loader;
+
+/// Engine version written to the header of every `Bundle`.
+pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+const MAGIC: &'static [u8] = b"ANIMABUN";
+
+// Longest string or file a `Bundle` may hold.
+const MAX_LEN: usize = 1 << 28;
+
+// Size of mruby's `rite_binary_header`, which starts every compiled file.
+const RITE_HEADER_LEN: usize = 22;
+
+/// A `struct` holding a game's scripts compiled ahead of time to mruby bytecode.
+///
+/// A `Bundle` keeps the files' places relative to the roots of the `Loader` they were found
+/// through, so a `Loader` made with `Loader::new_bundle` resolves `require` and
+/// `require_relative` to the same files without reading any source.
+///
+/// Bundles start with the version of the engine that wrote them. Bytecode and the API it was
+/// compiled against are only guaranteed to match within a version, so `read` rejects bundles
+/// written by any other one.
+///
+/// # Examples
+///
+/// ```no-run
+/// let loader = Loader::new(Path::new("game"));
+/// let mut bundle = Bundle::new(&loader, Path::new("game/game.rb")).unwrap();
+///
+/// bundle.add(Path::new("game/player.rb")).unwrap();
+/// bundle.write(&mut File::create("game.bundle").unwrap()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Bundle {
+    roots: Vec<PathBuf>,
+    sources: Vec<Option<PathBuf>>,
+    script: PathBuf,
+    files: Vec<(PathBuf, Vec<u8>)>
+}
+
+impl Bundle {
+    /// Creates a `Bundle` running `script` which can `require` files from `loader`'s roots,
+    /// compiling `script` into it.
+    ///
+    /// Returns a `BundleError` if `script` cannot be read, lies outside `loader`'s roots or does
+    /// not compile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::env;
+    /// # use std::fs::File;
+    /// # use std::io::Write;
+    /// # use std::path::Path;
+    /// # use anima_engine::scripting::{Bundle, Loader};
+    /// let root = env::temp_dir().join("anima_bundle_new");
+    ///
+    /// # ::std::fs::create_dir_all(&root).unwrap();
+    /// File::create(root.join("game.rb")).unwrap().write_all(b"class Game; end").unwrap();
+    ///
+    /// let bundle = Bundle::new(&Loader::new(&root), &root.join("game.rb")).unwrap();
+    ///
+    /// assert_eq!(bundle.script(), Path::new("game.rb"));
+    /// ```
+    pub fn new(loader: &Loader, script: &Path) -> Result<Bundle, BundleError> {
+        let sources: Vec<_> = loader.roots().iter().map(|root| root.canonicalize().ok()).collect();
+
+        let roots = sources.iter().enumerate().map(|(i, source)| {
+            if i == 0 {
+                return PathBuf::new();
+            }
+
+            // Search paths inside the game's root keep their place; others get a name of their
+            // own.
+            let inner = match (source, &sources[0]) {
+                (&Some(ref source), &Some(ref root)) => source.strip_prefix(root).ok(),
+                _                                    => None
+            };
+
+            match inner {
+                Some(inner) => inner.to_owned(),
+                None        => PathBuf::from(format!("<path {}>", i))
+            }
+        }).collect();
+
+        let mut bundle = Bundle {
+            roots: roots,
+            sources: sources,
+            script: PathBuf::new(),
+            files: vec![]
+        };
+
+        bundle.script = bundle.add(script)?;
+
+        Ok(bundle)
+    }
+
+    /// Compiles the script file at `path`, which must lie inside the roots of the `Loader` the
+    /// `Bundle` was created with, into the `Bundle`. Returns the path the file has in the
+    /// `Bundle`.
+    ///
+    /// Files `require`d conditionally, or from inside methods, need to be added like this since
+    /// nothing loads them before the `Bundle` is built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::env;
+    /// # use std::fs::{self, File};
+    /// # use std::path::Path;
+    /// # use anima_engine::scripting::{Bundle, Loader};
+    /// let root = env::temp_dir().join("anima_bundle_add");
+    ///
+    /// fs::create_dir_all(root.join("lib")).unwrap();
+    /// File::create(root.join("game.rb")).unwrap();
+    /// File::create(root.join("lib/player.rb")).unwrap();
+    ///
+    /// let mut bundle = Bundle::new(&Loader::new(&root), &root.join("game.rb")).unwrap();
+    ///
+    /// assert_eq!(bundle.add(&root.join("lib/player.rb")).unwrap(), Path::new("lib/player.rb"));
+    /// assert!(bundle.add(&env::temp_dir()).is_err());
+    /// ```
+    pub fn add(&mut self, path: &Path) -> Result<PathBuf, BundleError> {
+        let canonical = path.canonicalize()?;
+
+        let name = self.sources.iter().zip(self.roots.iter()).filter_map(|(source, root)| {
+            source.as_ref().and_then(|source| canonical.strip_prefix(source).ok())
+                           .map(|inner| root.join(inner))
+        }).next();
+
+        let name = match name {
+            Some(name) => name,
+            None       => {
+                return Err(BundleError::Io(format!("{} is outside the script roots",
+                                                   path.display())));
+            }
+        };
+
+        let code = compile_file(&canonical)?;
+
+        self.files.retain(|&(ref file, _)| *file != name);
+        self.files.push((name.clone(), code));
+
+        Ok(name)
+    }
+
+    /// Returns the roots of the `Bundle`, which stand in for the roots of the `Loader` it was
+    /// created with.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns the path of the script the `Bundle` runs.
+    pub fn script(&self) -> &Path {
+        &self.script
+    }
+
+    /// Returns the paths of the files in the `Bundle`.
+    pub fn files(&self) -> Vec<&Path> {
+        self.files.iter().map(|&(ref path, _)| path.as_path()).collect()
+    }
+
+    /// Returns the bytecode of the file at `path` in the `Bundle`, if any.
+    pub fn code(&self, path: &Path) -> Option<&[u8]> {
+        self.files.iter().find(|&&(ref file, _)| file == path).map(|&(_, ref code)| &code[..])
+    }
+
+    /// Writes the `Bundle` to `writer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::env;
+    /// # use std::fs::{self, File};
+    /// # use std::io::Write;
+    /// # use anima_engine::scripting::{Bundle, Loader};
+    /// let root = env::temp_dir().join("anima_bundle_write");
+    ///
+    /// # fs::create_dir_all(&root).unwrap();
+    /// File::create(root.join("game.rb")).unwrap().write_all(b"class Game; end").unwrap();
+    ///
+    /// let bundle = Bundle::new(&Loader::new(&root), &root.join("game.rb")).unwrap();
+    ///
+    /// let mut data = vec![];
+    /// bundle.write(&mut data).unwrap();
+    ///
+    /// let read = Bundle::read(&mut &data[..]).unwrap();
+    ///
+    /// assert_eq!(read.files(), bundle.files());
+    /// ```
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), BundleError> {
+        writer.write_all(MAGIC)?;
+        write_bytes(writer, VERSION.as_bytes())?;
+
+        write_u32(writer, self.roots.len() as u32)?;
+
+        for root in &self.roots {
+            write_bytes(writer, name(root).as_bytes())?;
+        }
+
+        write_bytes(writer, name(&self.script).as_bytes())?;
+        write_u32(writer, self.files.len() as u32)?;
+
+        for &(ref path, ref code) in &self.files {
+            write_bytes(writer, name(path).as_bytes())?;
+            write_bytes(writer, code)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a `Bundle` from `reader`.
+    ///
+    /// Returns a `BundleError::Version` if the `Bundle` was written by another version of the
+    /// engine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::{Bundle, BundleError};
+    /// let data = b"ANIMABUN\x05\x00\x00\x000.0.1";
+    ///
+    /// assert_eq!(Bundle::read(&mut &data[..]).unwrap_err(),
+    ///            BundleError::Version("0.0.1".to_owned()));
+    /// ```
+    pub fn read<R: Read>(reader: &mut R) -> Result<Bundle, BundleError> {
+        let mut magic = [0; 8];
+
+        read_exact(reader, &mut magic)?;
+
+        if magic != MAGIC {
+            return Err(BundleError::Format);
+        }
+
+        let version = read_string(reader)?;
+
+        if version != VERSION {
+            return Err(BundleError::Version(version));
+        }
+
+        let mut roots = vec![];
+
+        for _ in 0..read_u32(reader)? {
+            roots.push(PathBuf::from(read_string(reader)?));
+        }
+
+        let script = PathBuf::from(read_string(reader)?);
+        let mut files = vec![];
+
+        for _ in 0..read_u32(reader)? {
+            let path = PathBuf::from(read_string(reader)?);
+            let code = read_bytes(reader)?;
+
+            check(&code)?;
+
+            files.push((path, code));
+        }
+
+        if roots.is_empty() || !files.iter().any(|&(ref path, _)| *path == script) {
+            return Err(BundleError::Format);
+        }
+
+        Ok(Bundle {
+            sources: roots.iter().map(|_| None).collect(),
+            roots: roots,
+            script: script,
+            files: files
+        })
+    }
+}
+
+/// Runs the mruby bytecode `code` at the top level of `mruby`, raising a `ScriptError` if `code`
+/// cannot be read. mrusty's `runb` defines constants in the class running when it is called
+/// instead, e.g. in `Anima::Loader` while loading a required file, and trusts `code` blindly.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::mrusty::*;
+/// # use anima_engine::scripting;
+/// let mruby = Mruby::new();
+/// let code = scripting::compile("answer.rb", "ANSWER = 42").unwrap();
+///
+/// scripting::run(&mruby, &code).unwrap();
+///
+/// assert!(scripting::run(&mruby, &code[..10]).is_err());
+/// assert_eq!(mruby.run("ANSWER").unwrap().to_i32().unwrap(), 42);
+/// ```
+pub fn run(mruby: &MrubyType, code: &[u8]) -> Result<Value, MrubyError> {
+    if check(code).is_err() {
+        return Err(MrubyError::Runtime("ScriptError: cannot read bytecode".to_owned()));
+    }
+
+    let object = mruby.get_class("Object")?.class() as *const c_void;
+
+    unsafe {
+        let mrb = mruby.borrow().mrb as *mut State;
+
+        let irep = ffi::mrb_read_irep(mrb, code.as_ptr());
+
+        if irep.is_null() {
+            return Err(MrubyError::Runtime("ScriptError: cannot read bytecode".to_owned()));
+        }
+
+        let procedure = ffi::mrb_proc_new(mrb, irep);
+
+        ffi::mrb_irep_decref(mrb, irep);
+        (*procedure).target_class = object;
+
+        let value = ffi::mrb_toplevel_run(mrb, procedure);
+
+        match ffi::mrb_ext_get_exc(mrb).to_str(mruby.borrow().mrb) {
+            Ok(error) => Err(MrubyError::Runtime(error.to_owned())),
+            Err(_)    => Ok(Value::new(mruby.clone(), value))
+        }
+    }
+}
+
+// `mrb_read_irep` trusts the size in the header of `code` and reads that many bytes, so it has to
+// be checked against the length of `code` first.
+fn check(code: &[u8]) -> Result<(), BundleError> {
+    if code.len() < RITE_HEADER_LEN || &code[0..4] != b"RITE" || &code[4..8] != b"0003" {
+        return Err(BundleError::Format);
+    }
+
+    let size = (code[10] as usize) << 24 | (code[11] as usize) << 16 | (code[12] as usize) << 8 |
+               code[13] as usize;
+
+    if size < RITE_HEADER_LEN || size > code.len() {
+        return Err(BundleError::Format);
+    }
+
+    Ok(())
+}
+
+// Compiles the script at `path`, naming it after its file in backtraces.
+fn compile_file(path: &Path) -> Result<Vec<u8>, BundleError> {
+    let mut source = String::new();
+
+    File::open(path)?.read_to_string(&mut source)?;
+
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned())
+                                    .unwrap_or_default();
+
+    compile(&file_name, &source)
+}
+
+/// Compiles `source` to mruby bytecode in a scratch mruby state, naming it `file_name` in
+/// backtraces. Running the bytecode, e.g. with mrusty's `runb`, leaves the file name of the running
+/// state alone.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::mrusty::*;
+/// # use anima_engine::scripting;
+/// let mruby = Mruby::new();
+/// let code = scripting::compile("double.rb", "21 * 2").unwrap();
+///
+/// assert_eq!(mruby.runb(&code).unwrap().to_i32().unwrap(), 42);
+/// ```
+pub fn compile(file_name: &str, source: &str) -> Result<Vec<u8>, BundleError> {
+    let file_name = CString::new(file_name).unwrap();
+
+    let mruby = Mruby::new();
+
+    unsafe {
+        let mrb = mruby.borrow().mrb as *mut State;
+
+        let context = ffi::mrbc_context_new(mrb);
+
+        ffi::mrbc_filename(mrb, context, file_name.as_ptr());
+        (*context).flags |= ffi::CAPTURE_ERRORS | ffi::NO_EXEC;
+
+        let procedure = ffi::mrb_load_nstring_cxt(mrb, source.as_ptr() as *const c_char,
+                                                  source.len() as c_int, context);
+
+        ffi::mrbc_context_free(mrb, context);
+
+        if let Ok(error) = ffi::mrb_ext_get_exc(mrb).to_str(mruby.borrow().mrb) {
+            return Err(BundleError::Syntax(ScriptException::parse(error)));
+        }
+
+        let irep = (*(procedure.value as *const Proc)).irep;
+
+        let mut bin: *mut u8 = ptr::null_mut();
+        let mut size = 0;
+
+        if ffi::mrb_dump_irep(mrb, irep, ffi::DUMP_DEBUG_INFO, &mut bin, &mut size) != 0 {
+            return Err(BundleError::Io(format!("cannot compile {}", file_name.to_string_lossy())));
+        }
+
+        let code = slice::from_raw_parts(bin, size).to_vec();
+
+        ffi::mrb_free(mrb, bin as *mut c_void);
+
+        Ok(code)
+    }
+}
+
+// Paths are stored with `/` separators so that bundles work across platforms.
+fn name(path: &Path) -> String {
+    let parts: Vec<_> = path.components().map(|component| {
+        match component {
+            Component::Normal(part) => part.to_string_lossy().into_owned(),
+            component               => component.as_os_str().to_string_lossy().into_owned()
+        }
+    }).collect();
+
+    parts.join("/")
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<(), BundleError> {
+    let bytes = [value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8];
+
+    writer.write_all(&bytes)?;
+
+    Ok(())
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), BundleError> {
+    write_u32(writer, bytes.len() as u32)?;
+    writer.write_all(bytes)?;
+
+    Ok(())
+}
+
+// Running out of data means the bundle is truncated rather than unreadable.
+fn read_exact<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<(), BundleError> {
+    reader.read_exact(buffer).map_err(|error| {
+        match error.kind() {
+            ::std::io::ErrorKind::UnexpectedEof => BundleError::Format,
+            _                                   => BundleError::from(error)
+        }
+    })
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, BundleError> {
+    let mut bytes = [0; 4];
+
+    read_exact(reader, &mut bytes)?;
+
+    Ok(bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 |
+       (bytes[3] as u32) << 24)
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, BundleError> {
+    let len = read_u32(reader)? as usize;
+    let mut bytes = vec![];
+
+    if len > MAX_LEN {
+        return Err(BundleError::Format);
+    }
+
+    // The length is not trusted with an allocation of its own.
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+
+    if bytes.len() != len {
+        return Err(BundleError::Format);
+    }
+
+    Ok(bytes)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, BundleError> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| BundleError::Format)
+}
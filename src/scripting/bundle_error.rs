@@ -0,0 +1,61 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use super::bundle::VERSION;
+use super::exception::ScriptException;
+
+/// An `enum` describing what went wrong while building, writing or reading a `Bundle`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BundleError {
+    /// script or bundle could not be read or written
+    Io(String),
+    /// script could not be compiled
+    Syntax(ScriptException),
+    /// data is not a bundle or is truncated
+    Format,
+    /// script is not in the bundle, given here
+    Missing(String),
+    /// bundle was built by another engine version, given here
+    Version(String)
+}
+
+impl From<io::Error> for BundleError {
+    fn from(error: io::Error) -> BundleError {
+        BundleError::Io(format!("{}", error))
+    }
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BundleError::Io(ref error)         => write!(f, "Cannot access bundle: {}", error),
+            BundleError::Syntax(ref exception) => write!(f, "{}", exception),
+            BundleError::Format                => write!(f, "Data is not an Anima bundle"),
+            BundleError::Missing(ref script)   => write!(f, "{} is not in the bundle", script),
+            BundleError::Version(ref version)  => {
+                write!(f, "Bundle was built by Anima {}, but this is Anima {}; rebuild it",
+                       version, VERSION)
+            }
+        }
+    }
+}
+
+impl Error for BundleError {
+    fn description(&self) -> &str {
+        match *self {
+            BundleError::Io(_)      => "bundle cannot be accessed",
+            BundleError::Syntax(_)  => "bundled script has a syntax error",
+            BundleError::Format     => "data is not a bundle",
+            BundleError::Missing(_) => "script is not in the bundle",
+            BundleError::Version(_) => "bundle was built by another engine version"
+        }
+    }
+}
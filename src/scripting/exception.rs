@@ -0,0 +1,114 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::error::Error;
+use std::fmt;
+
+/// A `struct` describing an exception raised by mruby code.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::scripting::ScriptException;
+/// let exception = ScriptException::parse("game.rb:3: undefined method 'bar' (NoMethodError)");
+///
+/// assert_eq!(exception.class, "NoMethodError");
+/// assert_eq!(exception.message, "undefined method 'bar'");
+/// assert_eq!(exception.backtrace, vec!["game.rb:3"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptException {
+    /// `String` name of the exception's `Class`
+    pub class: String,
+    /// `String` message of the exception
+    pub message: String,
+    /// `Vec<String>` locations the exception went through, innermost first
+    pub backtrace: Vec<String>
+}
+
+impl ScriptException {
+    /// Parses the error text mruby reports for an uncaught exception, i.e. its `inspect` followed
+    /// by one backtrace location per line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::ScriptException;
+    /// let exception = ScriptException::parse("SyntaxError: syntax error");
+    ///
+    /// assert_eq!(exception.class, "SyntaxError");
+    /// assert_eq!(exception.message, "syntax error");
+    /// assert!(exception.backtrace.is_empty());
+    /// ```
+    pub fn parse(error: &str) -> ScriptException {
+        let mut lines = error.lines();
+
+        let head = lines.next().unwrap_or("");
+        let mut backtrace: Vec<String> = lines.map(|line| line.trim().to_owned())
+                                              .filter(|line| !line.is_empty())
+                                              .collect();
+
+        let (location, rest) = match head.find(": ") {
+            Some(i) if is_location(&head[..i]) => (Some(&head[..i]), &head[i + 2..]),
+            _                                  => (None, head)
+        };
+
+        let (class, message) = match location {
+            Some(location) => {
+                if backtrace.first().is_none_or(|first| !first.starts_with(location)) {
+                    backtrace.insert(0, location.to_owned());
+                }
+
+                match rest.rfind(" (") {
+                    Some(i) if rest.ends_with(')') => (&rest[i + 2..rest.len() - 1], &rest[..i]),
+                    _                              => (rest, rest)
+                }
+            },
+            None => {
+                match rest.find(": ") {
+                    Some(i) => (&rest[..i], &rest[i + 2..]),
+                    None    => (rest, rest)
+                }
+            }
+        };
+
+        ScriptException {
+            class: class.to_owned(),
+            message: message.to_owned(),
+            backtrace: backtrace
+        }
+    }
+}
+
+fn is_location(text: &str) -> bool {
+    match text.rfind(':') {
+        Some(i) => {
+            let line = &text[i + 1..];
+
+            !line.is_empty() && line.chars().all(|c| c.is_ascii_digit())
+        },
+        None => false
+    }
+}
+
+impl fmt::Display for ScriptException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.class, self.message)?;
+
+        for location in &self.backtrace {
+            write!(f, "\n    from {}", location)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ScriptException {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
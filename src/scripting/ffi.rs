@@ -0,0 +1,125 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+// Mirrors of the parts of mruby 1.2's structures and API which mrusty does not expose.
+
+use std::os::raw::{c_char, c_int, c_void};
+
+use mrusty::MrValue;
+
+#[repr(C)]
+pub struct State {
+    pub jmp: *mut c_void,
+    pub allocf: Allocf,
+    pub allocf_ud: *mut c_void,
+    pub context: *const Context,
+    pub root_context: *const Context,
+    pub exc: *mut c_void
+}
+
+pub type Allocf = extern "C" fn(*mut State, *mut c_void, usize, *mut c_void) -> *mut c_void;
+
+#[repr(C)]
+pub struct Context {
+    pub prev: *const Context,
+    pub stack: *const c_void,
+    pub stack_base: *const c_void,
+    pub stack_end: *const c_void,
+    pub ci: *const CallInfo
+}
+
+#[repr(C)]
+pub struct CallInfo {
+    pub mid: u32,
+    pub procedure: *const Proc
+}
+
+#[repr(C)]
+pub struct Proc {
+    pub header: u32,
+    pub class: *const c_void,
+    pub gcnext: *const c_void,
+    pub irep: *const Irep,
+    pub target_class: *const c_void
+}
+
+#[repr(C)]
+pub struct Irep {
+    pub nlocals: u16,
+    pub nregs: u16,
+    pub flags: u8,
+    pub iseq: *const u32,
+    pub pool: *const c_void,
+    pub syms: *const c_void,
+    pub reps: *const c_void,
+    pub lv: *const c_void,
+    pub filename: *const c_char,
+    pub lines: *const c_void,
+    pub debug_info: *const c_void,
+    pub ilen: usize,
+    pub plen: usize,
+    pub slen: usize,
+    pub rlen: usize,
+    pub refcnt: usize
+}
+
+// `Proc` flag, stored above the 8 bits of type and 3 bits of color in its header.
+pub const PROC_CFUNC: u32 = 128 << 11;
+
+// `Irep` flag marking bytecode which is not owned by the `Irep`, e.g. constant data.
+pub const ISEQ_NO_FREE: u8 = 1;
+
+// Opcodes take the lowest 7 bits of an instruction; `sBx` the 16 above, offset by `MAXARG_SBX`.
+pub const OP_JMP: u32 = 23;
+pub const OP_JMPIF: u32 = 24;
+pub const OP_JMPNOT: u32 = 25;
+pub const OP_RETURN: u32 = 41;
+pub const MAXARG_SBX: i32 = 0x7fff;
+
+#[repr(C)]
+pub struct CompileContext {
+    pub syms: *const c_void,
+    pub slen: c_int,
+    pub filename: *const c_char,
+    pub lineno: i16,
+    pub partial_hook: *const c_void,
+    pub partial_data: *const c_void,
+    pub target_class: *const c_void,
+    pub flags: u8
+}
+
+// `CompileContext` flags.
+pub const CAPTURE_ERRORS: u8 = 1;
+pub const NO_EXEC: u8 = 4;
+
+// `mrb_dump_irep` flag keeping line numbers.
+pub const DUMP_DEBUG_INFO: u8 = 1;
+
+extern "C" {
+    pub fn mrb_exc_new(mrb: *mut State, class: *const c_void, message: *const c_char,
+                       len: usize) -> MrValue;
+    pub fn mrb_gc_register(mrb: *mut State, value: MrValue);
+    pub fn mrb_gc_unregister(mrb: *mut State, value: MrValue);
+    pub fn mrb_free(mrb: *mut State, ptr: *mut c_void);
+
+    pub fn mrbc_context_new(mrb: *mut State) -> *mut CompileContext;
+    pub fn mrbc_context_free(mrb: *mut State, context: *mut CompileContext);
+    pub fn mrbc_filename(mrb: *mut State, context: *mut CompileContext,
+                         filename: *const c_char) -> *const c_char;
+
+    pub fn mrb_load_nstring_cxt(mrb: *mut State, code: *const c_char, len: c_int,
+                                context: *mut CompileContext) -> MrValue;
+    pub fn mrb_read_irep(mrb: *mut State, bin: *const u8) -> *const Irep;
+    pub fn mrb_irep_decref(mrb: *mut State, irep: *const Irep);
+    pub fn mrb_proc_new(mrb: *mut State, irep: *const Irep) -> *mut Proc;
+    pub fn mrb_toplevel_run(mrb: *mut State, procedure: *mut Proc) -> MrValue;
+
+    pub fn mrb_dump_irep(mrb: *mut State, irep: *const Irep, flags: u8, bin: *mut *mut u8,
+                         size: *mut usize) -> c_int;
+
+    pub fn mrb_ext_get_exc(mrb: *mut State) -> MrValue;
+}
@@ -0,0 +1,471 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use mrusty::*;
+
+use super::message::Message;
+use super::registry::{self, Registry};
+
+/// A `struct` running many named mruby scripts which talk to each other through messages.
+///
+/// Every script either gets an isolated mruby state of its own, with `spawn`, or runs in the body
+/// of a `Module` named after it inside a state shared with other scripts, with `spawn_module`.
+/// All states are built by the same `Registry`.
+///
+/// Scripts send messages with `Host.post(to, message)` and receive them in the block passed to
+/// `Host.on_message`, along with the name of the sender, or `nil` for messages posted from Rust.
+/// `Host.script` returns the name of the running script. Messages can be made of `nil`,
+/// booleans, `Fixnum`s, `Float`s, `String`s, `Symbol`s, `Array`s, `Hash`es, `Vector`s,
+/// `Vector2`s, `Vector4`s and `Quaternion`s; they are copied, so the receiver never shares
+/// objects with the sender.
+///
+/// Messages are queued and only delivered by `dispatch`, usually once per frame, in the order they
+/// were posted. Messages posted while dispatching are delivered by the next `dispatch`.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::mrusty::*;
+/// # use anima_engine::scripting::{Registry, ScriptHost};
+/// let mut host = ScriptHost::new(Registry::new_engine());
+///
+/// host.spawn("ai", "
+///   Host.on_message do |from, distance|
+///     Host.post from, { speed: distance * 2 }
+///   end
+/// ").unwrap();
+///
+/// host.spawn("gameplay", "
+///   Host.on_message do |from, orders|
+///     $speed = orders[:speed]
+///   end
+///
+///   Host.post :ai, 2.5
+/// ").unwrap();
+///
+/// assert_eq!(host.dispatch().unwrap(), 1);
+/// assert_eq!(host.dispatch().unwrap(), 1);
+///
+/// let speed = host.get_mruby("gameplay").unwrap().run("$speed").unwrap();
+///
+/// assert_eq!(speed.to_f64().unwrap(), 5.0);
+/// ```
+pub struct ScriptHost {
+    registry: Registry,
+    states: Vec<(Option<String>, MrubyType)>,
+    scripts: Vec<(String, usize)>,
+    mailbox: Rc<RefCell<Mailbox>>
+}
+
+struct Mailbox {
+    scripts: Vec<String>,
+    current: Option<String>,
+    queue: VecDeque<Envelope>
+}
+
+struct Envelope {
+    from: Option<String>,
+    to: String,
+    message: Message
+}
+
+impl ScriptHost {
+    /// Creates a `ScriptHost` building its mruby states with `registry`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::{Registry, ScriptHost};
+    /// let host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// assert!(host.scripts().is_empty());
+    /// ```
+    pub fn new(registry: Registry) -> ScriptHost {
+        ScriptHost {
+            registry: registry,
+            states: vec![],
+            scripts: vec![],
+            mailbox: Rc::new(RefCell::new(Mailbox {
+                scripts: vec![],
+                current: None,
+                queue: VecDeque::new()
+            }))
+        }
+    }
+
+    /// Runs `code` as the script `name` in a new mruby state. Its exceptions report `name.rb` as
+    /// their file. Scripts raising an exception are not spawned.
+    ///
+    /// Returns an `ArgumentError` if a script named `name` already exists or if `name` is not a
+    /// lowercase letter followed by lowercase letters, digits and underscores.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "$screen = :title").unwrap();
+    /// host.spawn("ai", "$screen = :none").unwrap();
+    ///
+    /// let screen = host.get_mruby("ui").unwrap().run("$screen").unwrap();
+    ///
+    /// assert_eq!(screen.call("to_s", vec![]).unwrap().to_str().unwrap(), "title");
+    /// ```
+    pub fn spawn(&mut self, name: &str, code: &str) -> Result<(), MrubyError> {
+        self.check(name)?;
+
+        let mruby = self.build()?;
+
+        self.states.push((None, mruby));
+
+        let state = self.states.len() - 1;
+
+        let result = self.add(name, state, code.to_owned());
+
+        if result.is_err() {
+            self.states.pop();
+        }
+
+        result
+    }
+
+    /// Runs `code` as the script `name` in the body of a `Module` named after it, e.g. `PlayerAi`
+    /// for `player_ai`, inside the mruby state `state`, which is shared by all scripts spawned
+    /// in it and created on first use. Its exceptions report `name.rb` as their file. Scripts
+    /// raising an exception are not spawned, but keep whatever they defined before raising.
+    ///
+    /// Returns an `ArgumentError` if a script named `name` already exists or if `name` is not a
+    /// lowercase letter followed by lowercase letters, digits and underscores.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn_module("hud", "ui", "def self.visible?; true; end").unwrap();
+    /// host.spawn_module("menu", "ui", "def self.open?; Hud.visible?; end").unwrap();
+    ///
+    /// let open = host.get_mruby("menu").unwrap().run("Menu.open?").unwrap();
+    ///
+    /// assert!(open.to_bool().unwrap());
+    /// ```
+    pub fn spawn_module(&mut self, name: &str, state: &str, code: &str) -> Result<(), MrubyError> {
+        self.check(name)?;
+
+        let index = match self.states.iter().position(|&(ref shared, _)| {
+            shared.as_ref().is_some_and(|shared| shared == state)
+        }) {
+            Some(index) => index,
+            None        => {
+                let mruby = self.build()?;
+
+                self.states.push((Some(state.to_owned()), mruby));
+
+                self.states.len() - 1
+            }
+        };
+
+        // Opening the `Module` on the first line keeps the script's line numbers.
+        let code = format!("module {}; {}\nend", module(name), code);
+
+        self.add(name, index, code)
+    }
+
+    /// Returns the names of the scripts, in the order they were spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::{Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "").unwrap();
+    /// host.spawn_module("ai", "world", "").unwrap();
+    ///
+    /// assert_eq!(host.scripts(), vec!["ui", "ai"]);
+    /// ```
+    pub fn scripts(&self) -> Vec<&str> {
+        self.scripts.iter().map(|&(ref name, _)| &name[..]).collect()
+    }
+
+    /// Returns the mruby state the script `name` runs in, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "$answer = 42").unwrap();
+    ///
+    /// let answer = host.get_mruby("ui").unwrap().run("$answer").unwrap();
+    ///
+    /// assert_eq!(answer.to_i32().unwrap(), 42);
+    /// assert!(host.get_mruby("ai").is_none());
+    /// ```
+    pub fn get_mruby(&self, name: &str) -> Option<&MrubyType> {
+        self.scripts.iter().find(|&&(ref script, _)| script == name).map(|&(_, state)| {
+            &self.states[state].1
+        })
+    }
+
+    /// Queues `message` for the script `to`, with a `nil` sender.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{Message, Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "Host.on_message { |from, message| $message = [from, message] }")
+    ///     .unwrap();
+    /// host.post("ui", Message::Symbol("pause".to_owned()));
+    /// host.dispatch().unwrap();
+    ///
+    /// let message = host.get_mruby("ui").unwrap().run("$message.inspect").unwrap();
+    ///
+    /// assert_eq!(message.to_str().unwrap(), "[nil, :pause]");
+    /// ```
+    pub fn post(&mut self, to: &str, message: Message) {
+        self.mailbox.borrow_mut().queue.push_back(Envelope {
+            from: None,
+            to: to.to_owned(),
+            message: message
+        });
+    }
+
+    /// Returns the number of messages waiting to be delivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::{Message, Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "").unwrap();
+    /// host.post("ui", Message::Nil);
+    ///
+    /// assert_eq!(host.pending(), 1);
+    /// ```
+    pub fn pending(&self) -> usize {
+        self.mailbox.borrow().queue.len()
+    }
+
+    /// Delivers the queued messages in the order they were posted, returning how many were
+    /// delivered. Messages for scripts without an `on_message` block are dropped.
+    ///
+    /// Stops at the first block raising an exception and returns it; the message which raised it
+    /// is dropped and the rest are delivered by the next `dispatch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::{Message, Registry, ScriptHost};
+    /// let mut host = ScriptHost::new(Registry::new_engine());
+    ///
+    /// host.spawn("ui", "Host.on_message { |_, message| raise 'boom' if message }").unwrap();
+    /// host.post("ui", Message::Bool(true));
+    /// host.post("ui", Message::Nil);
+    ///
+    /// assert!(host.dispatch().is_err());
+    /// assert_eq!(host.dispatch().unwrap(), 1);
+    /// ```
+    pub fn dispatch(&mut self) -> Result<usize, MrubyError> {
+        let mut delivered = 0;
+
+        for _ in 0..self.pending() {
+            let envelope = match self.mailbox.borrow_mut().queue.pop_front() {
+                Some(envelope) => envelope,
+                None           => return Ok(delivered)
+            };
+
+            let mruby = match self.get_mruby(&envelope.to) {
+                Some(mruby) => mruby.clone(),
+                None        => continue
+            };
+
+            let handlers = mruby.run("$host_handlers")?;
+            let handler = handlers.call("[]", vec![mruby.string(&envelope.to)])?;
+
+            if handler.call("nil?", vec![])?.to_bool()? {
+                continue;
+            }
+
+            let from = match envelope.from {
+                Some(ref from) => mruby.string(from),
+                None           => mruby.nil()
+            };
+            let message = envelope.message.to_value(&mruby);
+
+            self.enter(Some(envelope.to.clone()), || handler.call("call", vec![from, message]))?;
+
+            delivered += 1;
+        }
+
+        Ok(delivered)
+    }
+
+    fn check(&self, name: &str) -> Result<(), MrubyError> {
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase()) && name.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'
+        });
+
+        if !valid {
+            let error = format!("ArgumentError: script name {:?} must be a lowercase identifier",
+                                name);
+
+            return Err(MrubyError::Runtime(error));
+        }
+
+        if self.get_mruby(name).is_some() {
+            let error = format!("ArgumentError: script {:?} already exists", name);
+
+            return Err(MrubyError::Runtime(error));
+        }
+
+        Ok(())
+    }
+
+    fn build(&self) -> Result<MrubyType, MrubyError> {
+        let mruby = self.registry.build()?;
+
+        def_host(&mruby, &self.mailbox);
+
+        mruby.run(HOST_PRELUDE)?;
+
+        Ok(mruby)
+    }
+
+    // Scripts raising while they are spawned are forgotten.
+    fn add(&mut self, name: &str, state: usize, code: String) -> Result<(), MrubyError> {
+        self.scripts.push((name.to_owned(), state));
+        self.mailbox.borrow_mut().scripts.push(name.to_owned());
+
+        let mruby = self.states[state].1.clone();
+
+        mruby.filename(&format!("{}.rb", name));
+
+        let result = self.enter(Some(name.to_owned()), || mruby.run(&code)).map(|_| ());
+
+        if result.is_err() {
+            self.scripts.pop();
+            self.mailbox.borrow_mut().scripts.pop();
+        }
+
+        result
+    }
+
+    // Runs `f` as the script `name`, for `Host.post` and `Host.on_message` to know who calls them.
+    fn enter<F, T>(&self, name: Option<String>, f: F) -> T where F: FnOnce() -> T {
+        let outer = self.mailbox.borrow_mut().current.take();
+
+        self.mailbox.borrow_mut().current = name;
+
+        let result = f();
+
+        self.mailbox.borrow_mut().current = outer;
+
+        result
+    }
+}
+
+// Defines `Host.script`, returning the name of the running script, and `Host.enqueue(to,
+// message)`, returning `nil` or an `Array` containing the `Class` name and message of the
+// exception to raise. Exceptions are raised from Ruby since raising them from Rust does not
+// unwind mrusty's state.
+fn def_host(mruby: &MrubyType, mailbox: &Rc<RefCell<Mailbox>>) {
+    let class = mruby.def_class("Host");
+
+    let current = mailbox.clone();
+
+    mruby.def_class_method(class.clone(), "script", move |mruby, _slf| {
+        match current.borrow().current {
+            Some(ref name) => mruby.string(name),
+            None           => mruby.nil()
+        }
+    });
+
+    let mailbox = mailbox.clone();
+
+    mruby.def_class_method(class, "enqueue", move |mruby, _slf| {
+        let args = registry::args(&mruby);
+
+        if args.len() != 2 {
+            let message = registry::arity(args.len(), 2);
+
+            return mruby.array(vec![mruby.string("ArgumentError"), mruby.string(&message)]);
+        }
+
+        let to = args[0].to_str().unwrap_or("").to_owned();
+
+        if !mailbox.borrow().scripts.contains(&to) {
+            let message = format!("no script named {}", to);
+
+            return mruby.array(vec![mruby.string("ArgumentError"), mruby.string(&message)]);
+        }
+
+        match Message::from_value(&args[1]) {
+            Ok(message) => {
+                let mut mailbox = mailbox.borrow_mut();
+                let from = mailbox.current.clone();
+
+                mailbox.queue.push_back(Envelope {
+                    from: from,
+                    to: to,
+                    message: message
+                });
+
+                mruby.nil()
+            },
+            Err(class) => {
+                let message = format!("cannot post {}", class);
+
+                mruby.array(vec![mruby.string("TypeError"), mruby.string(&message)])
+            }
+        }
+    });
+}
+
+// Converts a script name to the name of its `Module`, e.g. `player_ai` to `PlayerAi`.
+fn module(name: &str) -> String {
+    name.split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+
+        match chars.next() {
+            Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+            None        => String::new()
+        }
+    }).collect()
+}
+
+const HOST_PRELUDE: &'static str = "
+  $host_handlers = {}
+
+  class Host
+    def self.post(to, message)
+      error = enqueue to.to_s, message
+
+      raise Object.const_get(error[0]), error[1] if error
+
+      nil
+    end
+
+    def self.on_message(&block)
+      $host_handlers[script] = block
+    end
+  end
+";
@@ -0,0 +1,208 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use super::bundle::Bundle;
+
+/// A `struct` resolving the files mruby scripts load with `require` and `require_relative`.
+///
+/// Files are searched for in script roots: the game's root directory, followed by any number of
+/// search paths. Names without an extension get `.rb` appended. A file resolves only if it lies
+/// inside one of the roots once symbolic links and `..` are resolved, so scripts cannot load
+/// arbitrary files.
+///
+/// A `Loader` made from a `Bundle` resolves files in the `Bundle` instead, the same way.
+///
+/// Add a `Loader` to a `Registry` to use it in mruby states.
+///
+/// # Examples
+///
+/// ```no-run
+/// let loader = Loader::new(Path::new("game")).path(Path::new("lib"));
+///
+/// // Resolves to game/player.rb or game/lib/player.rb.
+/// let player = loader.resolve("player", None).unwrap();
+///
+/// // Refused.
+/// assert!(loader.resolve("../secrets", None).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Loader {
+    roots: Vec<PathBuf>,
+    bundle: Option<Rc<Bundle>>
+}
+
+impl Loader {
+    /// Creates a `Loader` confined to the game's `root` directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use anima_engine::scripting::Loader;
+    /// let loader = Loader::new(Path::new("game"));
+    ///
+    /// assert_eq!(loader.roots(), &[Path::new("game")]);
+    /// ```
+    pub fn new(root: &Path) -> Loader {
+        Loader {
+            roots: vec![root.to_owned()],
+            bundle: None
+        }
+    }
+
+    /// Creates a `Loader` resolving files in `bundle`.
+    ///
+    /// # Examples
+    ///
+    /// ```no-run
+    /// let bundle = Bundle::read(&mut File::open("game.bundle").unwrap()).unwrap();
+    /// let loader = Loader::new_bundle(bundle);
+    ///
+    /// // Resolves to game.rb in the bundle.
+    /// let game = loader.resolve("game", None).unwrap();
+    /// ```
+    pub fn new_bundle(bundle: Bundle) -> Loader {
+        Loader {
+            roots: bundle.roots().to_vec(),
+            bundle: Some(Rc::new(bundle))
+        }
+    }
+
+    /// Adds a search `path` after the existing ones. Relative paths are relative to the game's
+    /// root directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use anima_engine::scripting::Loader;
+    /// let loader = Loader::new(Path::new("game")).path(Path::new("lib"))
+    ///                                            .path(Path::new("/usr/share/anima"));
+    ///
+    /// assert_eq!(loader.roots(), &[Path::new("game"), Path::new("game/lib"),
+    ///                              Path::new("/usr/share/anima")]);
+    /// ```
+    pub fn path(mut self, path: &Path) -> Loader {
+        let path = self.roots[0].join(path);
+
+        self.roots.push(path);
+
+        self
+    }
+
+    /// Returns the script roots, starting with the game's root directory.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns the `Bundle` the `Loader` resolves files in, if any.
+    pub fn bundle(&self) -> Option<&Bundle> {
+        self.bundle.as_deref()
+    }
+
+    /// Resolves `name` to the canonical path of a script file.
+    ///
+    /// Names starting with `./` or `../` are relative to the directory of the file `from`; other
+    /// relative names are searched for in the roots, in order. Without a `from` file, the game's
+    /// root directory is used instead.
+    ///
+    /// Returns an `io::Error` of kind `NotFound` if there is no such file, or of kind
+    /// `PermissionDenied` if the file lies outside the roots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::env;
+    /// # use std::fs::File;
+    /// # use std::io::ErrorKind;
+    /// # use anima_engine::scripting::Loader;
+    /// let root = env::temp_dir();
+    ///
+    /// File::create(root.join("anima_resolve.rb")).unwrap();
+    ///
+    /// let loader = Loader::new(&root);
+    ///
+    /// assert!(loader.resolve("anima_resolve", None).unwrap().ends_with("anima_resolve.rb"));
+    /// assert_eq!(loader.resolve("anima_nowhere", None).unwrap_err().kind(), ErrorKind::NotFound);
+    /// ```
+    pub fn resolve(&self, name: &str, from: Option<&Path>) -> io::Result<PathBuf> {
+        let root = &self.roots[0];
+        let base = from.and_then(|from| from.parent()).unwrap_or(root);
+
+        let dirs: Vec<&Path> = if name.starts_with("./") || name.starts_with("../") {
+            vec![base]
+        } else {
+            self.roots.iter().map(|root| root.as_path()).collect()
+        };
+
+        for dir in dirs {
+            let path = dir.join(name);
+            let candidates = match path.extension() {
+                Some(_) => vec![path],
+                None    => vec![path.with_extension("rb"), path]
+            };
+
+            for candidate in candidates {
+                if let Some(ref bundle) = self.bundle {
+                    let candidate = normalize(&candidate);
+
+                    if bundle.code(&candidate).is_some() {
+                        return self.confine(&candidate);
+                    }
+                } else if candidate.is_file() {
+                    return self.confine(&candidate);
+                }
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("cannot load such file -- {}", name)))
+    }
+
+    fn confine(&self, path: &Path) -> io::Result<PathBuf> {
+        // Bundles only hold files from inside the roots.
+        if self.bundle.is_some() {
+            return Ok(path.to_owned());
+        }
+
+        let path = path.canonicalize()?;
+
+        for root in &self.roots {
+            if let Ok(root) = root.canonicalize() {
+                if path.starts_with(root) {
+                    return Ok(path);
+                }
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::PermissionDenied,
+                           format!("{} is outside the script roots", path.display())))
+    }
+}
+
+// Resolves `.` and `..` in a path within a `Bundle`, where there are no links to follow.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir    => (),
+            Component::ParentDir => {
+                match normal.components().next_back() {
+                    Some(Component::Normal(_)) => { normal.pop(); },
+                    _                          => normal.push("..")
+                }
+            },
+            component            => normal.push(component.as_os_str())
+        }
+    }
+
+    normal
+}
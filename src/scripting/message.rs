@@ -0,0 +1,192 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use mrusty::*;
+
+use super::super::math::{Quaternion, Vector, Vector2, Vector4};
+
+/// An `enum` holding a value passed between mruby states by a `ScriptHost`. Messages are copied
+/// out of the sending state, so the receiver never shares objects with the sender.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::math::Vector;
+/// # use anima_engine::scripting;
+/// # use anima_engine::scripting::Message;
+/// let mruby = scripting::get_mruby();
+///
+/// let message = Message::Array(vec![
+///     Message::Symbol("hit".to_owned()),
+///     Message::Vector(Vector::new(1.0, 2.0, 3.0))
+/// ]);
+/// let value = message.to_value(&mruby);
+///
+/// assert_eq!(Message::from_value(&value).unwrap(), message);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Nil,
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(String),
+    Symbol(String),
+    Array(Vec<Message>),
+    /// `Hash` entries in insertion order
+    Hash(Vec<(Message, Message)>),
+    Vector(Vector),
+    Vector2(Vector2),
+    Vector4(Vector4),
+    Quaternion(Quaternion)
+}
+
+impl Message {
+    /// Copies `value` into a `Message`. Returns the name of the offending `Class` if `value` is
+    /// or contains anything but `nil`, booleans, `Fixnum`s, `Float`s, `String`s, `Symbol`s,
+    /// `Array`s, `Hash`es, vectors and `Quaternion`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting;
+    /// # use anima_engine::scripting::Message;
+    /// let mruby = scripting::get_mruby();
+    ///
+    /// let value = mruby.run("{ hp: 10 }").unwrap();
+    /// let message = Message::from_value(&value).unwrap();
+    ///
+    /// assert_eq!(message, Message::Hash(vec![(Message::Symbol("hp".to_owned()),
+    ///                                         Message::Int(10))]));
+    ///
+    /// let value = mruby.run("Object.new").unwrap();
+    ///
+    /// assert_eq!(Message::from_value(&value), Err("Object".to_owned()));
+    /// ```
+    pub fn from_value(value: &Value) -> Result<Message, String> {
+        // `Value::class` cannot name classes of Rust types.
+        let class = value.call("class", vec![]).and_then(|class| class.call("to_s", vec![]))
+                                               .and_then(|name| name.to_str().map(String::from))
+                                               .unwrap_or_default();
+
+        let message = match &class[..] {
+            "NilClass"   => Message::Nil,
+            "TrueClass"  => Message::Bool(true),
+            "FalseClass" => Message::Bool(false),
+            "Fixnum"     => Message::Int(value.to_i32().map_err(|_| class.clone())?),
+            "Float"      => Message::Float(value.to_f64().map_err(|_| class.clone())?),
+            "String"     => {
+                Message::String(value.to_str().map_err(|_| class.clone())?.to_owned())
+            },
+            "Symbol"     => {
+                let name = value.call("to_s", vec![]).map_err(|_| class.clone())?;
+
+                Message::Symbol(name.to_str().map_err(|_| class.clone())?.to_owned())
+            },
+            "Array"      => {
+                let values = value.to_vec().map_err(|_| class.clone())?;
+                let messages: Result<_, _> = values.iter().map(Message::from_value).collect();
+
+                Message::Array(messages?)
+            },
+            "Hash"       => {
+                let pairs = value.call("to_a", vec![]).and_then(|pairs| pairs.to_vec())
+                                                      .map_err(|_| class.clone())?;
+                let mut entries = vec![];
+
+                for pair in pairs {
+                    let pair = pair.to_vec().map_err(|_| class.clone())?;
+
+                    entries.push((Message::from_value(&pair[0])?,
+                                  Message::from_value(&pair[1])?));
+                }
+
+                Message::Hash(entries)
+            },
+            "Vector"     => Message::Vector(*value.to_obj::<Vector>().map_err(|_| class.clone())?),
+            "Vector2"    => {
+                Message::Vector2(*value.to_obj::<Vector2>().map_err(|_| class.clone())?)
+            },
+            "Vector4"    => {
+                Message::Vector4(*value.to_obj::<Vector4>().map_err(|_| class.clone())?)
+            },
+            "Quaternion" => {
+                Message::Quaternion(*value.to_obj::<Quaternion>().map_err(|_| class.clone())?)
+            },
+            _            => return Err(class)
+        };
+
+        Ok(message)
+    }
+
+    /// Creates a `Value` in `mruby` holding a copy of the `Message`. Vector and `Quaternion`
+    /// classes are defined in `mruby` if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Message;
+    /// let mruby = Mruby::new();
+    ///
+    /// let message = Message::Array(vec![Message::Int(1), Message::String("two".to_owned())]);
+    /// let value = message.to_value(&mruby);
+    ///
+    /// assert_eq!(value.call("inspect", vec![]).unwrap().to_str().unwrap(), "[1, \"two\"]");
+    /// ```
+    pub fn to_value(&self, mruby: &MrubyType) -> Value {
+        match *self {
+            Message::Nil                    => mruby.nil(),
+            Message::Bool(value)            => mruby.bool(value),
+            Message::Int(value)             => mruby.fixnum(value),
+            Message::Float(value)           => mruby.float(value),
+            Message::String(ref value)      => mruby.string(value),
+            Message::Symbol(ref value)      => mruby.symbol(value),
+            Message::Array(ref messages)    => {
+                mruby.array(messages.iter().map(|message| message.to_value(mruby)).collect())
+            },
+            Message::Hash(ref entries)      => {
+                let hash = mruby.run("{}").unwrap();
+
+                for &(ref key, ref value) in entries {
+                    hash.call("[]=", vec![key.to_value(mruby), value.to_value(mruby)]).unwrap();
+                }
+
+                hash
+            },
+            Message::Vector(vector)         => {
+                if !mruby.is_defined("Vector") {
+                    Vector::require(mruby.clone());
+                }
+
+                mruby.obj(vector)
+            },
+            Message::Vector2(vector)        => {
+                if !mruby.is_defined("Vector2") {
+                    Vector2::require(mruby.clone());
+                }
+
+                mruby.obj(vector)
+            },
+            Message::Vector4(vector)        => {
+                if !mruby.is_defined("Vector4") {
+                    Vector4::require(mruby.clone());
+                }
+
+                mruby.obj(vector)
+            },
+            Message::Quaternion(quaternion) => {
+                if !mruby.is_defined("Quaternion") {
+                    Quaternion::require(mruby.clone());
+                }
+
+                mruby.obj(quaternion)
+            }
+        }
+    }
+}
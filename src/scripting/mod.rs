@@ -7,24 +7,56 @@
 
 //! A `mod` containing scripting-related helpers.
 
-use mrusty::*;
+mod bundle;
+mod bundle_error;
+mod exception;
+mod ffi;
+mod host;
+mod loader;
+mod message;
+mod registry;
+mod watchdog;
+
+pub use self::bundle::Bundle;
+pub use self::bundle::compile;
+pub use self::bundle::run;
+pub use self::bundle_error::BundleError;
+pub use self::exception::ScriptException;
+pub use self::host::ScriptHost;
+pub use self::loader::Loader;
+pub use self::message::Message;
+pub use self::registry::Registry;
+pub use self::watchdog::Watchdog;
 
-use super::math::Bezier;
-use super::math::Interpolator;
-use super::math::Matrix;
-use super::math::Quaternion;
-use super::math::Vector;
+use mrusty::*;
 
 /// A `fn` that returns a new mruby state with `require`able Anima API.
 ///
 /// API is structured in virtual mruby files thus:
 ///
 /// * `math`
+///   * `Aabb`
 ///   * `Bezier`
+///   * `Frustum`
 ///   * `Interpolator`
 ///   * `Matrix`
+///   * `Obb`
+///   * `Plane`
 ///   * `Quaternion`
+///   * `Ray`
+///   * `Sphere`
+///   * `SquadPath`
+///   * `Transform`
+///   * `Triangle`
 ///   * `Vector`
+///   * `Vector2`
+///   * `Vector4`
+/// * `game`
+///   * `Profiler`
+///   * `TimeControl`
+///
+/// Use a `Registry` to choose which files a state exposes or to add your own, and a `ScriptHost` to
+/// run many scripts which talk to each other.
 ///
 /// # Examples
 ///
@@ -36,13 +68,5 @@ use super::math::Vector;
 /// mruby.run("require 'math'; Vector.uniform 0.1").unwrap();
 /// ```
 pub fn get_mruby() -> MrubyType {
-    let mruby = Mruby::new();
-
-    mruby.def_file::<Bezier>("math");
-    mruby.def_file::<Interpolator>("math");
-    mruby.def_file::<Matrix>("math");
-    mruby.def_file::<Quaternion>("math");
-    mruby.def_file::<Vector>("math");
-
-    mruby
+    Registry::new_engine().build().unwrap()
 }
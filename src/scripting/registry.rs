@@ -0,0 +1,519 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+use mrusty::*;
+
+use super::bundle;
+use super::exception::ScriptException;
+use super::loader::Loader;
+use super::super::game::{Profiler, PROFILER_PRELUDE, TimeControl};
+use super::super::math::{Aabb, Bezier, Frustum, Interpolator, Matrix, Obb, Plane, Quaternion};
+use super::super::math::{Ray, Sphere, SquadPath, Transform, Triangle, Vector, Vector2, Vector4};
+
+/// A `struct` describing which APIs an mruby state exposes, used to build such states.
+///
+/// APIs are grouped in virtual files which scripts load with `require`. A file can contain Rust
+/// types bound with `mrusty_class!` and Ruby code, which runs when the file is first required.
+/// Preludes are Ruby code run in every new state, after all files are defined.
+///
+/// With a `Loader`, `require` also loads script files from the filesystem and `require_relative`
+/// becomes available. Virtual files take precedence over script files of the same name.
+///
+/// # Examples
+///
+/// ```
+/// # use anima_engine::mrusty::*;
+/// # use anima_engine::math::Vector;
+/// # use anima_engine::scripting::Registry;
+/// let registry = Registry::new()
+///     .def_file::<Vector>("math")
+///     .def_file_code("math", "class Vector; def self.origin; Vector.zero; end; end")
+///     .prelude("require 'math'");
+///
+/// let mruby = registry.build().unwrap();
+///
+/// assert!(mruby.run("Vector.origin").is_ok());
+/// ```
+#[derive(Clone, Default)]
+pub struct Registry {
+    files: Vec<File>,
+    preludes: Vec<String>,
+    loader: Option<Loader>
+}
+
+type Def = Rc<dyn Fn(&MrubyType, &str)>;
+
+#[derive(Clone)]
+struct File {
+    name: String,
+    types: Vec<Def>,
+    code: Vec<String>
+}
+
+impl Registry {
+    /// Creates an empty `Registry`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new();
+    ///
+    /// assert!(registry.files().is_empty());
+    /// ```
+    pub fn new() -> Registry {
+        Registry {
+            files: vec![],
+            preludes: vec![],
+            loader: None
+        }
+    }
+
+    /// Creates a `Registry` containing the whole Anima API:
+    ///
+    /// * `math`
+    ///   * `Aabb`
+    ///   * `Bezier`
+    ///   * `Frustum`
+    ///   * `Interpolator`
+    ///   * `Matrix`
+    ///   * `Obb`
+    ///   * `Plane`
+    ///   * `Quaternion`
+    ///   * `Ray`
+    ///   * `Sphere`
+    ///   * `SquadPath`
+    ///   * `Transform`
+    ///   * `Triangle`
+    ///   * `Vector`
+    ///   * `Vector2`
+    ///   * `Vector4`
+    /// * `game`
+    ///   * `Profiler`
+    ///   * `TimeControl`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new_engine();
+    ///
+    /// assert_eq!(registry.files(), vec!["math", "game"]);
+    /// ```
+    pub fn new_engine() -> Registry {
+        Registry::new().def_file::<Aabb>("math")
+                       .def_file::<Bezier>("math")
+                       .def_file::<Frustum>("math")
+                       .def_file::<Interpolator>("math")
+                       .def_file::<Matrix>("math")
+                       .def_file::<Obb>("math")
+                       .def_file::<Plane>("math")
+                       .def_file::<Quaternion>("math")
+                       .def_file::<Ray>("math")
+                       .def_file::<Sphere>("math")
+                       .def_file::<SquadPath>("math")
+                       .def_file::<Transform>("math")
+                       .def_file::<Triangle>("math")
+                       .def_file::<Vector>("math")
+                       .def_file::<Vector2>("math")
+                       .def_file::<Vector4>("math")
+                       .def_file::<Profiler>("game")
+                       .def_file_code("game", PROFILER_PRELUDE)
+                       .def_file::<TimeControl>("game")
+    }
+
+    /// Adds the Rust type `T` to the virtual file `name`, creating the file if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::math::Vector;
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new().def_file::<Vector>("vector");
+    ///
+    /// assert_eq!(registry.files(), vec!["vector"]);
+    /// ```
+    pub fn def_file<T: MrubyFile + 'static>(mut self, name: &str) -> Registry {
+        self.file(name).types.push(Rc::new(|mruby, name| mruby.def_file::<T>(name)));
+
+        self
+    }
+
+    /// Adds Ruby `code` to the virtual file `name`, creating the file if needed. The code runs
+    /// when the file is first required, after its Rust types are defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new().def_file_code("greeting", "def hi; 'hi'; end");
+    /// let mruby = registry.build().unwrap();
+    ///
+    /// assert_eq!(mruby.run("require 'greeting'; hi").unwrap().to_str().unwrap(), "hi");
+    /// ```
+    pub fn def_file_code(mut self, name: &str, code: &str) -> Registry {
+        self.file(name).code.push(code.to_owned());
+
+        self
+    }
+
+    /// Adds Ruby `code` run in every new state. Preludes run in the order they were added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new_engine().prelude("require 'math'");
+    /// let mruby = registry.build().unwrap();
+    ///
+    /// assert!(mruby.run("Vector").is_ok());
+    /// ```
+    pub fn prelude(mut self, code: &str) -> Registry {
+        self.preludes.push(code.to_owned());
+
+        self
+    }
+
+    /// Loads script files from the filesystem with `loader`.
+    ///
+    /// Loaded files are cached: requiring a file again returns `false`, even under a different
+    /// name. Requiring a file which is still being loaded raises a `LoadError`, as does requiring
+    /// a missing file or one outside the `Loader`'s roots. The canonical paths of loaded files are
+    /// kept in `$LOADED_FEATURES`, in the order they finished loading.
+    ///
+    /// `require_relative` resolves names relative to the file being loaded, or to the game's root
+    /// directory once loading is over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::env;
+    /// # use std::fs::File;
+    /// # use std::io::Write;
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{Loader, Registry};
+    /// let root = env::temp_dir();
+    ///
+    /// File::create(root.join("anima_answer.rb")).unwrap().write_all(b"ANSWER = 42").unwrap();
+    ///
+    /// let mruby = Registry::new().loader(Loader::new(&root)).build().unwrap();
+    ///
+    /// assert!(mruby.run("require 'anima_answer'").unwrap().to_bool().unwrap());
+    /// assert!(!mruby.run("require_relative 'anima_answer'").unwrap().to_bool().unwrap());
+    /// assert!(mruby.run("require '../anima_answer'").is_err());
+    /// ```
+    pub fn loader(mut self, loader: Loader) -> Registry {
+        self.loader = Some(loader);
+
+        self
+    }
+
+    /// Keeps only the files called `names`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Registry;
+    /// let mruby = Registry::new_engine().only(&["math"]).build().unwrap();
+    ///
+    /// assert!(mruby.run("require 'math'").is_ok());
+    /// assert!(mruby.run("require 'game'").is_err());
+    /// ```
+    pub fn only(mut self, names: &[&str]) -> Registry {
+        self.files.retain(|file| names.contains(&&file.name[..]));
+
+        self
+    }
+
+    /// Removes the file called `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::scripting::Registry;
+    /// let registry = Registry::new_engine().without("game");
+    ///
+    /// assert_eq!(registry.files(), vec!["math"]);
+    /// ```
+    pub fn without(mut self, name: &str) -> Registry {
+        self.files.retain(|file| file.name != name);
+
+        self
+    }
+
+    /// Returns the `Loader` resolving script files, if any.
+    pub fn get_loader(&self) -> Option<&Loader> {
+        self.loader.as_ref()
+    }
+
+    /// Returns the names of the virtual files, in the order they were added.
+    pub fn files(&self) -> Vec<&str> {
+        self.files.iter().map(|file| &file.name[..]).collect()
+    }
+
+    /// Builds a new mruby state exposing the `Registry`'s files and runs its preludes. Returns an
+    /// error if a prelude fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Registry;
+    /// let mruby = Registry::new_engine().build().unwrap();
+    ///
+    /// mruby.run("require 'math'; Vector.one").unwrap();
+    /// ```
+    pub fn build(&self) -> Result<MrubyType, MrubyError> {
+        let mruby = Mruby::new();
+
+        for file in &self.files {
+            for def in &file.types {
+                def(&mruby, &file.name);
+            }
+        }
+
+        let code: Vec<_> = self.files.iter().filter(|file| !file.code.is_empty()).collect();
+
+        // Names are passed as values, since any text is a valid name.
+        if !code.is_empty() {
+            mruby.run("$registry_files = {}")?;
+
+            let add_file = mruby.run("proc { |name, file| $registry_files[name] = file }")?;
+
+            for file in code {
+                let code = mruby.run(&format!("proc do\n{}\nend", file.code.join("\n")))?;
+                let file_value = mruby.array(vec![mruby.bool(!file.types.is_empty()), code]);
+
+                add_file.call("call", vec![mruby.string(&file.name), file_value])?;
+            }
+
+            mruby.run(REQUIRE_PRELUDE)?;
+        }
+
+        if let Some(ref loader) = self.loader {
+            def_loader(&mruby, loader);
+
+            let virtual_files = self.files.iter().map(|file| mruby.string(&file.name)).collect();
+            let set_virtual = mruby.run("proc { |files| $registry_virtual = files }")?;
+
+            set_virtual.call("call", vec![mruby.array(virtual_files)])?;
+            mruby.run(LOADER_PRELUDE)?;
+        }
+
+        for prelude in &self.preludes {
+            mruby.run(prelude)?;
+        }
+
+        Ok(mruby)
+    }
+
+    fn file(&mut self, name: &str) -> &mut File {
+        if let Some(i) = self.files.iter().position(|file| file.name == name) {
+            return &mut self.files[i];
+        }
+
+        self.files.push(File {
+            name: name.to_owned(),
+            types: vec![],
+            code: vec![]
+        });
+
+        self.files.last_mut().unwrap()
+    }
+}
+
+// Defines `Anima::Loader.resolve(name, from)`, returning a path or an `Array` containing the
+// error message, and `Anima::Loader.load(path, from)`, returning `nil` or an `Array` containing
+// the `Class` name and message of the exception raised by the file. Exceptions are raised from
+// Ruby since raising them from Rust does not unwind mrusty's state.
+fn def_loader(mruby: &MrubyType, loader: &Loader) {
+    let anima = mruby.def_module("Anima");
+    let class = mruby.def_class_under("Loader", &anima);
+
+    let resolver = loader.clone();
+
+    mruby.def_class_method(class.clone(), "resolve", move |mruby, _slf| {
+        let args = args(&mruby);
+
+        if args.len() != 2 {
+            return mruby.array(vec![mruby.string(&arity(args.len(), 2))]);
+        }
+
+        let name = args[0].to_str().unwrap_or("");
+        let from = args[1].to_str().ok().map(Path::new);
+
+        match resolver.resolve(name, from) {
+            Ok(path)   => mruby.string(&path.to_string_lossy()),
+            Err(error) => mruby.array(vec![mruby.string(&error.to_string())])
+        }
+    });
+
+    let loader = loader.clone();
+
+    mruby.def_class_method(class, "load", move |mruby, _slf| {
+        let args = args(&mruby);
+
+        if args.len() != 2 {
+            return mruby.array(vec![mruby.string("ArgumentError"),
+                                    mruby.string(&arity(args.len(), 2))]);
+        }
+
+        let path = args[0].to_str().unwrap_or("").to_owned();
+        let from = args[1].to_str().ok().map(Path::new);
+
+        let result = match loader.bundle() {
+            Some(bundle) => {
+                match bundle.code(Path::new(&path)) {
+                    Some(code) => bundle::run(&mruby, code),
+                    None       => Err(MrubyError::Filetype)
+                }
+            },
+            None         => mruby.execute(Path::new(&path))
+        };
+
+        // `execute` leaves the name of the loaded file behind.
+        if let Some(name) = from.and_then(|from| from.file_name()) {
+            mruby.filename(&name.to_string_lossy());
+        }
+
+        match result {
+            Ok(_)                         => mruby.nil(),
+            Err(MrubyError::Runtime(err)) => {
+                let exception = ScriptException::parse(&err);
+
+                let message = match exception.backtrace.first() {
+                    Some(location) => format!("{}: {}", location, exception.message),
+                    None           => exception.message.clone()
+                };
+
+                mruby.array(vec![mruby.string(&exception.class), mruby.string(&message)])
+            },
+            Err(_)                        => {
+                let message = format!("cannot load {}", path);
+
+                mruby.array(vec![mruby.string("LoadError"), mruby.string(&message)])
+            }
+        }
+    });
+}
+
+// Message of the `ArgumentError` for methods defined with `args` which got `count` arguments
+// instead of `expected`.
+pub fn arity(count: usize, expected: usize) -> String {
+    format!("wrong number of arguments ({} for {})", count, expected)
+}
+
+// Returns the arguments of the method being called, like `mrfn!`'s rest arguments. Closures made
+// with `mrfn!` cannot capture their environment.
+pub fn args(mruby: &MrubyType) -> Vec<Value> {
+    let spec = CString::new("*").unwrap();
+
+    unsafe {
+        let mut args: *mut MrValue = ptr::null_mut();
+        let mut count: i32 = 0;
+
+        mrb_get_args(mruby.borrow().mrb, spec.as_ptr(), &mut args as *mut *mut MrValue,
+                     &mut count as *mut i32);
+
+        if count == 0 {
+            return vec![];
+        }
+
+        slice::from_raw_parts(args, count as usize).iter().map(|arg| {
+            Value::new(mruby.clone(), *arg)
+        }).collect()
+    }
+}
+
+// Routes `require` of anything but virtual files, and `require_relative`, through
+// `Anima::Loader`. It runs before any file name is set, so exceptions raised here carry no
+// location of their own and re-raised ones, prefixed with the original location, inspect just
+// like the original exceptions.
+const LOADER_PRELUDE: &'static str = "
+  $LOADED_FEATURES = []
+  $registry_loading = []
+
+  class LoadError < ScriptError; end unless Object.const_defined? :LoadError
+
+  class Anima::Loader
+    def self.require(name)
+      path = resolve name, $registry_loading.last
+
+      raise LoadError, path.first if path.is_a? Array
+
+      return false if $LOADED_FEATURES.include? path
+
+      raise LoadError, \"circular require of #{path}\" if $registry_loading.include? path
+
+      $registry_loading << path
+
+      begin
+        error = load path, $registry_loading[-2]
+      ensure
+        $registry_loading.pop
+      end
+
+      if error
+        exception = Object.const_get(error[0]) rescue RuntimeError
+
+        raise exception, error[1]
+      end
+
+      $LOADED_FEATURES << path
+
+      true
+    end
+  end
+
+  module Kernel
+    alias_method :loader_require, :require
+
+    def require(name)
+      return loader_require name if $registry_virtual.include? name
+
+      Anima::Loader.require name
+    end
+
+    def require_relative(name)
+      relative = name.start_with?('./') || name.start_with?('../') || name.start_with?('/')
+
+      Anima::Loader.require(relative ? name : \"./#{name}\")
+    end
+  end
+";
+
+// Makes `require` run the Ruby code of virtual files. `$registry_files` maps file names to
+// whether they contain Rust types and a `Proc` running their code, or `nil` once required.
+const REQUIRE_PRELUDE: &'static str = "
+  module Kernel
+    alias_method :registry_require, :require
+
+    def require(name)
+      return registry_require name unless $registry_files.key? name
+
+      file = $registry_files[name]
+
+      return false if file.nil?
+
+      $registry_files[name] = nil
+
+      registry_require name if file[0]
+      file[1].call
+
+      true
+    end
+  end
+";
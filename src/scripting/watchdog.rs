@@ -0,0 +1,483 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::{Cell, RefCell};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::sync::atomic::{AtomicPtr, AtomicU32, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mrusty::*;
+
+use super::bundle;
+use super::ffi::{self, Allocf, Irep, State};
+
+/// A `struct` enforcing wall-time budgets on calls into an mruby state.
+///
+/// A call running past its budget raises a `ScriptTimeout` in the script. `ScriptTimeout` does not
+/// inherit from `StandardError`, so a bare `rescue` does not catch it; it can still be rescued by
+/// name. Each call raises at most one, so a script that rescues it and keeps running is only
+/// reported, like the code below.
+///
+/// mruby is built without its instruction hook, so budgets are checked whenever the script
+/// allocates, as well as by a timer thread shared by all watchdogs. The `ScriptTimeout` is raised
+/// where the VM checks for exceptions itself: when the running method or block returns, or a
+/// method written in C or Rust finishes. If a loop does neither, like `while true; end`, the timer
+/// turns the backward jumps of the method or block running it into such checks until the
+/// `ScriptTimeout` is raised. Loops at the top level of a script, in precompiled bytecode, or
+/// waiting on a method written in C or Rust cannot be interrupted; calls still running a second
+/// past their budget are reported on standard error instead.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anima_engine::mrusty::*;
+/// # use anima_engine::scripting::Watchdog;
+/// let mruby = Mruby::new();
+/// let watchdog = Watchdog::new(&mruby);
+///
+/// match watchdog.run("loop { [1] }", Duration::new(0, 10_000_000)) {
+///     Err(MrubyError::Runtime(error)) => assert!(error.starts_with("ScriptTimeout")),
+///     _                               => panic!("loop did not time out")
+/// }
+/// ```
+pub struct Watchdog {
+    mruby: MrubyType,
+    caller: Value,
+    hook: Box<Hook>,
+    shared: Arc<Shared>
+}
+
+// Only used on the thread running mruby.
+struct Hook {
+    allocf: Allocf,
+    allocf_ud: *mut c_void,
+    mruby: *const RefCell<Mruby>,
+    class: *const c_void,
+    deadline: Cell<Option<Instant>>,
+    shared: Arc<Shared>
+}
+
+// Shared with the timer thread.
+struct Shared {
+    mrb: usize,
+    allocations: AtomicUsize,
+    // `ScriptTimeout` of the innermost call, taken by whoever raises it.
+    exception: AtomicPtr<c_void>,
+    calls: Mutex<Vec<Call>>
+}
+
+struct Call {
+    name: String,
+    start: Instant,
+    budget: Duration,
+    reported: bool,
+    exception: usize,
+    allocations: usize,
+    pending_in: usize,
+    patch: Option<Patch>
+}
+
+// Backward jumps of an `Irep` turned into returns, which raise the pending `ScriptTimeout`.
+struct Patch {
+    irep: usize,
+    jumps: Vec<(usize, u32)>,
+    restored: bool
+}
+
+const REPORT_AFTER: u64 = 1;
+
+const TICK: u32 = 5_000_000;
+
+static TIMER: Once = Once::new();
+static WATCHED: Mutex<Vec<Weak<Shared>>> = Mutex::new(Vec::new());
+
+const CALLER: &'static str = "
+  class ScriptTimeout < Exception; end
+
+  $watchdog = Object.new
+
+  def $watchdog.call
+    @call[0].__send__(*@call[1..-1])
+  end
+
+  $watchdog
+";
+
+// Allocating is no place to raise from, since jumping out would leave whatever mruby allocates for
+// half done. The `ScriptTimeout` is only left pending for the VM to raise at its next check.
+extern "C" fn allocf(mrb: *mut State, ptr: *mut c_void, size: usize,
+                     ud: *mut c_void) -> *mut c_void {
+    unsafe {
+        let hook = &*(ud as *const Hook);
+
+        hook.shared.allocations.fetch_add(1, Ordering::Relaxed);
+
+        if size > 0 && (*mrb).exc.is_null() {
+            if let Some(deadline) = hook.deadline.get() {
+                // Rust code calling into mruby through mrusty would take a pending exception for
+                // one raised by its own call, so its allocations are skipped.
+                if Instant::now() >= deadline && (*hook.mruby).try_borrow_mut().is_ok() {
+                    let exception = hook.shared.exception.swap(ptr::null_mut(), Ordering::SeqCst);
+
+                    if !exception.is_null() {
+                        (*mrb).exc = exception;
+                    }
+                }
+            }
+        }
+
+        (hook.allocf)(mrb, ptr, size, hook.allocf_ud)
+    }
+}
+
+impl Watchdog {
+    /// Creates a `Watchdog` for `mruby`, defining the `ScriptTimeout` `Class`. Use a single
+    /// `Watchdog` per state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Watchdog;
+    /// let mruby = Mruby::new();
+    /// let _watchdog = Watchdog::new(&mruby);
+    ///
+    /// assert!(mruby.is_defined("ScriptTimeout"));
+    /// ```
+    pub fn new(mruby: &MrubyType) -> Watchdog {
+        let caller = mruby.run(CALLER).unwrap();
+
+        let class = mruby.get_class("ScriptTimeout").unwrap();
+
+        let shared = Arc::new(Shared {
+            mrb: mruby.borrow().mrb as usize,
+            allocations: AtomicUsize::new(0),
+            exception: AtomicPtr::new(ptr::null_mut()),
+            calls: Mutex::new(vec![])
+        });
+
+        let hook = unsafe {
+            let state = &mut *(mruby.borrow().mrb as *mut State);
+
+            let hook = Box::new(Hook {
+                allocf: state.allocf,
+                allocf_ud: state.allocf_ud,
+                mruby: &**mruby,
+                class: class.class() as *const c_void,
+                deadline: Cell::new(None),
+                shared: shared.clone()
+            });
+
+            state.allocf = allocf;
+            state.allocf_ud = &*hook as *const Hook as *mut c_void;
+
+            hook
+        };
+
+        TIMER.call_once(|| { thread::spawn(time); });
+
+        WATCHED.lock().unwrap().push(Arc::downgrade(&shared));
+
+        Watchdog {
+            mruby: mruby.clone(),
+            caller: caller,
+            hook: hook,
+            shared: shared
+        }
+    }
+
+    /// Calls method `name` on `value` with `args`, raising a `ScriptTimeout` if the call runs
+    /// longer than `budget`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Watchdog;
+    /// let mruby = Mruby::new();
+    /// let watchdog = Watchdog::new(&mruby);
+    ///
+    /// let one = mruby.fixnum(1);
+    /// let two = watchdog.call(&one, "succ", vec![], Duration::new(1, 0)).unwrap();
+    ///
+    /// assert_eq!(two.to_i32().unwrap(), 2);
+    /// ```
+    pub fn call(&self, value: &Value, name: &str, args: Vec<Value>,
+                budget: Duration) -> Result<Value, MrubyError> {
+        let mut call = vec![value.clone(), self.mruby.symbol(name)];
+
+        call.extend(args);
+
+        // `Value::call` keeps the state borrowed while Ruby code runs, which would prevent the
+        // `ScriptTimeout` from being raised, so the call goes through `run` instead.
+        self.caller.set_var("@call", self.mruby.array(call));
+
+        let result = self.watch(name, budget, || self.mruby.run("$watchdog.call"));
+
+        self.caller.set_var("@call", self.mruby.nil());
+
+        result
+    }
+
+    /// Runs `script`, raising a `ScriptTimeout` if it runs longer than `budget`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::Watchdog;
+    /// let mruby = Mruby::new();
+    /// let watchdog = Watchdog::new(&mruby);
+    ///
+    /// let script = "
+    ///   begin
+    ///     loop { [1] }
+    ///   rescue ScriptTimeout
+    ///     'timed out'
+    ///   end
+    /// ";
+    ///
+    /// let result = watchdog.run(script, Duration::new(0, 1_000_000)).unwrap();
+    ///
+    /// assert_eq!(result.to_str().unwrap(), "timed out");
+    /// ```
+    pub fn run(&self, script: &str, budget: Duration) -> Result<Value, MrubyError> {
+        self.watch("script", budget, || self.mruby.run(script))
+    }
+
+    /// Runs the mruby bytecode `code`, e.g. made with `scripting::compile`, raising a
+    /// `ScriptTimeout` if it runs longer than `budget`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use anima_engine::mrusty::*;
+    /// # use anima_engine::scripting::{self, Watchdog};
+    /// let mruby = Mruby::new();
+    /// let watchdog = Watchdog::new(&mruby);
+    ///
+    /// let code = scripting::compile("loop.rb", "loop { [1] }").unwrap();
+    ///
+    /// match watchdog.runb(&code, Duration::new(0, 10_000_000)) {
+    ///     Err(MrubyError::Runtime(error)) => assert!(error.starts_with("ScriptTimeout")),
+    ///     _                               => panic!("loop did not time out")
+    /// }
+    /// ```
+    pub fn runb(&self, code: &[u8], budget: Duration) -> Result<Value, MrubyError> {
+        self.watch("script", budget, || bundle::run(&self.mruby, code))
+    }
+
+    fn watch<F, T>(&self, name: &str, budget: Duration, f: F) -> T where F: FnOnce() -> T {
+        let millis = budget.as_secs() * 1000 + budget.subsec_nanos() as u64 / 1_000_000;
+        let message = format!("{} exceeded its budget of {}ms", name, millis);
+
+        let mrb = self.mruby.borrow().mrb as *mut State;
+
+        // The `ScriptTimeout` is made up front since it cannot be made while allocating.
+        let exception = unsafe {
+            let exception = ffi::mrb_exc_new(mrb, self.hook.class,
+                                             message.as_ptr() as *const c_char, message.len());
+
+            ffi::mrb_gc_register(mrb, exception);
+
+            exception
+        };
+
+        // Nested calls get their own budget, after which the outer one is restored.
+        let outer = self.hook.deadline.get();
+        let outer_exception = self.shared.exception.swap(exception.value as *mut c_void,
+                                                         Ordering::SeqCst);
+
+        self.hook.deadline.set(Some(Instant::now() + budget));
+
+        self.shared.calls.lock().unwrap().push(Call {
+            name: name.to_owned(),
+            start: Instant::now(),
+            budget: budget,
+            reported: false,
+            exception: exception.value as usize,
+            allocations: 0,
+            pending_in: 0,
+            patch: None
+        });
+
+        let result = f();
+
+        let call = self.shared.calls.lock().unwrap().pop().unwrap();
+
+        self.shared.exception.store(outer_exception, Ordering::SeqCst);
+        self.hook.deadline.set(outer);
+
+        unsafe {
+            // The timer might have raised the `ScriptTimeout` as the call was finishing.
+            if (*mrb).exc == exception.value as *mut c_void {
+                (*mrb).exc = ptr::null_mut();
+            }
+
+            if let Some(mut patch) = call.patch {
+                patch.restore();
+
+                ffi::mrb_irep_decref(mrb, patch.irep as *const Irep);
+            }
+
+            ffi::mrb_gc_unregister(mrb, exception);
+        }
+
+        result
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        unsafe {
+            let state = &mut *(self.mruby.borrow().mrb as *mut State);
+
+            state.allocf = self.hook.allocf;
+            state.allocf_ud = self.hook.allocf_ud;
+        }
+    }
+}
+
+impl Shared {
+    // Only the innermost call can be running. Its `Watchdog` cannot be dropped before it ends,
+    // which takes the lock held here.
+    fn check(&self) {
+        let mut calls = self.calls.lock().unwrap();
+
+        let call = match calls.last_mut() {
+            Some(call) => call,
+            None       => return
+        };
+
+        let elapsed = call.start.elapsed();
+
+        if elapsed < call.budget {
+            return;
+        }
+
+        unsafe {
+            self.interrupt(call);
+        }
+
+        if !call.reported && elapsed > call.budget + Duration::new(REPORT_AFTER, 0) {
+            call.reported = true;
+
+            eprintln!("mruby {} has been running for {}s, past its budget, in code which cannot \
+                       be interrupted", call.name, elapsed.as_secs());
+        }
+    }
+
+    // Raises the `ScriptTimeout` of a call which has not allocated since the last check, then
+    // patches the method or block running it if the VM does not reach a check in time either.
+    // Methods written in C or Rust are left alone, like in `allocf`.
+    unsafe fn interrupt(&self, call: &mut Call) {
+        let mrb = self.mrb as *mut State;
+
+        let allocations = self.allocations.load(Ordering::Relaxed);
+        let idle = allocations == call.allocations;
+
+        call.allocations = allocations;
+
+        let ci = (*(*mrb).context).ci;
+        let procedure = (*ci).procedure;
+        let ruby = !procedure.is_null() && (*procedure).header & ffi::PROC_CFUNC == 0;
+
+        if (*mrb).exc as usize == call.exception {
+            // A pending `ScriptTimeout` means the frame running has not returned since.
+            if call.pending_in == ci as usize && ruby && call.patch.is_none() {
+                call.patch = Patch::new((*procedure).irep as *mut Irep);
+            }
+
+            call.pending_in = ci as usize;
+
+            return;
+        }
+
+        call.pending_in = 0;
+
+        if let Some(ref mut patch) = call.patch {
+            patch.restore();
+        }
+
+        if idle && ruby && (*mrb).exc.is_null() {
+            let exception = self.exception.swap(ptr::null_mut(), Ordering::SeqCst);
+
+            if !exception.is_null() {
+                (*mrb).exc = exception;
+                call.pending_in = ci as usize;
+            }
+        }
+    }
+}
+
+impl Patch {
+    // Bytecode not owned by its `Irep` might be constant, so it is never patched.
+    unsafe fn new(irep: *mut Irep) -> Option<Patch> {
+        if (*irep).flags & ffi::ISEQ_NO_FREE != 0 {
+            return None;
+        }
+
+        let mut jumps = vec![];
+
+        for i in 0..(*irep).ilen {
+            let instruction = &*((*irep).iseq.add(i) as *const AtomicU32);
+            let code = instruction.load(Ordering::Relaxed);
+
+            let op = code & 0x7f;
+            let offset = ((code >> 7) & 0xffff) as i32 - ffi::MAXARG_SBX;
+
+            if (op == ffi::OP_JMP || op == ffi::OP_JMPIF || op == ffi::OP_JMPNOT) && offset <= 0 {
+                jumps.push((instruction as *const AtomicU32 as usize, code));
+
+                instruction.store(ffi::OP_RETURN, Ordering::Relaxed);
+            }
+        }
+
+        // Keeps the `Irep` alive until the call ends, even if its method is redefined.
+        (*irep).refcnt += 1;
+
+        Some(Patch {
+            irep: irep as usize,
+            jumps: jumps,
+            restored: false
+        })
+    }
+
+    unsafe fn restore(&mut self) {
+        if self.restored {
+            return;
+        }
+
+        for &(instruction, code) in &self.jumps {
+            (*(instruction as *const AtomicU32)).store(code, Ordering::Relaxed);
+        }
+
+        self.restored = true;
+    }
+}
+
+// Checks the calls of all watchdogs every `TICK` nanoseconds.
+fn time() {
+    loop {
+        thread::sleep(Duration::new(0, TICK));
+
+        let mut watched = WATCHED.lock().unwrap();
+
+        watched.retain(|shared| shared.upgrade().is_some());
+
+        for shared in watched.iter().filter_map(Weak::upgrade) {
+            shared.check();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::rc::Rc;
+use std::str;
+use std::time::Duration;
+
+use self::anima_engine::console::{self, Console};
+use self::anima_engine::math::{Matrix, Quaternion, Vector};
+use self::anima_engine::mrusty::*;
+use self::anima_engine::scripting::{self, Watchdog};
+
+#[test]
+fn test_is_complete() {
+    assert!(console::is_complete(""));
+    assert!(console::is_complete("puts 'end'"));
+    assert!(console::is_complete("x = 1 while false"));
+    assert!(console::is_complete("[1, 2].each do |i|\n  i\nend"));
+    assert!(console::is_complete("while false do\n  1\nend"));
+    assert!(console::is_complete("h = { if: 1, end: 2 }"));
+    assert!(console::is_complete("x.end # comment do"));
+
+    assert!(!console::is_complete("class Foo"));
+    assert!(!console::is_complete("if x"));
+    assert!(!console::is_complete("x = if y\n  1\nelse\n  2"));
+    assert!(!console::is_complete("[1, 2].map { |i|"));
+    assert!(!console::is_complete("'unterminated"));
+    assert!(!console::is_complete("1 +"));
+    assert!(!console::is_complete("foo."));
+}
+
+#[test]
+fn test_pretty() {
+    let mruby = scripting::get_mruby();
+
+    mruby.run("require 'math'").unwrap();
+
+    assert_eq!(console::pretty(&mruby.obj(Quaternion::new(0.0, 0.0, 0.0, 1.0))),
+               "Quaternion(0, 0, 0, 1)");
+    assert_eq!(console::pretty(&mruby.obj(Matrix::ident().trans(Vector::new(10.0, 0.0, 0.0)))),
+               "Matrix\n\
+                |  1   0   0  10 |\n\
+                |  0   1   0   0 |\n\
+                |  0   0   1   0 |\n\
+                |  0   0   0   1 |");
+    assert_eq!(console::pretty(&mruby.run("[1, :a]").unwrap()), "[1, :a]");
+}
+
+#[test]
+fn test_eval() {
+    let mruby = scripting::get_mruby();
+    let mut console = Console::new();
+
+    assert_eq!(console.eval(&mruby, "require 'math'"), "=> true");
+    assert_eq!(console.eval(&mruby, "Vector.one"), "=> Vector(1, 1, 1)");
+    assert_eq!(console.eval(&mruby, "undefined"),
+               "NoMethodError: undefined method 'undefined' for main\n    from console:1");
+    assert_eq!(console.eval(&mruby, "!2"), "=> Vector(1, 1, 1)");
+    assert_eq!(console.eval(&mruby, "!9"), "No history entry 9.");
+    assert_eq!(console.history().len(), 4);
+}
+
+#[test]
+fn test_eval_keeps_filename() {
+    let mruby = Mruby::new();
+    let mut console = Console::new();
+
+    mruby.filename("game.rb");
+
+    assert_eq!(console.eval(&mruby, "1"), "=> 1");
+
+    match mruby.run("undefined") {
+        Err(MrubyError::Runtime(error)) => assert!(error.contains("game.rb:1")),
+        _                               => panic!("undefined did not raise")
+    }
+}
+
+#[test]
+fn test_eval_syntax_error() {
+    let mruby = Mruby::new();
+    let mut console = Console::new();
+
+    assert_eq!(console.eval(&mruby, "1 +"), "SyntaxError: line 1: syntax error, unexpected $end");
+    assert_eq!(console.history().len(), 1);
+}
+
+#[test]
+fn test_eval_budget() {
+    let mruby = Mruby::new();
+    let mut console = Console::new();
+
+    let target = mruby.run("Object.new").unwrap();
+
+    console.budget(Rc::new(Watchdog::new(&mruby)), Duration::new(0, 10_000_000));
+
+    let output = console.eval_in(&mruby, &target, "loop { [1] }");
+
+    assert!(output.starts_with("ScriptTimeout: script exceeded its budget of 10ms"));
+    assert_eq!(console.eval(&mruby, "1 + 2"), "=> 3");
+}
+
+#[test]
+fn test_tcp_session() {
+    let mruby = Mruby::new();
+    let mut console = Console::new();
+
+    let address = console.listen_tcp("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(address).unwrap();
+
+    stream.set_read_timeout(Some(Duration::new(0, 10_000_000))).unwrap();
+    stream.write_all(b"def double(x)\n  x * 2\nend\ndouble 21\n").unwrap();
+
+    let mut output = String::new();
+
+    while !output.ends_with("=> 42\nanima> ") {
+        console.poll(&mruby);
+
+        let mut buffer = [0; 256];
+
+        if let Ok(n) = stream.read(&mut buffer) {
+            output.push_str(str::from_utf8(&buffer[..n]).unwrap());
+        }
+    }
+
+    assert_eq!(output, "anima> anima* anima* => :double\nanima> => 42\nanima> ");
+}
@@ -0,0 +1,439 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use self::anima_engine::game::{Clock, FrameRate, Game, GameLoop, ManualClock, Profiler,
+                               SharedGame, Timestep};
+
+struct Recorder {
+    updates: Vec<Duration>,
+    alphas: Vec<f32>,
+    events: Vec<&'static str>,
+    limit: usize
+}
+
+impl Recorder {
+    fn new(limit: usize) -> Recorder {
+        Recorder {
+            updates: vec![],
+            alphas: vec![],
+            events: vec![],
+            limit: limit
+        }
+    }
+}
+
+impl Game for Recorder {
+    fn init(&mut self) {
+        self.events.push("init");
+    }
+
+    fn update(&mut self, dt: Duration) -> bool {
+        self.updates.push(dt);
+
+        self.updates.len() < self.limit
+    }
+
+    fn draw(&mut self, alpha: f32) {
+        self.alphas.push(alpha);
+    }
+
+    fn pause(&mut self) {
+        self.events.push("pause");
+    }
+
+    fn resume(&mut self) {
+        self.events.push("resume");
+    }
+
+    fn shutdown(&mut self) {
+        self.events.push("shutdown");
+    }
+}
+
+#[test]
+fn test_variable_draw() {
+    let mut game_loop = GameLoop::new(Recorder::new(3));
+
+    game_loop.run();
+
+    assert_eq!(game_loop.game.updates.len(), 3);
+    assert_eq!(game_loop.game.alphas, vec![1.0, 1.0]);
+}
+
+#[test]
+fn test_fixed_step() {
+    let step = Duration::new(0, 100_000);
+    let mut game_loop = GameLoop::new_fixed(Recorder::new(10), step, 4);
+
+    game_loop.run();
+
+    assert!(game_loop.game.updates.iter().all(|dt| *dt == step));
+
+    for alpha in game_loop.game.alphas.iter() {
+        assert!(*alpha >= 0.0 && *alpha < 1.0);
+    }
+}
+
+#[test]
+fn test_fixed_max_steps() {
+    struct Slow {
+        frames: u32,
+        updates: u32
+    }
+
+    impl Game for Slow {
+        fn update(&mut self, _: Duration) -> bool {
+            self.updates += 1;
+
+            self.frames < 5
+        }
+
+        fn draw(&mut self, _: f32) {
+            self.frames += 1;
+
+            // Every frame takes far longer than the step.
+            ::std::thread::sleep(Duration::new(0, 2_000_000));
+        }
+    }
+
+    let game = Slow { frames: 0, updates: 0 };
+    let mut game_loop = GameLoop::new_fixed(game, Duration::new(0, 1_000), 2);
+
+    game_loop.run();
+
+    assert!(game_loop.game.updates <= (game_loop.game.frames + 1) * 2);
+}
+
+#[test]
+fn test_manual_step() {
+    let tick = Duration::new(0, 1_000_000);
+    let mut game_loop = GameLoop::new_clock(Recorder::new(20_000), ManualClock::new(tick));
+
+    assert!(game_loop.step(10_000));
+
+    assert_eq!(game_loop.game.updates.len(), 10_000);
+    assert!(game_loop.game.updates.iter().all(|dt| *dt == tick));
+    assert_eq!(game_loop.clock.now(), Duration::new(10, 0));
+}
+
+#[test]
+fn test_manual_stop() {
+    let clock = ManualClock::new(Duration::new(1, 0));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(5), clock);
+
+    assert!(!game_loop.step(10));
+    assert_eq!(game_loop.game.updates.len(), 5);
+}
+
+#[test]
+fn test_manual_run_for_fixed() {
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000),
+                                            ManualClock::new(Duration::new(0, 25_000_000)));
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 10_000_000), max_steps: 5 });
+
+    assert!(game_loop.run_for(Duration::new(1, 0)));
+
+    assert_eq!(game_loop.game.updates.len(), 100);
+    assert_eq!(game_loop.game.alphas.len(), 40);
+    assert_eq!(game_loop.game.alphas[0], 0.5);
+    assert_eq!(game_loop.game.alphas[1], 0.0);
+}
+
+#[test]
+fn test_lifecycle() {
+    let clock = ManualClock::new(Duration::new(1, 0));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(3), clock);
+
+    assert!(game_loop.step(2));
+    assert_eq!(game_loop.game.events, vec!["init"]);
+
+    assert!(!game_loop.step(2));
+    assert!(!game_loop.step(2));
+
+    let game = game_loop.into_game();
+
+    assert_eq!(game.updates.len(), 3);
+    assert_eq!(game.events, vec!["init", "shutdown"]);
+}
+
+#[test]
+fn test_stop() {
+    let clock = ManualClock::new(Duration::new(1, 0));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(3), clock);
+
+    game_loop.stop();
+
+    assert!(!game_loop.step(1));
+    assert!(game_loop.game.events.is_empty());
+}
+
+#[test]
+fn test_shared_game() {
+    struct Counter {
+        count: Cell<u32>
+    }
+
+    impl SharedGame for Counter {
+        fn update(&self, _: Duration) -> bool {
+            self.count.set(self.count.get() + 1);
+
+            self.count.get() < 4
+        }
+    }
+
+    let mut game_loop = GameLoop::new(Counter { count: Cell::new(0) });
+
+    game_loop.run();
+
+    assert_eq!(game_loop.into_game().count.get(), 4);
+}
+
+#[test]
+fn test_manual_frame_rate() {
+    let clock = ManualClock::new(Duration::new(0, 1_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.frame_rate = FrameRate::Capped(100);
+
+    assert!(game_loop.run_for(Duration::new(1, 0)));
+
+    // The first frame is not paced.
+    assert_eq!(game_loop.game.updates.len(), 101);
+    assert_eq!(game_loop.game.updates[0], Duration::new(0, 1_000_000));
+    assert!(game_loop.game.updates[1..].iter().all(|dt| *dt == Duration::new(0, 10_000_000)));
+}
+
+#[test]
+fn test_manual_frame_rate_slow_frames() {
+    let clock = ManualClock::new(Duration::new(0, 20_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.frame_rate = FrameRate::Capped(100);
+
+    assert!(game_loop.step(10));
+
+    assert!(game_loop.game.updates.iter().all(|dt| *dt == Duration::new(0, 20_000_000)));
+}
+
+#[test]
+fn test_zero_frame_rate() {
+    let clock = ManualClock::new(Duration::new(0, 1_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.frame_rate = FrameRate::Capped(0);
+
+    assert!(game_loop.step(10));
+
+    assert!(game_loop.game.updates.iter().all(|dt| *dt == Duration::new(0, 1_000_000)));
+}
+
+#[test]
+fn test_system_frame_rate() {
+    let mut game_loop = GameLoop::new(Recorder::new(1_000));
+
+    game_loop.frame_rate = FrameRate::Capped(100);
+
+    assert!(game_loop.run_for(Duration::new(0, 100_000_000)));
+
+    let frames = game_loop.game.updates.len();
+
+    assert!((9..=12).contains(&frames));
+    assert!(game_loop.game.updates[1..].iter().all(|dt| *dt >= Duration::new(0, 10_000_000)));
+}
+
+#[test]
+fn test_time_scale_and_pause() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.time.set_scale(0.5);
+
+    assert!(game_loop.step(2));
+
+    game_loop.time.pause();
+
+    assert!(game_loop.step(2));
+
+    game_loop.time.step();
+
+    assert!(game_loop.step(2));
+
+    assert_eq!(game_loop.game.updates, vec![
+        Duration::new(0, 5_000_000),
+        Duration::new(0, 5_000_000),
+        Duration::new(0, 0),
+        Duration::new(0, 0),
+        Duration::new(0, 5_000_000),
+        Duration::new(0, 0)
+    ]);
+    assert_eq!(game_loop.time.real_time(), Duration::new(0, 60_000_000));
+}
+
+#[test]
+fn test_fixed_pause_step() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 1_000_000), max_steps: 100 });
+    game_loop.time.pause();
+
+    assert!(game_loop.step(3));
+    assert!(game_loop.game.updates.is_empty());
+
+    game_loop.time.step();
+
+    assert!(game_loop.step(3));
+    assert_eq!(game_loop.game.updates.len(), 1);
+    assert_eq!(game_loop.game.alphas.len(), 6);
+}
+
+#[test]
+fn test_pause_resume() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    assert!(game_loop.step(1));
+
+    game_loop.time.pause();
+    game_loop.time.step();
+
+    assert!(game_loop.step(2));
+
+    game_loop.time.resume();
+
+    assert!(game_loop.step(2));
+    assert_eq!(game_loop.game.events, vec!["init", "pause", "resume"]);
+}
+
+#[test]
+fn test_fixed_update() {
+    struct Fixed {
+        updates: u32,
+        fixed_updates: u32
+    }
+
+    impl Game for Fixed {
+        fn update(&mut self, _dt: Duration) -> bool {
+            self.updates += 1;
+
+            true
+        }
+
+        fn fixed_update(&mut self, _step: Duration) -> bool {
+            self.fixed_updates += 1;
+
+            true
+        }
+    }
+
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Fixed { updates: 0, fixed_updates: 0 }, clock);
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 5_000_000), max_steps: 10 });
+
+    assert!(game_loop.step(3));
+    assert_eq!(game_loop.game.updates, 0);
+    assert_eq!(game_loop.game.fixed_updates, 6);
+}
+
+#[test]
+#[should_panic(expected = "Fixed timestep cannot be zero.")]
+fn test_zero_fixed_step() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 0), max_steps: 5 });
+}
+
+#[test]
+fn test_stats() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    assert!(game_loop.step(10));
+
+    game_loop.clock.advance(Duration::new(0, 40_000_000));
+
+    assert!(game_loop.step(1));
+
+    let stats = &game_loop.stats;
+
+    assert_eq!(stats.frames(), 11);
+    assert_eq!(stats.long_frames(), 1);
+    assert_eq!(stats.min(), Duration::new(0, 10_000_000));
+    assert_eq!(stats.max(), Duration::new(0, 50_000_000));
+    assert_eq!(stats.avg(), Duration::new(0, 13_636_363));
+    assert_eq!(stats.percentile(90.0), Duration::new(0, 10_000_000));
+    assert_eq!(stats.last(), Some(Duration::new(0, 50_000_000)));
+}
+
+#[test]
+fn test_stats_dropped_steps() {
+    let clock = ManualClock::new(Duration::new(0, 10_000_000));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 1_000_000), max_steps: 4 });
+
+    assert!(game_loop.step(3));
+
+    assert_eq!(game_loop.game.updates.len(), 12);
+    assert_eq!(game_loop.stats.dropped_steps(), 18);
+}
+
+#[test]
+fn test_stats_scopes() {
+    struct Profiled {
+        profiler: Profiler
+    }
+
+    impl Game for Profiled {
+        fn attach_profiler(&mut self, profiler: &Profiler) {
+            self.profiler = profiler.clone();
+        }
+
+        fn update(&mut self, _: Duration) -> bool {
+            for _ in 0..3 {
+                let _scope = self.profiler.scope("physics");
+            }
+
+            true
+        }
+    }
+
+    let game = Profiled { profiler: Profiler::new() };
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(2));
+
+    let names: Vec<_> = game_loop.stats.scopes().iter().map(|scope| &scope.name[..]).collect();
+
+    assert_eq!(names, vec!["physics", "update", "draw"]);
+    assert_eq!(game_loop.stats.scopes()[0].calls, 3);
+}
+
+#[test]
+fn test_on_frame() {
+    let frames = Rc::new(Cell::new(0));
+    let counter = frames.clone();
+
+    let clock = ManualClock::new(Duration::new(0, 1));
+    let mut game_loop = GameLoop::new_clock(Recorder::new(1_000), clock);
+
+    game_loop.on_frame(move |stats| {
+        counter.set(stats.frames());
+    });
+
+    assert!(game_loop.step(5));
+    assert_eq!(frames.get(), 5);
+}
@@ -0,0 +1,576 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::cell::Cell;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str;
+use std::time::Duration;
+
+use self::anima_engine::console::Console;
+use self::anima_engine::game::{ErrorAction, Game, GameLoop, ManualClock, MrubyGame,
+                               MrubyGameError, Profiler, Timestep};
+use self::anima_engine::mrusty::MrubyImpl;
+use self::anima_engine::scripting::{Bundle, BundleError, Loader, Registry};
+
+fn script(name: &str, source: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("anima_{}.rb", name));
+
+    File::create(&path).unwrap().write_all(source.as_bytes()).unwrap();
+
+    path
+}
+
+#[test]
+fn test_missing_file() {
+    let error = MrubyGame::new(&env::temp_dir().join("anima_missing.rb")).err().unwrap();
+
+    match error {
+        MrubyGameError::Load(_) => (),
+        error                   => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_syntax_error() {
+    let path = script("syntax_error", "class Game\n  def update(dt\nend\n");
+
+    match MrubyGame::new(&path).err().unwrap() {
+        MrubyGameError::Syntax(exception) => assert_eq!(exception.class, "SyntaxError"),
+        error                             => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_missing_game() {
+    let path = script("missing_game", "class Gmae\nend\n");
+
+    assert_eq!(MrubyGame::new(&path).err().unwrap(), MrubyGameError::MissingGame);
+}
+
+#[test]
+fn test_registry() {
+    let path = script("registry", "
+require 'rules'
+
+class Game
+  def update(dt)
+    RULES
+  end
+end
+");
+
+    let registry = Registry::new().def_file_code("rules", "RULES = false");
+
+    assert!(MrubyGame::new_registry(&path, Registry::new()).is_err());
+
+    let game = MrubyGame::new_registry(&path, registry).unwrap();
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(!game_loop.step(3));
+    assert!(game_loop.game.last_error().is_none());
+}
+
+#[test]
+fn test_bundle() {
+    let root = env::temp_dir().join("anima_bundle_game");
+
+    fs::create_dir_all(&root).unwrap();
+
+    File::create(root.join("speed.rb")).unwrap().write_all(b"SPEED = 2").unwrap();
+    File::create(root.join("game.rb")).unwrap().write_all(b"
+require 'speed'
+
+class Game
+  def update(dt)
+    @frames = (@frames || 0) + SPEED
+
+    @frames < 10
+  end
+end
+").unwrap();
+
+    let bundle = MrubyGame::new(&root.join("game.rb")).unwrap().compile().unwrap();
+    let mut data = vec![];
+
+    bundle.write(&mut data).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    let game = MrubyGame::new_bundle(Bundle::read(&mut &data[..]).unwrap()).unwrap();
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(!game_loop.step(10));
+    assert!(game_loop.game.last_error().is_none());
+
+    let frames = game_loop.game.game.get_var("@frames").unwrap();
+
+    assert_eq!(frames.to_i32().unwrap(), 10);
+}
+
+#[test]
+fn test_bundle_missing_script() {
+    let path = script("bundle_missing_script", "class Game; end");
+    let bundle = MrubyGame::new(&path).unwrap().compile().unwrap();
+    let missing = path.with_file_name("anima_missing.rb");
+
+    let registry = Registry::new_engine().loader(Loader::new_bundle(bundle));
+
+    match MrubyGame::new_registry(&missing, registry) {
+        Err(MrubyGameError::Bundle(BundleError::Missing(script))) => {
+            assert_eq!(script, missing.to_string_lossy());
+        },
+        _ => panic!("missing script was run")
+    }
+}
+
+#[test]
+fn test_exception_stops() {
+    let path = script("exception_stops", "
+class Game
+  def update(dt)
+    undefined_method
+  end
+end
+");
+
+    let game = MrubyGame::new(&path).unwrap();
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(!game_loop.step(10));
+
+    match game_loop.game.last_error() {
+        Some(&MrubyGameError::Exception(ref exception)) => {
+            assert_eq!(exception.class, "NoMethodError");
+            assert_eq!(exception.backtrace[0], "anima_exception_stops.rb:4");
+        },
+        error => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_budget() {
+    let path = script("budget", "
+class Game
+  def update(dt)
+    frames = [dt].dup while true
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.budget(Duration::new(0, 10_000_000));
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(!game_loop.step(10));
+
+    match game_loop.game.last_error() {
+        Some(&MrubyGameError::Timeout(ref exception)) => {
+            assert_eq!(exception.class, "ScriptTimeout");
+            assert_eq!(exception.backtrace, vec!["anima_budget.rb:4:in Game.update"]);
+        },
+        error => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_return_value_ignored() {
+    let path = script("return_value_ignored", "
+class Game
+  def update(dt)
+    dt > 0 ? 'running' : nil
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.on_error(|error| panic!("unexpected {:?}", error));
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(3));
+}
+
+#[test]
+fn test_strict_returns() {
+    let path = script("strict_returns", "
+class Game
+  def update(dt)
+    @running ? 'running' : nil
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.strict_returns(true);
+    game.on_error(|_| ErrorAction::Continue);
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(1));
+    assert!(game_loop.game.last_error().is_none());
+
+    game_loop.game.game.set_var("@running", game_loop.game.mruby.bool(true));
+
+    assert!(game_loop.step(1));
+    assert_eq!(game_loop.game.last_error(),
+               Some(&MrubyGameError::ReturnType("\"running\"".to_owned())));
+}
+
+#[test]
+fn test_lifecycle() {
+    let path = script("lifecycle", "
+class Game
+  def initialize
+    @log = []
+  end
+
+  def init
+    @log << :init
+  end
+
+  def update(dt)
+    @log << :update
+  end
+
+  def draw(alpha)
+    @log << :draw
+
+    quit if @log.count(:draw) == 3
+  end
+
+  def on_pause
+    @log << :on_pause
+  end
+
+  def on_resume
+    @log << :on_resume
+  end
+
+  def shutdown
+    @log << :shutdown
+  end
+end
+");
+
+    let game = MrubyGame::new(&path).unwrap();
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(1));
+
+    game_loop.time.pause();
+
+    assert!(game_loop.step(1));
+
+    game_loop.time.resume();
+
+    assert!(game_loop.step(1));
+    assert!(!game_loop.step(1));
+
+    let log = game_loop.game.game.get_var("@log").unwrap();
+    let log: Vec<_> = log.to_vec().unwrap().iter().map(|event| {
+        event.call("to_s", vec![]).unwrap().to_str().unwrap().to_owned()
+    }).collect();
+
+    assert_eq!(log, vec!["init", "update", "draw", "on_pause", "update", "draw", "on_resume",
+                         "update", "draw", "shutdown"]);
+}
+
+#[test]
+fn test_fixed_update() {
+    let path = script("fixed_update", "
+class Game
+  def initialize
+    @steps = []
+  end
+
+  def fixed_update(step)
+    @steps << step
+  end
+end
+");
+
+    let game = MrubyGame::new(&path).unwrap();
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 10_000_000)));
+
+    game_loop.set_timestep(Timestep::Fixed { step: Duration::new(0, 5_000_000), max_steps: 10 });
+
+    assert!(game_loop.step(2));
+
+    let steps = game_loop.game.game.get_var("@steps").unwrap().to_vec().unwrap();
+
+    assert_eq!(steps.len(), 4);
+    assert_eq!(steps[0].to_f64().unwrap(), 0.005);
+}
+
+#[test]
+fn test_event() {
+    let path = script("event", "
+class Game
+  def update(dt)
+  end
+
+  def on_event(event)
+    quit if event[:type] == :close
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    let key = game.mruby.run("{ type: :key_down }").unwrap();
+    game.event(key);
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(2));
+
+    let close = game_loop.game.mruby.run("{ type: :close }").unwrap();
+    game_loop.game.event(close);
+
+    assert!(!game_loop.step(1));
+    assert!(game_loop.game.last_error().is_none());
+}
+
+#[test]
+fn test_exception_pause() {
+    let path = script("exception_pause", "
+class Game
+  def update(dt)
+    raise 'boom' if dt > 0
+
+    true
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.on_error(|_| ErrorAction::Pause);
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(3));
+    assert!(game_loop.time.is_paused());
+}
+
+#[test]
+fn test_profiler_error() {
+    let path = script("profiler_error", "
+Profiler = 1
+
+class Game
+  def update(dt); end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.on_error(|_| ErrorAction::Continue);
+    game.attach_profiler(&Profiler::new());
+
+    match game.last_error() {
+        Some(&MrubyGameError::Exception(ref exception)) => {
+            assert_eq!(exception.class, "TypeError");
+        },
+        error => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_reload() {
+    let path = script("reload", "
+class Game
+  def initialize
+    @frames = 0
+  end
+
+  def update(dt)
+    @frames += 1
+
+    true
+  end
+
+  def value
+    @frames * 1
+  end
+end
+");
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.watch(Duration::new(0, 0));
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(2));
+
+    script("reload", "
+class Game
+  def value
+    @frames * 10
+  end
+
+  def reloaded
+    @frames += 100
+  end
+end
+");
+
+    assert!(game_loop.step(1));
+
+    let value = game_loop.game.game.call("value", vec![]).unwrap();
+
+    assert_eq!(value.to_i32().unwrap(), 1030);
+}
+
+#[test]
+fn test_reload_error_keeps_version() {
+    let path = script("reload_error", "
+class Game
+  def update(dt)
+    true
+  end
+
+  def value
+    1
+  end
+end
+");
+
+    let errors = Rc::new(Cell::new(0));
+    let counter = errors.clone();
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.watch(Duration::new(0, 0));
+    game.on_reload(move |result| {
+        match result.err().unwrap() {
+            &MrubyGameError::Exception(ref exception) => assert_eq!(exception.message, "oops"),
+            error                                    => panic!("unexpected {:?}", error)
+        }
+
+        counter.set(counter.get() + 1);
+    });
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+
+    assert!(game_loop.step(1));
+
+    script("reload_error", "
+class Game
+  def value
+    2
+  end
+end
+
+raise 'oops'
+");
+
+    assert!(game_loop.step(3));
+
+    let value = game_loop.game.game.call("value", vec![]).unwrap();
+
+    assert_eq!(value.to_i32().unwrap(), 1);
+    assert_eq!(errors.get(), 1);
+}
+
+#[test]
+fn test_reload_required() {
+    let library = script("reload_library", "
+class Library
+  def self.value
+    1
+  end
+end
+");
+
+    let library = library.to_str().unwrap();
+    let path = script("reload_required", &format!("
+require '{}'
+
+class Game
+  def update(dt)
+    true
+  end
+end
+", &library[..library.len() - 3]));
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    assert!(!game.reload().unwrap());
+
+    script("reload_library", "
+class Library
+  def self.value
+    20
+  end
+end
+");
+
+    assert!(game.reload().unwrap());
+
+    let value = game.mruby.run("Library.value").unwrap();
+
+    assert_eq!(value.to_i32().unwrap(), 20);
+}
+
+#[test]
+fn test_console() {
+    let path = script("console", "
+class Game
+  def initialize
+    @frames = 0
+  end
+
+  def update(dt)
+    @frames += 1
+
+    true
+  end
+end
+");
+
+    let console = Console::new();
+    let address = console.listen_tcp("127.0.0.1:0").unwrap();
+
+    let mut game = MrubyGame::new(&path).unwrap();
+
+    game.attach_console(console);
+
+    let mut game_loop = GameLoop::new_clock(game, ManualClock::new(Duration::new(0, 1)));
+    let mut stream = TcpStream::connect(address).unwrap();
+
+    stream.set_read_timeout(Some(Duration::new(0, 10_000_000))).unwrap();
+    stream.write_all(b"@frames += 10\n").unwrap();
+
+    let mut output = String::new();
+
+    while !output.ends_with("\nanima> ") {
+        assert!(game_loop.step(1));
+
+        let mut buffer = [0; 256];
+
+        if let Ok(n) = stream.read(&mut buffer) {
+            output.push_str(str::from_utf8(&buffer[..n]).unwrap());
+        }
+    }
+
+    let frames = game_loop.game.game.get_var("@frames").unwrap().to_i32().unwrap() as u32;
+
+    assert!(output.starts_with("anima> => "));
+    assert_eq!(frames, game_loop.stats.frames() as u32 + 10);
+}
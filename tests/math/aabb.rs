@@ -0,0 +1,84 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        #[test]
+        fn test_new() {
+            let b = Aabb::new(Vector::one(), Vector::new(-1.0, 2.0, 0.0));
+
+            assert_eq!(b.min, Vector::new(-1.0, 1.0, 0.0));
+            assert_eq!(b.max, Vector::new(1.0, 2.0, 1.0));
+
+            let points = [Vector::zero(), Vector::new(1.0, -2.0, 0.5), Vector::forward()];
+
+            assert_eq!(Aabb::new_points(&points),
+                       Aabb::new(Vector::new(0.0, -2.0, 0.0), Vector::new(1.0, 0.0, 1.0)));
+        }
+
+        #[test]
+        #[should_panic(expected = "Cannot bound an empty set of points.")]
+        fn test_new_points_empty_panic() {
+            Aabb::new_points(&[]);
+        }
+
+        #[test]
+        fn test_contains() {
+            let b = Aabb::new(Vector::zero(), Vector::one());
+
+            assert!(b.contains(Vector::new_unf(0.5)));
+            assert!(b.contains(Vector::one()));
+            assert!(!b.contains(Vector::new(0.5, 1.5, 0.5)));
+            assert_eq!(b.closest_point(Vector::new(0.5, 1.5, -1.0)), Vector::new(0.5, 1.0, 0.0));
+        }
+
+        #[test]
+        fn test_hit_aabb() {
+            let b1 = Aabb::new(Vector::zero(), Vector::one());
+            let b2 = Aabb::new(Vector::new(0.75, 0.0, 0.0), Vector::new(2.0, 1.0, 1.0));
+            let b3 = Aabb::new(Vector::new(1.0, 0.0, 0.0), Vector::new(2.0, 1.0, 1.0));
+
+            assert_eq!(b1.hit_aabb(b2), Some(Hit::new(0.25, Vector::right())));
+            assert_eq!(b2.hit_aabb(b1), Some(Hit::new(0.25, Vector::left())));
+            assert_eq!(b1.hit_aabb(b3), None);
+        }
+
+        #[test]
+        fn test_hit_plane() {
+            let b = Aabb::new(Vector::zero(), Vector::one());
+
+            assert_eq!(b.hit_plane(Plane::new(Vector::up(), 0.75)),
+                       Some(Hit::new(0.25, Vector::down())));
+            assert_eq!(b.hit_plane(Plane::new(Vector::up(), 2.0)), None);
+        }
+
+        #[test]
+        fn test_transform() {
+            let m = Matrix::ident().rot(Quaternion::new_rot(Vector::up(), 1.0));
+            let b = m * Aabb::new(Vector::new_unf(-1.0), Vector::one());
+
+            assert!(b.contains(Vector::new(1.0, 1.0, 1.0)));
+            assert!(b.max.x > 1.0);
+            assert_eq!(b.max.y, 1.0);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Aabb, Hit, Matrix, Plane, Quaternion, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DAabb as Aabb, DHit as Hit, DMatrix as Matrix};
+    use super::anima_engine::math::{DPlane as Plane, DQuaternion as Quaternion, DVector as Vector};
+
+    suite!(f64);
+}
@@ -0,0 +1,170 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
+
+        const EPSILON: $float = 0.0001;
+
+        fn persp(depth: DepthRange) -> Frustum {
+            let m = Matrix::new_persp(consts::PI / 2.0, 2.0, 1.0, 10.0, depth);
+
+            Frustum::from_matrix(m, depth)
+        }
+
+        fn assert_planes(f: Frustum, near: $float, far: Option<$float>) {
+            let sqrt = consts::FRAC_1_SQRT_2;
+
+            let sides = [
+                Vector::new(1.0 / 5.0f64.sqrt() as $float, 0.0, -2.0 / 5.0f64.sqrt() as $float),
+                Vector::new(-1.0 / 5.0f64.sqrt() as $float, 0.0, -2.0 / 5.0f64.sqrt() as $float),
+                Vector::new(0.0, sqrt, -sqrt),
+                Vector::new(0.0, -sqrt, -sqrt)
+            ];
+
+            for (plane, normal) in f.planes.iter().zip(sides.iter()) {
+                assert!(plane.normal.dist(*normal) < EPSILON);
+                assert!(plane.distance.abs() < EPSILON);
+            }
+
+            assert!(f.planes[4].normal.dist(Vector::back()) < EPSILON);
+            assert!((f.planes[4].distance - near).abs() < EPSILON);
+
+            match far {
+                Some(far) => {
+                    assert!(f.planes[5].normal.dist(Vector::forward()) < EPSILON);
+                    assert!((f.planes[5].distance - far).abs() < EPSILON);
+                }
+                None => {
+                    assert_eq!(f.planes[5].normal, Vector::zero());
+                    assert_eq!(f.classify_point(Vector::new(0.0, 0.0, -1000000.0)),
+                               Containment::Inside);
+                }
+            }
+        }
+
+        #[test]
+        fn test_from_matrix() {
+            assert_planes(persp(DepthRange::OpenGl), 1.0, Some(-10.0));
+            assert_planes(persp(DepthRange::Vulkan), 1.0, Some(-10.0));
+        }
+
+        #[test]
+        fn test_from_matrix_rev() {
+            let m = Matrix::new_persp_rev(consts::PI / 2.0, 2.0, 1.0, 10.0, DepthRange::Vulkan);
+            let f = Frustum::from_matrix(m, DepthRange::Vulkan);
+
+            // The planes at the start and end of the depth range are swapped.
+            let planes = [f.planes[0], f.planes[1], f.planes[2], f.planes[3], f.planes[5],
+                          f.planes[4]];
+
+            assert_planes(Frustum::new(planes), 1.0, Some(-10.0));
+        }
+
+        #[test]
+        fn test_from_matrix_inf() {
+            let m = Matrix::new_persp_inf(consts::PI / 2.0, 2.0, 1.0, DepthRange::OpenGl);
+
+            assert_planes(Frustum::from_matrix(m, DepthRange::OpenGl), 1.0, None);
+
+            let m = Matrix::new_persp_inf(consts::PI / 2.0, 2.0, 1.0, DepthRange::Vulkan);
+
+            assert_planes(Frustum::from_matrix(m, DepthRange::Vulkan), 1.0, None);
+        }
+
+        #[test]
+        fn test_view_proj() {
+            let proj = Matrix::new_persp(consts::PI / 2.0, 1.0, 1.0, 10.0, DepthRange::OpenGl);
+            let view = Matrix::new_look_at(Vector::new(5.0, 0.0, 0.0), Vector::zero(),
+                                           Vector::up());
+
+            let f = Frustum::from_matrix(proj * view, DepthRange::OpenGl);
+
+            assert_eq!(f.classify_point(Vector::zero()), Containment::Inside);
+            assert_eq!(f.classify_point(Vector::new(0.0, 6.0, 0.0)), Containment::Outside);
+            assert_eq!(f.classify_point(Vector::new(4.5, 0.0, 0.0)), Containment::Outside);
+            assert_eq!(f.classify_point(Vector::new(-6.0, 0.0, 0.0)), Containment::Outside);
+        }
+
+        #[test]
+        fn test_classify_sphere() {
+            let f = persp(DepthRange::OpenGl);
+
+            let inside = Sphere::new(Vector::new(0.0, 0.0, -5.0), 1.0);
+            let near = Sphere::new(Vector::new(0.0, 0.0, -1.0), 0.5);
+            let behind = Sphere::new(Vector::new(0.0, 0.0, 1.0), 0.5);
+            let side = Sphere::new(Vector::new(0.0, 6.0, -5.0), 0.5);
+
+            assert_eq!(f.classify_sphere(inside), Containment::Inside);
+            assert_eq!(f.classify_sphere(near), Containment::Intersecting);
+            assert_eq!(f.classify_sphere(behind), Containment::Outside);
+            assert_eq!(f.classify_sphere(side), Containment::Outside);
+        }
+
+        #[test]
+        fn test_classify_aabb() {
+            let f = persp(DepthRange::Vulkan);
+
+            let inside = Aabb::new(Vector::new(-1.0, -1.0, -6.0), Vector::new(1.0, 1.0, -4.0));
+            let far = Aabb::new(Vector::new(-1.0, -1.0, -11.0), Vector::new(1.0, 1.0, -9.0));
+            let side = Aabb::new(Vector::new(15.0, -1.0, -6.0), Vector::new(16.0, 1.0, -4.0));
+
+            assert_eq!(f.classify_aabb(inside), Containment::Inside);
+            assert_eq!(f.classify_aabb(far), Containment::Intersecting);
+            assert_eq!(f.classify_aabb(side), Containment::Outside);
+        }
+
+        #[test]
+        fn test_cull() {
+            let f = persp(DepthRange::OpenGl);
+
+            let mut spheres = vec![];
+            let mut aabbs = vec![];
+
+            for x in -10..11 {
+                for z in -12..3 {
+                    let center = Vector::new(x as $float, 1.0, z as $float);
+
+                    spheres.push(Sphere::new(center, 0.5));
+                    aabbs.push(Aabb::new(center - Vector::new_unf(0.5),
+                                         center + Vector::new_unf(0.5)));
+                }
+            }
+
+            let visible_spheres: Vec<usize> = (0..spheres.len()).filter(|&i| {
+                f.classify_sphere(spheres[i]) != Containment::Outside
+            }).collect();
+            let visible_aabbs: Vec<usize> = (0..aabbs.len()).filter(|&i| {
+                f.classify_aabb(aabbs[i]) != Containment::Outside
+            }).collect();
+
+            assert!(!visible_spheres.is_empty());
+            assert!(visible_spheres.len() < spheres.len());
+
+            assert_eq!(f.cull_spheres(&spheres), visible_spheres);
+            assert_eq!(f.cull_aabbs(&aabbs), visible_aabbs);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Aabb, Containment, DepthRange, Frustum, Matrix, Sphere};
+    use super::anima_engine::math::Vector;
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{Containment, DepthRange};
+    use super::anima_engine::math::{DAabb as Aabb, DFrustum as Frustum, DMatrix as Matrix};
+    use super::anima_engine::math::{DSphere as Sphere, DVector as Vector};
+
+    suite!(f64);
+}
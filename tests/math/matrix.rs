@@ -7,58 +7,313 @@
 
 extern crate anima_engine;
 
-use std::f32::consts;
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
 
-use self::anima_engine::math::{Matrix, Vector, Quaternion};
+        #[test]
+        fn test_mul() {
+            let mut a1 = [0.0; 16];
+            let mut a2 = [0.0; 16];
 
-#[test]
-fn test_mul() {
-    let mut a1 = [0.0; 16];
-    let mut a2 = [0.0; 16];
+            for i in 0..16 {
+                a1[i] = (i as $float) + 1.0;
+                a2[i] = 16.0 - (i as $float);
+            }
 
-    for i in 0..16 {
-        a1[i] = (i as f32) + 1.0;
-        a2[i] = 16.0 - (i as f32);
-    }
+            let m1 = Matrix::new(a1);
+            let m2 = Matrix::new(a2);
 
-    let m1 = Matrix::new(a1);
-    let m2 = Matrix::new(a2);
+            assert_eq!((m1 * m2).array, [
+                386.0, 444.0, 502.0, 560.0,
+                274.0, 316.0, 358.0, 400.0,
+                162.0, 188.0, 214.0, 240.0,
+                 50.0,  60.0,  70.0,  80.0
+            ]);
+        }
 
-    assert_eq!((m1 * m2).array, [
-        386.0, 444.0, 502.0, 560.0,
-        274.0, 316.0, 358.0, 400.0,
-        162.0, 188.0, 214.0, 240.0,
-         50.0,  60.0,  70.0,  80.0
-    ]);
-}
+        #[test]
+        fn test_linearity() {
+            let v = Vector::new(1.0, 1.0, 0.0);
+
+            let m = Matrix::ident()
+                    .rot_around(Quaternion::new_rot(Vector::right(), consts::PI),
+                                Vector::new(0.0, 1.0, 0.0))
+                    .rot(Quaternion::new_rot(Vector::forward(), consts::PI / 4.0))
+                    .scale(Vector::new(1.0, 1.0 / (2.0 as $float).sqrt(), 1.0))
+                    .trans(Vector::new(1.0, -1.0, 0.0));
+
+            let v = m * v;
+            let v = Vector::new(v.x.round(), v.y.round(), v.z.round());
+
+            assert_eq!(v, Vector::new(1.0, 0.0, 0.0));
+        }
+
+        #[test]
+        fn test_inv() {
+            let m = Matrix::ident().rot(Quaternion::new_rot(Vector::forward(), consts::PI / 4.0));
+
+            assert_eq!(m * m.inv(), Matrix::ident());
+        }
+
+        #[test]
+        fn test_persp() {
+            let fovy = consts::PI / 2.0;
+            let near = Vector::new(0.0, 0.0, -1.0);
+            let far = Vector::new(0.0, 0.0, -100.0);
+
+            let depths = |m: Matrix| ((m * near).z, (m * far).z);
+            let close = |(a, b): ($float, $float), (c, d): ($float, $float)| {
+                assert!((a - c).abs() < 0.0001 && (b - d).abs() < 0.0001);
+            };
+
+            close(depths(Matrix::new_persp(fovy, 1.0, 1.0, 100.0, DepthRange::OpenGl)),
+                  (-1.0, 1.0));
+            close(depths(Matrix::new_persp(fovy, 1.0, 1.0, 100.0, DepthRange::Vulkan)),
+                  (0.0, 1.0));
+            close(depths(Matrix::new_persp_rev(fovy, 1.0, 1.0, 100.0, DepthRange::OpenGl)),
+                  (1.0, -1.0));
+            close(depths(Matrix::new_persp_rev(fovy, 1.0, 1.0, 100.0, DepthRange::Vulkan)),
+                  (1.0, 0.0));
+            close(depths(Matrix::new_persp_inf(fovy, 1.0, 1.0, DepthRange::OpenGl)),
+                  (-1.0, 0.98));
+            close(depths(Matrix::new_persp_inf_rev(fovy, 1.0, 1.0, DepthRange::Vulkan)),
+                  (1.0, 0.01));
+
+            let m = Matrix::new_persp(fovy, 2.0, 1.0, 100.0, DepthRange::OpenGl);
+            let corner = m * Vector::new(2.0, 1.0, -1.0);
+
+            close((corner.x, corner.y), (1.0, 1.0));
+        }
+
+        #[test]
+        fn test_frustum() {
+            let fovy = consts::PI / 2.0;
+
+            let persp = Matrix::new_persp(fovy, 2.0, 1.0, 100.0, DepthRange::Vulkan);
+            let frustum = Matrix::new_frustum(-2.0, 2.0, -1.0, 1.0, 1.0, 100.0,
+                                              DepthRange::Vulkan);
+
+            for (a, b) in persp.array.iter().zip(frustum.array.iter()) {
+                assert!((a - b).abs() < 0.0001);
+            }
+        }
+
+        #[test]
+        fn test_ortho() {
+            let m = Matrix::new_ortho(-1.0, 3.0, -2.0, 2.0, 1.0, 5.0, DepthRange::OpenGl);
+
+            assert_eq!(m * Vector::new(-1.0, -2.0, -1.0), Vector::new(-1.0, -1.0, -1.0));
+            assert_eq!(m * Vector::new(3.0, 2.0, -5.0), Vector::new(1.0, 1.0, 1.0));
+            assert_eq!(m * Vector::new(1.0, 0.0, -3.0), Vector::new(0.0, 0.0, 0.0));
+        }
+
+        #[test]
+        fn test_look_at() {
+            let eye = Vector::new(1.0, 2.0, 3.0);
+            let m = Matrix::new_look_at(eye, Vector::new(1.0, 2.0, 0.0), Vector::up());
+
+            assert_eq!(m * eye, Vector::zero());
+            assert_eq!(m * Vector::new(2.0, 3.0, 3.0), Vector::new(1.0, 1.0, 0.0));
+
+            let m = Matrix::new_look_at(eye, Vector::new(2.0, 2.0, 3.0), Vector::up());
+            let v = m * Vector::new(3.0, 2.0, 3.0);
+
+            assert!(v.x.abs() < 0.0001 && v.y.abs() < 0.0001 && (v.z + 2.0).abs() < 0.0001);
+        }
+
+        #[test]
+        fn test_transpose() {
+            let m = Matrix::ident().rot(Quaternion::new_rot(Vector::forward(), 1.0))
+                                   .trans(Vector::one());
+
+            assert_eq!(m.transpose().transpose(), m);
+            assert_eq!(m.transpose().array[3], 1.0);
+        }
+
+        #[test]
+        fn test_det() {
+            let m = Matrix::ident().scale(Vector::new(2.0, 3.0, 4.0)).trans(Vector::one());
+
+            assert_eq!(m.det(), 24.0);
+            assert_eq!(m.transpose().det(), 24.0);
+            assert_eq!(Matrix::new([1.0; 16]).det(), 0.0);
+        }
+
+        #[test]
+        fn test_try_inv() {
+            let m = Matrix::ident().scale(Vector::new_unf(2.0));
+
+            assert_eq!(m.try_inv(), Some(m.inv()));
+            assert_eq!(Matrix::new([0.0; 16]).try_inv(), None);
+        }
+
+        #[test]
+        fn test_normal() {
+            let m = Matrix::ident().rot(Quaternion::new_rot(Vector::up(), consts::PI / 3.0))
+                                   .trans(Vector::new(5.0, 0.0, 0.0));
+
+            let normal = m.normal().unwrap() * Vector::forward();
+            let direction = m * Vector::forward() - m * Vector::zero();
+
+            assert!(normal.dist(direction) < 0.0001);
+            assert_eq!(Matrix::new([0.0; 16]).normal(), None);
+        }
+
+        #[test]
+        fn test_decompose() {
+            let translation = Vector::new(1.0, -2.0, 3.0);
+            let scale = Vector::new(0.5, 2.0, 3.0);
 
-#[test]
-fn test_linearity() {
-    let v = Vector::new(1.0, 1.0, 0.0);
+            let rotations = [
+                Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 0.5),
+                Quaternion::new_rot(Vector::right(), consts::PI * 0.9),
+                Quaternion::new_rot(Vector::up(), consts::PI * 0.9),
+                Quaternion::new_rot(Vector::forward(), consts::PI * 0.9)
+            ];
 
-    let m = Matrix::ident()
-            .rot_around(Quaternion::new_rot(Vector::right(), consts::PI), Vector::new(0.0, 1.0, 0.0))
-            .rot(Quaternion::new_rot(Vector::forward(), consts::PI / 4.0))
-            .scale(Vector::new(1.0, 1.0 / 2.0f32.sqrt(), 1.0))
-            .trans(Vector::new(1.0, -1.0, 0.0));
+            for &rotation in rotations.iter() {
+                let (t, r, s) = Matrix::compose(translation, rotation, scale).decompose();
 
-    let v = m * v;
-    let v = Vector::new(v.x.round(), v.y.round(), v.z.round());
+                assert!(t.dist(translation) < 0.0001);
+                assert!(r.dot(rotation).abs() > 0.9999);
+                assert!(s.dist(scale) < 0.0001);
+            }
 
-    assert_eq!(v, Vector::new(1.0, 0.0, 0.0));
+            let mirror = Matrix::ident().scale(Vector::new(1.0, 1.0, -1.0));
+            let (_, r, s) = mirror.decompose();
+            let recomposed = Matrix::compose(Vector::zero(), r, s);
+
+            for (a, b) in recomposed.array.iter().zip(mirror.array.iter()) {
+                assert!((a - b).abs() < 0.0001);
+            }
+        }
+
+        // Deterministic, well-conditioned matrices with entries in [-1.0, 1.0) plus 4.0 on the
+        // primary diagonal.
+        fn random_matrices(count: usize) -> Vec<Matrix> {
+            let mut state = 12345u32;
+            let mut next = || {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+
+                (state >> 8) as $float / (1 << 23) as $float - 1.0
+            };
+
+            (0..count).map(|_| {
+                let mut array = [0.0; 16];
+
+                for i in 0..16 {
+                    array[i] = next() + if i % 5 == 0 { 4.0 } else { 0.0 };
+                }
+
+                Matrix::new(array)
+            }).collect()
+        }
+
+        // Scalar product of the matrix by a 4 component vector, term by term.
+        fn reference(m: &Matrix, v: [$float; 4]) -> [$float; 4] {
+            let m = m.array;
+            let mut result = [0.0; 4];
+
+            for i in 0..4 {
+                result[i] = m[i] * v[0] + m[i + 4] * v[1] + m[i + 8] * v[2] + m[i + 12] * v[3];
+            }
+
+            result
+        }
+
+        #[test]
+        fn test_mul_reference() {
+            let matrices = random_matrices(64);
+
+            for pair in matrices.windows(2) {
+                let product = pair[0] * pair[1];
+
+                for i in 0..4 {
+                    let r = &pair[1].array[i * 4..i * 4 + 4];
+                    let column = reference(&pair[0], [r[0], r[1], r[2], r[3]]);
+
+                    assert_eq!(&product.array[i * 4..i * 4 + 4], &column);
+                }
+            }
+        }
+
+        #[test]
+        fn test_trans_points() {
+            let m = Matrix::new_persp(consts::PI / 2.0, 1.5, 1.0, 10.0, DepthRange::OpenGl) *
+                    random_matrices(1)[0];
+
+            let mut points: Vec<Vector> = random_matrices(19).iter().map(|m| {
+                Vector::new(m.array[1], m.array[2], m.array[3])
+            }).collect();
+            let expected: Vec<Vector> = points.iter().map(|&p| {
+                let r = reference(&m, [p.x, p.y, p.z, 1.0]);
+
+                Vector::new(r[0] / r[3], r[1] / r[3], r[2] / r[3])
+            }).collect();
+
+            for (&point, &expected) in points.iter().zip(expected.iter()) {
+                assert_eq!(m * point, expected);
+            }
+
+            m.trans_points(&mut points);
+
+            assert_eq!(points, expected);
+        }
+
+        #[test]
+        fn test_trans_dirs() {
+            let m = random_matrices(1)[0];
+
+            let mut dirs: Vec<Vector> = random_matrices(19).iter().map(|m| {
+                Vector::new(m.array[1], m.array[2], m.array[3])
+            }).collect();
+            let expected: Vec<Vector> = dirs.iter().map(|&d| {
+                let r = reference(&m, [d.x, d.y, d.z, 0.0]);
+
+                Vector::new(r[0], r[1], r[2])
+            }).collect();
+
+            m.trans_dirs(&mut dirs);
+
+            assert_eq!(dirs, expected);
+        }
+
+        #[test]
+        fn test_inv_reference() {
+            for m in random_matrices(64) {
+                let inv = m.inv();
+                let reference = m.cast::<f64>().inv();
+
+                for (a, b) in (m * inv).array.iter().zip(Matrix::ident().array.iter()) {
+                    assert!((a - b).abs() < 0.00001);
+                }
+
+                for (a, b) in inv.array.iter().zip(reference.array.iter()) {
+                    assert!((*a as f64 - b).abs() < 0.00001);
+                }
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_det_0_panic() {
+            let m = Matrix::new([0.0; 16]);
+
+            m.inv();
+        }
+    }
 }
 
-#[test]
-fn test_inv() {
-    let m = Matrix::ident().rot(Quaternion::new_rot(Vector::forward(), consts::PI / 4.0));
+mod single {
+    use super::anima_engine::math::{DepthRange, Matrix, Vector, Quaternion};
 
-    assert_eq!(m * m.inv(), Matrix::ident());
+    suite!(f32);
 }
 
-#[test]
-#[should_panic]
-fn test_det_0_panic() {
-    let m = Matrix::new([0.0; 16]);
+mod double {
+    use super::anima_engine::math::{DepthRange, DMatrix as Matrix, DVector as Vector};
+    use super::anima_engine::math::DQuaternion as Quaternion;
 
-    m.inv();
+    suite!(f64);
 }
@@ -6,5 +6,15 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 mod vector;
+mod vector2;
+mod vector4;
 mod quaternion;
 mod matrix;
+mod transform;
+mod ray;
+mod plane;
+mod aabb;
+mod sphere;
+mod triangle;
+mod obb;
+mod frustum;
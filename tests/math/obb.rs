@@ -0,0 +1,106 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
+
+        fn rotated() -> Obb {
+            let rotation = Quaternion::new_rot(Vector::forward(), consts::PI / 4.0);
+
+            Obb::new(Vector::zero(), rotation, Vector::new(2.0, 1.0, 1.0))
+        }
+
+        #[test]
+        fn test_local() {
+            let b = rotated();
+            let point = Vector::new(0.5, -1.0, 0.25);
+
+            assert!(b.to_world(b.to_local(point)).dist(point) < 0.0001);
+            assert_eq!(b.local_aabb(), Aabb::new(Vector::new(-2.0, -1.0, -1.0),
+                                                 Vector::new(2.0, 1.0, 1.0)));
+        }
+
+        #[test]
+        fn test_contains() {
+            let b = rotated();
+            let axes = b.axes();
+
+            assert!(b.contains(axes[0] * 1.9));
+            assert!(!b.contains(axes[1] * 1.1));
+            assert!(!b.contains(Vector::new(1.9, 0.0, 0.0)));
+            assert!(b.closest_point(axes[0] * 3.0).dist(axes[0] * 2.0) < 0.0001);
+        }
+
+        #[test]
+        fn test_hit_obb() {
+            let b1 = rotated();
+            let axis = b1.axes()[1];
+
+            let b2 = Obb::new(axis * 1.75, b1.rotation, Vector::one());
+            let hit = b1.hit_obb(b2).unwrap();
+
+            assert!((hit.distance - 0.25).abs() < 0.0001);
+            assert!(hit.normal.dist(-axis) < 0.0001);
+
+            let b3 = Obb::new(axis * 2.25, b1.rotation, Vector::one());
+
+            assert_eq!(b1.hit_obb(b3), None);
+        }
+
+        #[test]
+        fn test_hit_aabb() {
+            let b = Obb::new(Vector::zero(), Quaternion::ident(), Vector::one());
+            let a = Aabb::new(Vector::new(0.5, -1.0, -1.0), Vector::new(3.0, 1.0, 1.0));
+
+            assert_eq!(b.hit_aabb(a), Some(Hit::new(0.5, Vector::right())));
+        }
+
+        #[test]
+        fn test_hit_plane() {
+            let b = rotated();
+
+            let hit = b.hit_plane(Plane::new(Vector::up(), 2.0)).unwrap();
+
+            assert!((hit.distance - (3.0 * consts::SQRT_2 / 2.0 - 2.0)).abs() < 0.0001);
+            assert_eq!(hit.normal, Vector::down());
+            assert_eq!(b.hit_plane(Plane::new(Vector::up(), 3.0)), None);
+        }
+
+        #[test]
+        fn test_from_aabb() {
+            let a = Aabb::new(Vector::zero(), Vector::new(2.0, 4.0, 6.0));
+
+            assert_eq!(Obb::from(a), Obb::new(Vector::new(1.0, 2.0, 3.0), Quaternion::ident(),
+                                              Vector::new(1.0, 2.0, 3.0)));
+        }
+
+        #[test]
+        fn test_transform() {
+            let m = Matrix::ident().scale(Vector::new_unf(2.0)).trans(Vector::up());
+            let b = m * rotated();
+
+            assert!(b.center.dist(Vector::up()) < 0.0001);
+            assert!(b.half_extents.dist(Vector::new(4.0, 2.0, 2.0)) < 0.0001);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Aabb, Hit, Matrix, Obb, Plane, Quaternion, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DAabb as Aabb, DHit as Hit, DMatrix as Matrix, DObb as Obb};
+    use super::anima_engine::math::{DPlane as Plane, DQuaternion as Quaternion, DVector as Vector};
+
+    suite!(f64);
+}
@@ -0,0 +1,59 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        #[test]
+        fn test_new() {
+            let plane = Plane::new(Vector::new(0.0, 2.0, 0.0), 2.0);
+
+            assert_eq!(plane, Plane::new_point(Vector::up(), Vector::new(3.0, 1.0, 3.0)));
+        }
+
+        #[test]
+        fn test_new_tri() {
+            let a = Vector::new(0.0, 1.0, 0.0);
+            let b = Vector::new(0.0, 1.0, 1.0);
+            let c = Vector::new(1.0, 1.0, 0.0);
+
+            assert_eq!(Plane::new_tri(a, b, c), Plane::new(Vector::up(), 1.0));
+            assert_eq!(Plane::new_tri(a, c, b), Plane::new(Vector::down(), -1.0));
+        }
+
+        #[test]
+        fn test_dist() {
+            let plane = Plane::new(Vector::up(), 1.0);
+
+            assert_eq!(plane.dist(Vector::new(5.0, 3.0, 5.0)), 2.0);
+            assert_eq!(plane.dist(Vector::zero()), -1.0);
+            assert_eq!(plane.closest_point(Vector::new(5.0, 3.0, 5.0)), Vector::new(5.0, 1.0, 5.0));
+        }
+
+        #[test]
+        fn test_transform() {
+            let m = Matrix::ident().scale(Vector::new(1.0, 2.0, 1.0)).trans(Vector::up());
+            let plane = m * Plane::new(Vector::up(), 1.0);
+
+            assert!(plane.normal.dist(Vector::up()) < 0.0001);
+            assert!((plane.distance - 3.0).abs() < 0.0001);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Matrix, Plane, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DMatrix as Matrix, DPlane as Plane, DVector as Vector};
+
+    suite!(f64);
+}
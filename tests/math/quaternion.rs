@@ -7,12 +7,212 @@
 
 extern crate anima_engine;
 
-use self::anima_engine::math::Quaternion;
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
 
-#[test]
-fn test_mul() {
-    let q1 = Quaternion::new(0.0, 1.0, 2.0, 3.0);
-    let q2 = Quaternion::new(3.0, 2.0, 1.0, 0.0);
+        const ORDERS: [EulerOrder; 6] = [
+            EulerOrder::Xyz,
+            EulerOrder::Xzy,
+            EulerOrder::Yxz,
+            EulerOrder::Yzx,
+            EulerOrder::Zxy,
+            EulerOrder::Zyx
+        ];
 
-    assert_eq!(q1 * q2, Quaternion::new(12.0, 0.0, 6.0, -4.0));
+        #[test]
+        fn test_mul() {
+            let q1 = Quaternion::new(0.0, 1.0, 2.0, 3.0);
+            let q2 = Quaternion::new(3.0, 2.0, 1.0, 0.0);
+
+            assert_eq!(q1 * q2, Quaternion::new(12.0, 0.0, 6.0, -4.0));
+        }
+
+        #[test]
+        fn test_norm() {
+            let q = Quaternion::new(0.0, 3.0, 0.0, 4.0);
+
+            assert_eq!(q.len(), 5.0);
+            assert_eq!(q.norm(), Quaternion::new(0.0, 0.6, 0.0, 0.8));
+        }
+
+        #[test]
+        fn test_euler() {
+            let x = Quaternion::new_rot(Vector::new(1.0, 0.0, 0.0), 0.3);
+            let y = Quaternion::new_rot(Vector::new(0.0, 1.0, 0.0), -0.7);
+            let z = Quaternion::new_rot(Vector::new(0.0, 0.0, 1.0), 1.1);
+            let angles = Vector::new(0.3, -0.7, 1.1);
+
+            let expected = [x * y * z, x * z * y, y * x * z, y * z * x, z * x * y, z * y * x];
+
+            for (&order, &expected) in ORDERS.iter().zip(expected.iter()) {
+                let q = Quaternion::new_euler(angles, order);
+
+                assert!(q.dot(expected).abs() > 0.9999);
+                assert!(q.to_euler(order).dist(angles) < 0.0001);
+            }
+        }
+
+        #[test]
+        fn test_euler_gimbal_lock() {
+            let angles = Vector::new(consts::PI / 2.0, consts::PI / 2.0, consts::PI / 2.0);
+
+            for &order in ORDERS.iter() {
+                let q = Quaternion::new_euler(angles, order);
+                let round_trip = Quaternion::new_euler(q.to_euler(order), order);
+
+                assert!(q.dot(round_trip).abs() > 0.9999);
+            }
+        }
+
+        #[test]
+        fn test_axis_angle() {
+            let q = Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 2.5);
+            let (direction, angle) = q.axis_angle();
+
+            assert!(direction.dist(Vector::new(1.0, 2.0, 3.0).norm()) < 0.0001);
+            assert!((angle - 2.5).abs() < 0.0001);
+
+            assert_eq!(Quaternion::ident().axis_angle(), (Vector::new(1.0, 0.0, 0.0), 0.0));
+        }
+
+        #[test]
+        fn test_matrix() {
+            let rotations = [
+                Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 0.5),
+                Quaternion::new_rot(Vector::right(), consts::PI * 0.9),
+                Quaternion::new_rot(Vector::up(), consts::PI * 0.9),
+                Quaternion::new_rot(Vector::forward(), consts::PI * 0.9)
+            ];
+
+            for &rotation in rotations.iter() {
+                let m = Matrix::ident().scale(Vector::new(0.5, 2.0, 3.0)).rot(rotation);
+
+                assert!(Quaternion::from_matrix(m).dot(rotation).abs() > 0.9999);
+            }
+        }
+
+        #[test]
+        fn test_look_rot() {
+            let forward = Vector::new(1.0, 1.0, 0.0);
+            let m = Quaternion::new_look_rot(forward, Vector::up()).to_matrix();
+
+            let up = m * Vector::up();
+
+            assert!((m * Vector::forward()).dist(forward.norm()) < 0.0001);
+            assert!(up.dot(forward).abs() < 0.0001);
+            assert!(up.y > 0.0);
+
+            let q = Quaternion::new_look_rot(Vector::forward(), Vector::up());
+
+            assert!(q.dot(Quaternion::ident()) > 0.9999);
+        }
+
+        #[test]
+        fn test_slerp() {
+            let q1 = Quaternion::new_rot(Vector::up(), 0.2);
+            let q2 = Quaternion::new_rot(Vector::up(), 2.2);
+
+            for &ratio in [0.0, 0.25, 0.5, 0.75, 1.0].iter() {
+                let expected = Quaternion::new_rot(Vector::up(), 0.2 + 2.0 * ratio);
+
+                assert!(q1.slerp(q2, ratio).dot(expected).abs() > 0.9999);
+                assert!(q1.interpolate(q2, ratio).dot(expected).abs() > 0.9999);
+            }
+
+            assert_eq!(q1.slerp(q1, 0.5), q1);
+        }
+
+        #[test]
+        fn test_slerp_shortest_path() {
+            let q1 = Quaternion::new_rot(Vector::up(), 0.0);
+            let q2 = Quaternion::new_rot(Vector::up(), consts::PI * 1.5);
+            let expected = Quaternion::new_rot(Vector::up(), -consts::PI / 4.0);
+
+            assert!(q1.slerp(q2, 0.5).dot(expected) > 0.9999);
+
+            let opposite = Quaternion::new(-q2.x, -q2.y, -q2.z, -q2.w);
+
+            assert!(q2.slerp(opposite, 0.5).dot(q2) > 0.9999);
+        }
+
+        #[test]
+        fn test_nlerp() {
+            let q1 = Quaternion::new_rot(Vector::right(), 0.3);
+            let q2 = Quaternion::new_rot(Vector::right(), 0.9);
+            let expected = Quaternion::new_rot(Vector::right(), 0.6);
+
+            assert!(q1.nlerp(q2, 0.5).dot(expected) > 0.9999);
+            assert!((q1.nlerp(q2, 0.3).len() - 1.0).abs() < 0.0001);
+        }
+
+        #[test]
+        fn test_squad() {
+            let keys = [
+                Quaternion::new_rot(Vector::up(), 0.0),
+                Quaternion::new_rot(Vector::right(), 1.0),
+                Quaternion::new_rot(Vector::forward(), 1.5),
+                Quaternion::new_rot(Vector::up(), 0.5)
+            ];
+            let ctrl = |i: usize| {
+                let prev = keys[if i > 0 { i - 1 } else { 0 }];
+                let next = keys[if i < 3 { i + 1 } else { 3 }];
+
+                Quaternion::new_squad_ctrl(prev, keys[i], next)
+            };
+            let squad = |i: usize, ratio| keys[i].squad(ctrl(i), ctrl(i + 1), keys[i + 1], ratio);
+
+            assert!(squad(0, 0.0).dot(keys[0]) > 0.9999);
+            assert!(squad(0, 1.0).dot(keys[1]) > 0.9999);
+
+            // Angular velocities match where segments join.
+            let step = 0.001;
+            let before = squad(0, 1.0 - step).conj() * squad(0, 1.0);
+            let after = squad(1, 0.0).conj() * squad(1, step);
+
+            assert!(before.dot(after) > 0.9999);
+        }
+
+        #[test]
+        fn test_squad_path() {
+            let keys = vec![
+                Quaternion::new_rot(Vector::up(), 0.0),
+                Quaternion::new_rot(Vector::right(), 1.0),
+                Quaternion::new_rot(Vector::forward(), 1.5)
+            ];
+            let p = SquadPath::new(keys.clone());
+
+            for (i, &key) in keys.iter().enumerate() {
+                assert!(p.interpolate(i as $float / 2.0).dot(key).abs() > 0.9999);
+            }
+
+            assert!(p.interpolate(-1.0).dot(keys[0]).abs() > 0.9999);
+            assert!(p.interpolate(2.0).dot(keys[2]).abs() > 0.9999);
+
+            let single = SquadPath::new(vec![keys[1]]);
+
+            assert_eq!(single.interpolate(0.5), keys[1]);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_squad_path_empty_panic() {
+            SquadPath::new(vec![]);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{EulerOrder, Interpolate, Matrix, Quaternion, SquadPath};
+    use super::anima_engine::math::Vector;
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{EulerOrder, Interpolate, DMatrix as Matrix};
+    use super::anima_engine::math::{DQuaternion as Quaternion, DSquadPath as SquadPath};
+    use super::anima_engine::math::DVector as Vector;
+
+    suite!(f64);
 }
@@ -0,0 +1,121 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
+
+        #[test]
+        fn test_hit_plane() {
+            let ray = Ray::new(Vector::new(0.0, 2.0, 0.0), Vector::new(1.0, -1.0, 0.0));
+            let plane = Plane::new(Vector::up(), 0.0);
+
+            let hit = ray.hit_plane(plane).unwrap();
+
+            assert!(ray.point(hit.distance).dist(Vector::new(2.0, 0.0, 0.0)) < 0.0001);
+            assert_eq!(hit.normal, Vector::up());
+
+            let parallel = Ray::new(Vector::up(), Vector::forward());
+
+            assert_eq!(parallel.hit_plane(plane), None);
+        }
+
+        #[test]
+        fn test_hit_sphere() {
+            let sphere = Sphere::new(Vector::new(0.0, 0.0, 5.0), 2.0);
+
+            let hit = Ray::new(Vector::zero(), Vector::forward()).hit_sphere(sphere).unwrap();
+
+            assert_eq!(hit, Hit::new(3.0, Vector::back()));
+
+            let miss = Ray::new(Vector::new(0.0, 3.0, 0.0), Vector::forward());
+            let behind = Ray::new(Vector::new(0.0, 0.0, 8.0), Vector::forward());
+
+            assert_eq!(miss.hit_sphere(sphere), None);
+            assert_eq!(behind.hit_sphere(sphere), None);
+        }
+
+        #[test]
+        fn test_hit_aabb() {
+            let aabb = Aabb::new(Vector::new(-1.0, -1.0, 4.0), Vector::new(1.0, 1.0, 6.0));
+
+            let hit = Ray::new(Vector::zero(), Vector::forward()).hit_aabb(aabb).unwrap();
+
+            assert_eq!(hit, Hit::new(4.0, Vector::back()));
+
+            let inside = Ray::new(Vector::new(0.0, 0.0, 5.0), Vector::up()).hit_aabb(aabb);
+
+            assert_eq!(inside, Some(Hit::new(1.0, Vector::up())));
+
+            let miss = Ray::new(Vector::new(2.0, 0.0, 0.0), Vector::forward());
+            let away = Ray::new(Vector::zero(), Vector::back());
+
+            assert_eq!(miss.hit_aabb(aabb), None);
+            assert_eq!(away.hit_aabb(aabb), None);
+        }
+
+        #[test]
+        fn test_hit_obb() {
+            let rotation = Quaternion::new_rot(Vector::forward(), consts::PI / 4.0);
+            let obb = Obb::new(Vector::new(0.0, 0.0, 5.0), rotation, Vector::one());
+
+            let ray = Ray::new(Vector::new(0.0, 5.0, 5.0), Vector::down());
+            let hit = ray.hit_obb(obb).unwrap();
+
+            assert!((hit.distance - (5.0 - consts::SQRT_2)).abs() < 0.0001);
+
+            // The ray hits a corner, between two faces.
+            assert!(hit.normal.y > 0.0);
+
+            let edge = Ray::new(Vector::new(1.2, 5.0, 5.0), Vector::down());
+            let miss = Ray::new(Vector::new(1.5, 5.0, 5.0), Vector::down());
+
+            assert!(edge.hit_obb(obb).is_some());
+            assert_eq!(miss.hit_obb(obb), None);
+        }
+
+        #[test]
+        fn test_hit_triangle() {
+            let triangle = Triangle::new(Vector::zero(), Vector::up(), Vector::new(1.0, 0.0, 0.0));
+
+            let front = Ray::new(Vector::new(0.25, 0.25, 2.0), Vector::back());
+            let back = Ray::new(Vector::new(0.25, 0.25, -2.0), Vector::forward());
+
+            assert_eq!(front.hit_triangle(triangle), Some(Hit::new(2.0, Vector::forward())));
+            assert_eq!(back.hit_triangle(triangle), Some(Hit::new(2.0, Vector::back())));
+
+            let miss = Ray::new(Vector::new(0.75, 0.75, 2.0), Vector::back());
+
+            assert_eq!(miss.hit_triangle(triangle), None);
+        }
+
+        #[test]
+        fn test_transform() {
+            let m = Matrix::ident().scale(Vector::new_unf(2.0)).trans(Vector::up());
+            let ray = m * Ray::new(Vector::one(), Vector::forward());
+
+            assert_eq!(ray, Ray::new(Vector::new(2.0, 3.0, 2.0), Vector::forward()));
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Aabb, Hit, Matrix, Obb, Plane, Quaternion, Ray, Sphere};
+    use super::anima_engine::math::{Triangle, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DAabb as Aabb, DHit as Hit, DMatrix as Matrix, DObb as Obb};
+    use super::anima_engine::math::{DPlane as Plane, DQuaternion as Quaternion, DRay as Ray};
+    use super::anima_engine::math::{DSphere as Sphere, DTriangle as Triangle, DVector as Vector};
+
+    suite!(f64);
+}
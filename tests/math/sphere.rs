@@ -0,0 +1,107 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        use std::$float::consts;
+
+        #[test]
+        fn test_contains() {
+            let s = Sphere::new(Vector::up(), 1.0);
+
+            assert!(s.contains(Vector::new(0.0, 1.5, 0.5)));
+            assert!(!s.contains(Vector::new(1.0, 0.0, 1.0)));
+            assert_eq!(s.closest_point(Vector::new(0.0, 5.0, 0.0)), Vector::new(0.0, 2.0, 0.0));
+            assert_eq!(s.closest_point(Vector::up()), Vector::up());
+        }
+
+        #[test]
+        fn test_hit_sphere() {
+            let s1 = Sphere::new(Vector::zero(), 1.0);
+            let s2 = Sphere::new(Vector::new(0.0, 1.5, 0.0), 1.0);
+
+            assert_eq!(s1.hit_sphere(s2), Some(Hit::new(0.5, Vector::down())));
+            assert_eq!(s2.hit_sphere(s1), Some(Hit::new(0.5, Vector::up())));
+            assert_eq!(s1.hit_sphere(s1), Some(Hit::new(2.0, Vector::up())));
+        }
+
+        #[test]
+        fn test_hit_plane() {
+            let s = Sphere::new(Vector::zero(), 1.0);
+
+            assert_eq!(s.hit_plane(Plane::new(Vector::up(), 0.5)),
+                       Some(Hit::new(0.5, Vector::down())));
+            assert_eq!(s.hit_plane(Plane::new(Vector::up(), 1.0)), None);
+        }
+
+        #[test]
+        fn test_hit_aabb() {
+            let b = Aabb::new(Vector::zero(), Vector::new(4.0, 1.0, 4.0));
+
+            let outside = Sphere::new(Vector::new(2.0, 1.5, 2.0), 1.0);
+            let inside = Sphere::new(Vector::new(2.0, 0.75, 2.0), 0.5);
+            let corner = Sphere::new(Vector::new(-0.5, 1.5, -0.5), 0.5);
+
+            assert_eq!(outside.hit_aabb(b), Some(Hit::new(0.5, Vector::up())));
+            assert_eq!(inside.hit_aabb(b), Some(Hit::new(0.75, Vector::up())));
+            assert_eq!(corner.hit_aabb(b), None);
+        }
+
+        #[test]
+        fn test_hit_obb() {
+            let rotation = Quaternion::new_rot(Vector::forward(), consts::PI / 4.0);
+            let b = Obb::new(Vector::zero(), rotation, Vector::one());
+
+            let s = Sphere::new(Vector::new(0.0, 2.0, 0.0), 1.0);
+            let hit = s.hit_obb(b).unwrap();
+
+            assert!((hit.distance - (consts::SQRT_2 - 1.0)).abs() < 0.0001);
+            assert!(hit.normal.dist(Vector::up()) < 0.0001);
+
+            assert_eq!(Sphere::new(Vector::new(0.0, 2.5, 0.0), 1.0).hit_obb(b), None);
+        }
+
+        #[test]
+        fn test_hit_triangle() {
+            let t = Triangle::new(Vector::zero(), Vector::forward(), Vector::new(1.0, 0.0, 0.0));
+
+            let above = Sphere::new(Vector::new(0.25, 0.5, 0.25), 1.0);
+            let on = Sphere::new(Vector::new(0.25, 0.0, 0.25), 1.0);
+            let edge = Sphere::new(Vector::new(1.0, 0.0, 1.0), 1.0);
+
+            assert_eq!(above.hit_triangle(t), Some(Hit::new(0.5, Vector::up())));
+            assert_eq!(on.hit_triangle(t), Some(Hit::new(1.0, Vector::up())));
+            assert!(edge.hit_triangle(t).is_some());
+            assert_eq!(Sphere::new(Vector::new(2.0, 0.0, 2.0), 1.0).hit_triangle(t), None);
+        }
+
+        #[test]
+        fn test_transform() {
+            let m = Matrix::ident().scale(Vector::new(1.0, 3.0, 2.0)).trans(Vector::up());
+            let s = m * Sphere::new(Vector::zero(), 1.0);
+
+            assert_eq!(s, Sphere::new(Vector::up(), 3.0));
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Aabb, Hit, Matrix, Obb, Plane, Quaternion, Sphere};
+    use super::anima_engine::math::{Triangle, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DAabb as Aabb, DHit as Hit, DMatrix as Matrix, DObb as Obb};
+    use super::anima_engine::math::{DPlane as Plane, DQuaternion as Quaternion};
+    use super::anima_engine::math::{DSphere as Sphere, DTriangle as Triangle, DVector as Vector};
+
+    suite!(f64);
+}
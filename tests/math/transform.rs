@@ -0,0 +1,90 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        fn transform() -> Transform {
+            Transform::new(Vector::new(1.0, -2.0, 3.0),
+                           Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 0.5),
+                           Vector::new(0.5, 2.0, 3.0))
+        }
+
+        #[test]
+        fn test_trans_point() {
+            let t = transform();
+            let m = t.to_matrix();
+            let point = Vector::new(1.0, 2.0, 3.0);
+
+            assert!(t.trans_point(point).dist(m * point) < 0.0001);
+            assert!((t.trans_dir(point) + t.translation).dist(m * point) < 0.0001);
+        }
+
+        #[test]
+        fn test_matrix() {
+            let t = transform();
+            let result = Transform::from_matrix(t.to_matrix());
+
+            assert!(result.translation.dist(t.translation) < 0.0001);
+            assert!(result.rotation.dot(t.rotation).abs() > 0.9999);
+            assert!(result.scale.dist(t.scale) < 0.0001);
+        }
+
+        #[test]
+        fn test_mul() {
+            let t1 = Transform::new(Vector::new(3.0, 0.0, -1.0),
+                                    Quaternion::new_rot(Vector::up(), 1.0),
+                                    Vector::new_unf(2.0));
+            let t2 = transform();
+
+            let m = t1.to_matrix() * t2.to_matrix();
+            let point = Vector::new(1.0, 2.0, 3.0);
+
+            assert!((t1 * t2).trans_point(point).dist(m * point) < 0.0001);
+            assert!(t1.trans_point(t2.trans_point(point)).dist(m * point) < 0.0001);
+        }
+
+        #[test]
+        fn test_inv() {
+            let t = Transform::new(Vector::new(3.0, 0.0, -1.0),
+                                   Quaternion::new_rot(Vector::new(1.0, 2.0, 3.0), 1.0),
+                                   Vector::new_unf(2.0));
+            let point = Vector::new(1.0, 2.0, 3.0);
+
+            assert!(t.inv().trans_point(t.trans_point(point)).dist(point) < 0.0001);
+            assert!((t * t.inv()).trans_point(point).dist(point) < 0.0001);
+        }
+
+        #[test]
+        fn test_interpolate() {
+            let t1 = Transform::ident();
+            let t2 = Transform::new(Vector::new_unf(2.0),
+                                    Quaternion::new_rot(Vector::up(), 1.0),
+                                    Vector::new_unf(3.0));
+
+            let result = t1.interpolate(t2, 0.5);
+
+            assert_eq!(result.translation, Vector::new_unf(1.0));
+            assert!(result.rotation.dot(Quaternion::new_rot(Vector::up(), 0.5)) > 0.9999);
+            assert_eq!(result.scale, Vector::new_unf(2.0));
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Interpolate, Quaternion, Transform, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{Interpolate, DQuaternion as Quaternion};
+    use super::anima_engine::math::{DTransform as Transform, DVector as Vector};
+
+    suite!(f64);
+}
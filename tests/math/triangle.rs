@@ -0,0 +1,54 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    ($float:ident) => {
+        #[test]
+        fn test_normal() {
+            let t = Triangle::new(Vector::zero(), Vector::forward(), Vector::new(1.0, 0.0, 0.0));
+
+            assert_eq!(t.normal(), Vector::up());
+        }
+
+        #[test]
+        fn test_closest_point() {
+            let t = Triangle::new(Vector::zero(), Vector::new(0.0, 0.0, 2.0),
+                                  Vector::new(2.0, 0.0, 0.0));
+
+            // Face, vertices and edges.
+            assert_eq!(t.closest_point(Vector::new(0.5, 3.0, 0.5)), Vector::new(0.5, 0.0, 0.5));
+            assert_eq!(t.closest_point(Vector::new(-1.0, 1.0, -1.0)), Vector::zero());
+            assert_eq!(t.closest_point(Vector::new(3.0, 0.0, -1.0)), Vector::new(2.0, 0.0, 0.0));
+            assert_eq!(t.closest_point(Vector::new(1.0, 0.0, -1.0)), Vector::new(1.0, 0.0, 0.0));
+            assert_eq!(t.closest_point(Vector::new(-1.0, 0.0, 1.0)), Vector::new(0.0, 0.0, 1.0));
+            assert_eq!(t.closest_point(Vector::new(2.0, 0.0, 2.0)), Vector::new(1.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_transform() {
+            let t = Triangle::new(Vector::zero(), Vector::forward(), Vector::new(1.0, 0.0, 0.0));
+            let t = Matrix::ident().trans(Vector::up()) * t;
+
+            assert_eq!(t, Triangle::new(Vector::up(), Vector::new(0.0, 1.0, 1.0),
+                                        Vector::new(1.0, 1.0, 0.0)));
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Matrix, Triangle, Vector};
+
+    suite!(f32);
+}
+
+mod double {
+    use super::anima_engine::math::{DMatrix as Matrix, DTriangle as Triangle, DVector as Vector};
+
+    suite!(f64);
+}
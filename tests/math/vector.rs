@@ -7,35 +7,59 @@
 
 extern crate anima_engine;
 
-use self::anima_engine::math::Vector;
+macro_rules! suite {
+    () => {
+        #[test]
+        fn test_add() {
+            let v1 = Vector::new_unf(1.0);
+            let v2 = Vector::new_unf(2.0);
 
-#[test]
-fn test_add() {
-    let v1 = Vector::new_unf(1.0);
-    let v2 = Vector::new_unf(2.0);
+            assert_eq!(v1 + v2, Vector::new(3.0, 3.0, 3.0));
+        }
+
+        #[test]
+        fn test_mul() {
+            let v1 = Vector::new_unf(1.0);
+            let v2 = Vector::new_unf(2.0);
+
+            assert_eq!(v1 * v2, Vector::new(2.0, 2.0, 2.0));
+        }
+
+        #[test]
+        fn test_scalar_mul() {
+            let v = Vector::new_unf(1.0);
+
+            assert_eq!(v * 2.0, Vector::new(2.0, 2.0, 2.0));
+            assert_eq!(2.0 * v, Vector::new(2.0, 2.0, 2.0));
+        }
 
-    assert_eq!(v1 + v2, Vector::new(3.0, 3.0, 3.0));
+        #[test]
+        fn test_neg() {
+            let v = Vector::new_unf(1.0);
+
+            assert_eq!(-v, Vector::new(-1.0, -1.0, -1.0));
+        }
+    }
 }
 
-#[test]
-fn test_mul() {
-    let v1 = Vector::new_unf(1.0);
-    let v2 = Vector::new_unf(2.0);
+mod single {
+    use super::anima_engine::math::Vector;
 
-    assert_eq!(v1 * v2, Vector::new(2.0, 2.0, 2.0));
+    suite!();
 }
 
-#[test]
-fn test_scalar_mul() {
-    let v = Vector::new_unf(1.0);
+mod double {
+    use super::anima_engine::math::DVector as Vector;
 
-    assert_eq!(v * 2.0, Vector::new(2.0, 2.0, 2.0));
-    assert_eq!(2.0 * v, Vector::new(2.0, 2.0, 2.0));
+    suite!();
 }
 
 #[test]
-fn test_neg() {
-    let v = Vector::new_unf(1.0);
+fn test_cast() {
+    use self::anima_engine::math::{DVector, Vector};
+
+    let v = Vector::new(0.1, 0.2, 0.3);
 
-    assert_eq!(-v, Vector::new(-1.0, -1.0, -1.0));
+    assert_eq!(DVector::from(v).cast::<f32>(), v);
+    assert!(DVector::new(0.1, 0.2, 0.3) != DVector::from(v));
 }
@@ -0,0 +1,101 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    () => {
+        #[test]
+        fn test_add() {
+            let v1 = Vector2::new_unf(1.0);
+            let v2 = Vector2::new_unf(2.0);
+
+            assert_eq!(v1 + v2, Vector2::new(3.0, 3.0));
+        }
+
+        #[test]
+        fn test_sub() {
+            let v1 = Vector2::new_unf(1.0);
+            let v2 = Vector2::new(2.0, 3.0);
+
+            assert_eq!(v1 - v2, Vector2::new(-1.0, -2.0));
+        }
+
+        #[test]
+        fn test_mul() {
+            let v1 = Vector2::new_unf(2.0);
+            let v2 = Vector2::new(2.0, 3.0);
+
+            assert_eq!(v1 * v2, Vector2::new(4.0, 6.0));
+        }
+
+        #[test]
+        fn test_scalar_mul() {
+            let v = Vector2::new_unf(1.0);
+
+            assert_eq!(v * 2.0, Vector2::new(2.0, 2.0));
+            assert_eq!(2.0 * v, Vector2::new(2.0, 2.0));
+        }
+
+        #[test]
+        fn test_neg() {
+            let v = Vector2::new_unf(1.0);
+
+            assert_eq!(-v, Vector2::new(-1.0, -1.0));
+        }
+
+        #[test]
+        fn test_cross() {
+            let v1 = Vector2::new(1.0, 0.0);
+            let v2 = Vector2::new(0.0, 1.0);
+
+            assert_eq!(v1.cross(v2), 1.0);
+            assert_eq!(v2.cross(v1), -1.0);
+        }
+
+        #[test]
+        fn test_interpolate() {
+            let v1 = Vector2::new(1.0, 2.0);
+            let v2 = Vector2::new(3.0, 6.0);
+
+            assert_eq!(v1.interpolate(v2, 0.0), v1);
+            assert_eq!(v1.interpolate(v2, 0.5), Vector2::new(2.0, 4.0));
+            assert_eq!(v1.interpolate(v2, 1.0), v2);
+        }
+
+        #[test]
+        fn test_conversions() {
+            let v = Vector2::new(1.0, 2.0);
+
+            assert_eq!(v.extend(3.0), Vector::new(1.0, 2.0, 3.0));
+            assert_eq!(v.extend(3.0).xy(), v);
+            assert_eq!(v.extend(3.0).extend(4.0).xy(), v);
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Interpolate, Vector, Vector2};
+
+    suite!();
+}
+
+mod double {
+    use super::anima_engine::math::{DVector as Vector, DVector2 as Vector2, Interpolate};
+
+    suite!();
+}
+
+#[test]
+fn test_cast() {
+    use self::anima_engine::math::{DVector2, Vector2};
+
+    let v = Vector2::new(0.1, 0.2);
+
+    assert_eq!(DVector2::from(v).cast::<f32>(), v);
+    assert!(DVector2::new(0.1, 0.2) != DVector2::from(v));
+}
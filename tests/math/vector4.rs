@@ -0,0 +1,102 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+macro_rules! suite {
+    () => {
+        #[test]
+        fn test_add() {
+            let v1 = Vector4::new_unf(1.0);
+            let v2 = Vector4::new_unf(2.0);
+
+            assert_eq!(v1 + v2, Vector4::new(3.0, 3.0, 3.0, 3.0));
+        }
+
+        #[test]
+        fn test_sub() {
+            let v1 = Vector4::new_unf(1.0);
+            let v2 = Vector4::new(2.0, 3.0, 4.0, 5.0);
+
+            assert_eq!(v1 - v2, Vector4::new(-1.0, -2.0, -3.0, -4.0));
+        }
+
+        #[test]
+        fn test_mul() {
+            let v1 = Vector4::new_unf(2.0);
+            let v2 = Vector4::new(2.0, 3.0, 4.0, 5.0);
+
+            assert_eq!(v1 * v2, Vector4::new(4.0, 6.0, 8.0, 10.0));
+        }
+
+        #[test]
+        fn test_scalar_mul() {
+            let v = Vector4::new_unf(1.0);
+
+            assert_eq!(v * 2.0, Vector4::new(2.0, 2.0, 2.0, 2.0));
+            assert_eq!(2.0 * v, Vector4::new(2.0, 2.0, 2.0, 2.0));
+        }
+
+        #[test]
+        fn test_neg() {
+            let v = Vector4::new_unf(1.0);
+
+            assert_eq!(-v, Vector4::new(-1.0, -1.0, -1.0, -1.0));
+        }
+
+        #[test]
+        fn test_interpolate() {
+            let v1 = Vector4::new(1.0, 2.0, 3.0, 4.0);
+            let v2 = Vector4::new(3.0, 6.0, 9.0, 12.0);
+
+            assert_eq!(v1.interpolate(v2, 0.0), v1);
+            assert_eq!(v1.interpolate(v2, 0.5), Vector4::new(2.0, 4.0, 6.0, 8.0));
+            assert_eq!(v1.interpolate(v2, 1.0), v2);
+        }
+
+        #[test]
+        fn test_conversions() {
+            let v = Vector::new(1.0, 2.0, 3.0);
+
+            assert_eq!(v.extend(1.0).xyz(), v);
+            assert_eq!(v.extend(1.0).xy(), v.xy());
+            assert_eq!(v.extend(2.0).project(), v * 0.5);
+        }
+
+        #[test]
+        fn test_matrix_mul() {
+            let m = Matrix::ident().trans(Vector::one());
+            let point = Vector::new(1.0, 2.0, 3.0);
+
+            assert_eq!(m * point.extend(1.0), (m * point).extend(1.0));
+            assert_eq!(m * point.extend(0.0), point.extend(0.0));
+        }
+    }
+}
+
+mod single {
+    use super::anima_engine::math::{Interpolate, Matrix, Vector, Vector4};
+
+    suite!();
+}
+
+mod double {
+    use super::anima_engine::math::{DMatrix as Matrix, DVector as Vector, DVector4 as Vector4};
+    use super::anima_engine::math::Interpolate;
+
+    suite!();
+}
+
+#[test]
+fn test_cast() {
+    use self::anima_engine::math::{DVector4, Vector4};
+
+    let v = Vector4::new(0.1, 0.2, 0.3, 0.4);
+
+    assert_eq!(DVector4::from(v).cast::<f32>(), v);
+    assert!(DVector4::new(0.1, 0.2, 0.3, 0.4) != DVector4::from(v));
+}
@@ -0,0 +1,190 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use self::anima_engine::mrusty::{MrubyError, MrubyImpl, MrubyType};
+use self::anima_engine::scripting::{Bundle, BundleError, Loader, Registry, ScriptException};
+
+fn root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = env::temp_dir().join(format!("anima_bundle_{}", name));
+
+    let _ = fs::remove_dir_all(&root);
+
+    for &(path, source) in files {
+        let path = root.join(path);
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(&path).unwrap().write_all(source.as_bytes()).unwrap();
+    }
+
+    root
+}
+
+// Writes and reads `bundle` back, then removes the sources so only bytecode is left.
+fn ship(bundle: Bundle, root: &Path) -> MrubyType {
+    let mut data = vec![];
+
+    bundle.write(&mut data).unwrap();
+    fs::remove_dir_all(root).unwrap();
+
+    let bundle = Bundle::read(&mut &data[..]).unwrap();
+
+    Registry::new_engine().loader(Loader::new_bundle(bundle)).build().unwrap()
+}
+
+#[test]
+fn test_require() {
+    let root = root("require", &[
+        ("game/main.rb", "require 'player'; require 'util'"),
+        ("game/player.rb", "require_relative 'lib/util'; PLAYER = Util.name"),
+        ("game/lib/util.rb", "module Util; def self.name; 'player'; end; end")
+    ]);
+    let loader = Loader::new(&root.join("game")).path(Path::new("lib"));
+
+    let mut bundle = Bundle::new(&loader, &root.join("game/main.rb")).unwrap();
+
+    bundle.add(&root.join("game/player.rb")).unwrap();
+    bundle.add(&root.join("game/lib/util.rb")).unwrap();
+
+    assert_eq!(bundle.files(), vec![Path::new("main.rb"), Path::new("player.rb"),
+                                    Path::new("lib/util.rb")]);
+
+    let mruby = ship(bundle, &root);
+
+    assert!(mruby.run("require 'main'").unwrap().to_bool().unwrap());
+    assert_eq!(mruby.run("PLAYER").unwrap().to_str().unwrap(), "player");
+    assert_eq!(mruby.run("$LOADED_FEATURES").unwrap().to_vec().unwrap().len(), 3);
+    assert!(!mruby.run("require 'util'").unwrap().to_bool().unwrap());
+}
+
+#[test]
+fn test_outside_paths() {
+    let root = root("outside_paths", &[
+        ("game/main.rb", "require 'shared'"),
+        ("shared/shared.rb", "SHARED = true")
+    ]);
+    let loader = Loader::new(&root.join("game")).path(&root.join("shared"));
+
+    let mut bundle = Bundle::new(&loader, &root.join("game/main.rb")).unwrap();
+
+    assert_eq!(bundle.roots(), &[PathBuf::new(), PathBuf::from("<path 1>")]);
+
+    bundle.add(&root.join("shared/shared.rb")).unwrap();
+
+    let mruby = ship(bundle, &root);
+
+    assert!(mruby.run("require 'main'; SHARED").unwrap().to_bool().unwrap());
+    assert!(mruby.run("require '../shared/shared'").is_err());
+}
+
+#[test]
+fn test_outside_roots() {
+    let root = root("outside_roots", &[("game/main.rb", ""), ("secret.rb", "")]);
+    let loader = Loader::new(&root.join("game"));
+
+    let mut bundle = Bundle::new(&loader, &root.join("game/main.rb")).unwrap();
+
+    match bundle.add(&root.join("secret.rb")).unwrap_err() {
+        BundleError::Io(error) => assert!(error.ends_with("is outside the script roots")),
+        error                  => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_syntax_error() {
+    let root = root("syntax_error", &[("main.rb", "class Game\n  def update(dt\nend\n")]);
+
+    match Bundle::new(&Loader::new(&root), &root.join("main.rb")).unwrap_err() {
+        BundleError::Syntax(exception) => assert_eq!(exception.class, "SyntaxError"),
+        error                          => panic!("unexpected {:?}", error)
+    }
+}
+
+#[test]
+fn test_backtrace() {
+    let root = root("backtrace", &[("main.rb", "\ndef fail\n  undefined_method\nend\n")]);
+    let bundle = Bundle::new(&Loader::new(&root), &root.join("main.rb")).unwrap();
+
+    let mruby = ship(bundle, &root);
+
+    mruby.run("require 'main'").unwrap();
+
+    match mruby.run("fail") {
+        Err(MrubyError::Runtime(error)) => {
+            let exception = ScriptException::parse(&error);
+
+            assert_eq!(exception.class, "NoMethodError");
+            assert_eq!(exception.backtrace[0], "main.rb:3");
+        },
+        _ => panic!("fail did not raise")
+    }
+}
+
+#[test]
+fn test_version() {
+    let root = root("version", &[("main.rb", "")]);
+    let bundle = Bundle::new(&Loader::new(&root), &root.join("main.rb")).unwrap();
+
+    let mut data = vec![];
+
+    bundle.write(&mut data).unwrap();
+
+    // Swaps the version for an older one of the same length.
+    let len = data[8] as usize;
+    let old = vec![b'0'; len];
+
+    data.splice(12..12 + len, old);
+
+    assert_eq!(Bundle::read(&mut &data[..]).unwrap_err(),
+               BundleError::Version(String::from_utf8(vec![b'0'; len]).unwrap()));
+}
+
+#[test]
+fn test_format() {
+    let root = root("format", &[("main.rb", "")]);
+    let bundle = Bundle::new(&Loader::new(&root), &root.join("main.rb")).unwrap();
+
+    let mut data = vec![];
+
+    bundle.write(&mut data).unwrap();
+
+    assert!(Bundle::read(&mut &data[..]).is_ok());
+    assert_eq!(Bundle::read(&mut &data[..data.len() - 1]).unwrap_err(), BundleError::Format);
+    assert_eq!(Bundle::read(&mut &b"class Game; end"[..]).unwrap_err(), BundleError::Format);
+}
+
+#[test]
+fn test_bytecode_size() {
+    let root = root("bytecode_size", &[("main.rb", "")]);
+    let bundle = Bundle::new(&Loader::new(&root), &root.join("main.rb")).unwrap();
+
+    let mut data = vec![];
+
+    bundle.write(&mut data).unwrap();
+
+    // Claims more bytecode than the file holds.
+    let start = data.windows(4).position(|window| window == b"RITE").unwrap();
+
+    data[start + 10] = 0xff;
+
+    assert_eq!(Bundle::read(&mut &data[..]).unwrap_err(), BundleError::Format);
+}
+
+#[test]
+fn test_length() {
+    let mut data = b"ANIMABUN".to_vec();
+
+    data.extend_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+
+    assert_eq!(Bundle::read(&mut &data[..]).unwrap_err(), BundleError::Format);
+}
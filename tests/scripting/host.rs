@@ -0,0 +1,199 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use self::anima_engine::math::{Quaternion, Vector};
+use self::anima_engine::mrusty::{MrubyError, MrubyImpl};
+use self::anima_engine::scripting::{Message, Registry, ScriptException, ScriptHost};
+
+fn inspect(host: &ScriptHost, name: &str, code: &str) -> String {
+    let value = host.get_mruby(name).unwrap().run(code).unwrap();
+
+    value.call("inspect", vec![]).unwrap().to_str().unwrap().to_owned()
+}
+
+#[test]
+fn test_isolated() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("ui", "$owner = Host.script").unwrap();
+    host.spawn("ai", "$owner = Host.script").unwrap();
+
+    assert_eq!(inspect(&host, "ui", "$owner"), "\"ui\"");
+    assert_eq!(inspect(&host, "ai", "$owner"), "\"ai\"");
+}
+
+#[test]
+fn test_shared_modules() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn_module("player_ai", "world", "
+      def self.caller_name
+        Host.script
+      end
+
+      Host.on_message { |from, message| $received = [Host.script, from, message] }
+    ").unwrap();
+    host.spawn_module("physics", "world", "Host.post :player_ai, PlayerAi.caller_name").unwrap();
+
+    assert!(host.get_mruby("player_ai").unwrap().is_defined("Physics"));
+    assert_eq!(host.dispatch().unwrap(), 1);
+    assert_eq!(inspect(&host, "physics", "$received"),
+               "[\"player_ai\", \"physics\", \"physics\"]");
+}
+
+#[test]
+fn test_message_values() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("receiver", "Host.on_message { |_, message| $message = message }").unwrap();
+    host.spawn("sender", "
+      Host.on_message do |_, message|
+        $sent = message
+
+        Host.post :receiver, message
+      end
+    ").unwrap();
+
+    let message = Message::Array(vec![
+        Message::Nil,
+        Message::Bool(true),
+        Message::Int(1),
+        Message::Float(2.5),
+        Message::String("text".to_owned()),
+        Message::Symbol("symbol".to_owned()),
+        Message::Hash(vec![(Message::Symbol("hp".to_owned()),
+                            Message::Array(vec![Message::Int(1), Message::Int(2)]))]),
+        Message::Vector(Vector::new(1.0, 2.0, 3.0)),
+        Message::Quaternion(Quaternion::new(0.0, 0.0, 0.0, 1.0))
+    ]);
+
+    host.post("sender", message.clone());
+    host.dispatch().unwrap();
+    host.dispatch().unwrap();
+
+    let received = host.get_mruby("receiver").unwrap().run("$message").unwrap();
+
+    assert_eq!(Message::from_value(&received).unwrap(), message);
+    assert_eq!(inspect(&host, "receiver", "$message[7].class"), "Vector");
+
+    // Messages are copies.
+    host.get_mruby("receiver").unwrap().run("$message[4] << '!'").unwrap();
+
+    assert_eq!(inspect(&host, "sender", "$sent[4]"), "\"text\"");
+}
+
+#[test]
+fn test_order() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("log", "
+      $log = []
+
+      Host.on_message do |from, message|
+        $log << [from, message]
+
+        Host.post :echo, message if from == 'echo'
+      end
+    ").unwrap();
+    host.spawn("echo", "Host.on_message { |_, message| Host.post :log, message + 10 }")
+        .unwrap();
+    host.spawn("first", "Host.post :log, 1; Host.post :echo, 2").unwrap();
+
+    host.post("log", Message::Int(3));
+
+    assert_eq!(host.dispatch().unwrap(), 3);
+    assert_eq!(host.pending(), 1);
+    assert_eq!(host.dispatch().unwrap(), 1);
+    assert_eq!(host.dispatch().unwrap(), 1);
+    assert_eq!(inspect(&host, "log", "$log"),
+               "[[\"first\", 1], [nil, 3], [\"echo\", 12]]");
+    assert_eq!(host.pending(), 1);
+}
+
+#[test]
+fn test_post_errors() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("ui", "").unwrap();
+
+    let error = |host: &ScriptHost, code: &str| {
+        match host.get_mruby("ui").unwrap().run(code) {
+            Err(MrubyError::Runtime(error)) => ScriptException::parse(&error),
+            result                          => panic!("unexpected {:?}", result.map(|_| ()))
+        }
+    };
+
+    let missing = error(&host, "Host.post :nobody, 1");
+    let object = error(&host, "Host.post :ui, [Object.new]");
+
+    assert_eq!(missing.class, "ArgumentError");
+    assert_eq!(missing.message, "no script named nobody");
+    assert_eq!(object.class, "TypeError");
+    assert_eq!(object.message, "cannot post Object");
+    assert_eq!(host.pending(), 0);
+    assert_eq!(inspect(&host, "ui", "Host.enqueue 'ui'"),
+               "[\"ArgumentError\", \"wrong number of arguments (1 for 2)\"]");
+}
+
+#[test]
+fn test_dispatch_count() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("ui", "Host.on_message { |_, message| $message = message }").unwrap();
+    host.spawn("log", "").unwrap();
+
+    host.post("ui", Message::Int(1));
+    host.post("log", Message::Int(2));
+
+    assert_eq!(host.dispatch().unwrap(), 1);
+    assert_eq!(host.pending(), 0);
+}
+
+#[test]
+fn test_spawn_error() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    match host.spawn("broken", "\nundefined_method") {
+        Err(MrubyError::Runtime(error)) => {
+            assert_eq!(ScriptException::parse(&error).backtrace, vec!["broken.rb:2"]);
+        },
+        result                          => panic!("unexpected {:?}", result)
+    }
+
+    assert!(host.scripts().is_empty());
+}
+
+fn argument_error(result: Result<(), MrubyError>) -> String {
+    match result {
+        Err(MrubyError::Runtime(error)) => ScriptException::parse(&error).message,
+        result                          => panic!("unexpected {:?}", result)
+    }
+}
+
+#[test]
+fn test_duplicate_name() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    host.spawn("ui", "").unwrap();
+
+    assert_eq!(argument_error(host.spawn_module("ui", "world", "")),
+               "script \"ui\" already exists");
+    assert_eq!(host.scripts(), vec!["ui"]);
+}
+
+#[test]
+fn test_invalid_name() {
+    let mut host = ScriptHost::new(Registry::new_engine());
+
+    assert_eq!(argument_error(host.spawn("Ui", "")),
+               "script name \"Ui\" must be a lowercase identifier");
+    assert_eq!(argument_error(host.spawn("ui-1", "")),
+               "script name \"ui-1\" must be a lowercase identifier");
+    assert!(host.scripts().is_empty());
+}
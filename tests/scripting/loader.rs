@@ -0,0 +1,170 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use self::anima_engine::mrusty::{MrubyError, MrubyImpl, MrubyType};
+use self::anima_engine::scripting::{Loader, Registry, ScriptException};
+
+fn root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = env::temp_dir().join(format!("anima_loader_{}", name));
+
+    let _ = fs::remove_dir_all(&root);
+
+    for &(path, source) in files {
+        let path = root.join(path);
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(&path).unwrap().write_all(source.as_bytes()).unwrap();
+    }
+
+    root
+}
+
+fn mruby(loader: Loader) -> MrubyType {
+    Registry::new_engine().loader(loader).build().unwrap()
+}
+
+fn error(mruby: &MrubyType, script: &str) -> ScriptException {
+    match mruby.run(script) {
+        Err(MrubyError::Runtime(error)) => ScriptException::parse(&error),
+        result                          => panic!("unexpected {:?}", result.map(|_| ()))
+    }
+}
+
+#[test]
+fn test_resolve() {
+    let root = root("resolve", &[("game/player.rb", ""), ("game/lib/util.rb", ""),
+                                 ("secret.rb", "")]);
+    let loader = Loader::new(&root.join("game")).path(Path::new("lib"));
+
+    let player = loader.resolve("player", None).unwrap();
+    let util = loader.resolve("util.rb", None).unwrap();
+
+    assert!(player.ends_with("game/player.rb"));
+    assert!(util.ends_with("game/lib/util.rb"));
+    assert_eq!(loader.resolve("./util", Some(&player)).unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(loader.resolve("../secret", None).unwrap_err().kind(),
+               ErrorKind::PermissionDenied);
+    assert_eq!(loader.resolve(root.join("secret").to_str().unwrap(), None).unwrap_err().kind(),
+               ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn test_require_search_paths() {
+    let root = root("search_paths", &[("lib/util.rb", "UTIL = 1"), ("util.rb", "UTIL = 2"),
+                                      ("vendor/extra.rb", "EXTRA = 3")]);
+    let mruby = mruby(Loader::new(&root).path(Path::new("lib")).path(Path::new("vendor")));
+
+    assert!(mruby.run("require 'util'").unwrap().to_bool().unwrap());
+    assert!(mruby.run("require 'extra'").unwrap().to_bool().unwrap());
+    assert_eq!(mruby.run("UTIL + EXTRA").unwrap().to_i32().unwrap(), 5);
+}
+
+#[test]
+fn test_require_cache() {
+    let root = root("cache", &[("counter.rb", "$count = ($count || 0) + 1")]);
+    let mruby = mruby(Loader::new(&root));
+
+    assert!(mruby.run("require 'counter'").unwrap().to_bool().unwrap());
+    assert!(!mruby.run("require 'counter.rb'").unwrap().to_bool().unwrap());
+    assert!(!mruby.run("require_relative 'counter'").unwrap().to_bool().unwrap());
+    assert_eq!(mruby.run("$count").unwrap().to_i32().unwrap(), 1);
+    assert_eq!(mruby.run("$LOADED_FEATURES.size").unwrap().to_i32().unwrap(), 1);
+}
+
+#[test]
+fn test_require_relative() {
+    let root = root("relative", &[("enemies/boss.rb", "require_relative 'minion'\nBOSS = 1"),
+                                  ("enemies/minion.rb", "require_relative '../items'\nMINION = 2"),
+                                  ("items.rb", "ITEMS = 3")]);
+    let mruby = mruby(Loader::new(&root));
+
+    assert!(mruby.run("require 'enemies/boss'").unwrap().to_bool().unwrap());
+    assert_eq!(mruby.run("BOSS + MINION + ITEMS").unwrap().to_i32().unwrap(), 6);
+
+    let loaded = mruby.run("$LOADED_FEATURES").unwrap().to_vec().unwrap();
+    let loaded: Vec<_> = loaded.iter().map(|path| path.to_str().unwrap().to_owned()).collect();
+
+    assert!(loaded[0].ends_with("items.rb"));
+    assert!(loaded[1].ends_with("minion.rb"));
+    assert!(loaded[2].ends_with("boss.rb"));
+}
+
+#[test]
+fn test_require_virtual() {
+    let root = root("virtual", &[("math.rb", "raise 'shadowed'")]);
+    let mruby = mruby(Loader::new(&root));
+
+    assert!(mruby.run("require 'math'; Vector").is_ok());
+}
+
+#[test]
+fn test_require_missing() {
+    let root = root("missing", &[]);
+    let mruby = mruby(Loader::new(&root));
+
+    let exception = error(&mruby, "require 'nowhere'");
+
+    assert_eq!(exception.class, "LoadError");
+    assert_eq!(exception.message, "cannot load such file -- nowhere");
+
+    // Failed requires leave the state usable.
+    assert!(mruby.run("require 'math'").is_ok());
+}
+
+#[test]
+fn test_require_escape() {
+    let root = root("escape", &[("game/main.rb", ""), ("secret.rb", "SECRET = 1")]);
+    let mruby = mruby(Loader::new(&root.join("game")));
+
+    assert_eq!(error(&mruby, "require '../secret'").class, "LoadError");
+    assert_eq!(error(&mruby, "require_relative '../secret'").class, "LoadError");
+    assert!(!mruby.is_defined("SECRET"));
+}
+
+#[test]
+fn test_require_cycle() {
+    let root = root("cycle", &[("a.rb", "require 'b'"), ("b.rb", "require 'a'")]);
+    let mruby = mruby(Loader::new(&root));
+
+    let exception = error(&mruby, "require 'a'");
+
+    assert_eq!(exception.class, "LoadError");
+    assert!(exception.message.contains("circular require"));
+    assert_eq!(mruby.run("$LOADED_FEATURES.size").unwrap().to_i32().unwrap(), 0);
+}
+
+#[test]
+fn test_require_exception() {
+    let root = root("exception", &[("broken.rb", "\nundefined_method")]);
+    let mruby = mruby(Loader::new(&root));
+
+    let exception = error(&mruby, "require 'broken'");
+
+    assert_eq!(exception.class, "NoMethodError");
+    assert_eq!(exception.message, "undefined method 'undefined_method' for main");
+    assert_eq!(exception.backtrace, vec!["broken.rb:2"]);
+}
+
+#[test]
+fn test_loader_arguments() {
+    let root = root("arguments", &[]);
+    let mruby = mruby(Loader::new(&root));
+
+    let resolved = mruby.run("Anima::Loader.resolve('x')").unwrap().to_vec().unwrap();
+    let loaded = mruby.run("Anima::Loader.load").unwrap().to_vec().unwrap();
+
+    assert_eq!(resolved[0].to_str().unwrap(), "wrong number of arguments (1 for 2)");
+    assert_eq!(loaded[0].to_str().unwrap(), "ArgumentError");
+    assert_eq!(loaded[1].to_str().unwrap(), "wrong number of arguments (0 for 2)");
+}
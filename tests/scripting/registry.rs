@@ -0,0 +1,96 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::env;
+
+use self::anima_engine::math::Vector;
+use self::anima_engine::mrusty::MrubyImpl;
+use self::anima_engine::scripting::{Loader, Registry};
+
+#[test]
+fn test_engine_files() {
+    let mruby = Registry::new_engine().build().unwrap();
+
+    assert!(mruby.run("require 'math'").unwrap().to_bool().unwrap());
+    assert!(mruby.run("require 'game'").unwrap().to_bool().unwrap());
+    assert!(mruby.run("Profiler.method_defined? :scope").unwrap().to_bool().unwrap());
+    assert!(mruby.run("require 'input'").is_err());
+}
+
+#[test]
+fn test_only() {
+    let mruby = Registry::new_engine().only(&["game"]).build().unwrap();
+
+    assert!(mruby.run("require 'game'").is_ok());
+    assert!(mruby.run("require 'math'").is_err());
+}
+
+#[test]
+fn test_without() {
+    let mruby = Registry::new_engine().without("math").build().unwrap();
+
+    assert!(mruby.run("TimeControl").is_err());
+    assert!(mruby.run("require 'game'; TimeControl").is_ok());
+    assert!(mruby.run("require 'math'").is_err());
+}
+
+#[test]
+fn test_file_code_runs_once() {
+    let registry = Registry::new().def_file_code("counter", "$count = ($count || 0) + 1");
+    let mruby = registry.build().unwrap();
+
+    assert!(mruby.run("require 'counter'").unwrap().to_bool().unwrap());
+    assert!(!mruby.run("require 'counter'").unwrap().to_bool().unwrap());
+    assert_eq!(mruby.run("$count").unwrap().to_i32().unwrap(), 1);
+}
+
+#[test]
+fn test_file_code_after_types() {
+    let registry = Registry::new().def_file_code("vector", "class Vector; def one?; true; end; end")
+                                  .def_file::<Vector>("vector");
+    let mruby = registry.build().unwrap();
+
+    assert!(mruby.run("Vector").is_err());
+    assert!(mruby.run("require 'vector'; Vector.one.one?").unwrap().to_bool().unwrap());
+}
+
+#[test]
+fn test_file_names_are_not_code() {
+    let registry = Registry::new().def_file_code("#{$injected = 1}", "$loaded = true")
+                                  .loader(Loader::new(&env::temp_dir()));
+    let mruby = registry.build().unwrap();
+
+    assert!(mruby.run("$injected").unwrap().to_i32().is_err());
+    assert!(mruby.run("require '#{$injected = 1}'; $loaded").unwrap().to_bool().unwrap());
+}
+
+#[test]
+fn test_preludes() {
+    let registry = Registry::new_engine().prelude("require 'math'")
+                                         .prelude("$vector = Vector.one");
+    let mruby = registry.build().unwrap();
+
+    assert!(mruby.run("$vector.is_a? Vector").unwrap().to_bool().unwrap());
+}
+
+#[test]
+fn test_prelude_error() {
+    assert!(Registry::new().prelude("raise 'boom'").build().is_err());
+}
+
+#[test]
+fn test_build_twice() {
+    let registry = Registry::new().def_file_code("answer", "ANSWER = 42");
+
+    for _ in 0..2 {
+        let mruby = registry.build().unwrap();
+
+        assert_eq!(mruby.run("require 'answer'; ANSWER").unwrap().to_i32().unwrap(), 42);
+    }
+}
@@ -0,0 +1,158 @@
+// Anima Engine. The quirky game engine
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+extern crate anima_engine;
+
+use std::time::{Duration, Instant};
+
+use self::anima_engine::mrusty::{Mruby, MrubyError, MrubyImpl, Value};
+use self::anima_engine::scripting::{ScriptException, Watchdog};
+
+fn exception(result: Result<Value, MrubyError>) -> ScriptException {
+    match result {
+        Err(MrubyError::Runtime(error)) => ScriptException::parse(&error),
+        _                               => panic!("script did not raise")
+    }
+}
+
+#[test]
+fn test_call_timeout() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    mruby.filename("game.rb");
+
+    let game = mruby.run("
+      class Game
+        def update(dt)
+          frames = [dt].dup while true
+        end
+      end
+
+      Game.new
+    ").unwrap();
+
+    let start = Instant::now();
+    let exception = exception(watchdog.call(&game, "update", vec![mruby.float(0.5)],
+                                            Duration::new(0, 10_000_000)));
+
+    assert!(start.elapsed() < Duration::new(1, 0));
+    assert_eq!(exception.class, "ScriptTimeout");
+    assert_eq!(exception.message, "update exceeded its budget of 10ms");
+    assert_eq!(exception.backtrace, vec!["game.rb:4:in Game.update"]);
+}
+
+#[test]
+fn test_call_timeout_without_allocating() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    mruby.filename("game.rb");
+
+    let game = mruby.run("
+      class Game
+        def update
+          while true; end
+        end
+
+        def count(n)
+          i = 0
+          i += 1 while i < n
+          i
+        end
+      end
+
+      Game.new
+    ").unwrap();
+
+    let start = Instant::now();
+    let exception = exception(watchdog.call(&game, "update", vec![], Duration::new(0, 10_000_000)));
+
+    assert!(start.elapsed() < Duration::new(1, 0));
+    assert_eq!(exception.class, "ScriptTimeout");
+    assert_eq!(exception.backtrace, vec!["game.rb:4:in Game.update"]);
+
+    let forever = mruby.fixnum(1 << 30);
+
+    assert!(watchdog.call(&game, "count", vec![forever], Duration::new(0, 10_000_000)).is_err());
+
+    // Loops are restored once the call is over.
+    let count = game.call("count", vec![mruby.fixnum(1_000)]).unwrap();
+
+    assert_eq!(count.to_i32().unwrap(), 1_000);
+}
+
+#[test]
+fn test_block_timeout_without_allocating() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    let start = Instant::now();
+    let exception = exception(watchdog.run("loop { }", Duration::new(0, 10_000_000)));
+
+    assert!(start.elapsed() < Duration::new(1, 0));
+    assert_eq!(exception.class, "ScriptTimeout");
+}
+
+#[test]
+fn test_call_within_budget() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    let string = mruby.string("anima");
+    let result = watchdog.call(&string, "*", vec![mruby.fixnum(2)], Duration::new(1, 0)).unwrap();
+
+    assert_eq!(result.to_str().unwrap(), "animaanima");
+}
+
+#[test]
+fn test_rescue() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    let result = watchdog.run("
+      rescued = begin
+        loop { [1] }
+      rescue
+        :standard_error
+      rescue ScriptTimeout
+        :timeout
+      end
+
+      rescued
+    ", Duration::new(0, 1_000_000)).unwrap();
+
+    assert_eq!(result.to_str().unwrap(), "timeout");
+}
+
+#[test]
+fn test_budget_per_call() {
+    let mruby = Mruby::new();
+    let watchdog = Watchdog::new(&mruby);
+
+    let outer = watchdog.run("1 + 1", Duration::new(0, 1_000_000)).unwrap();
+    let inner = exception(watchdog.run("loop { [1] }", Duration::new(0, 1_000_000)));
+
+    assert_eq!(outer.to_i32().unwrap(), 2);
+    assert_eq!(inner.class, "ScriptTimeout");
+
+    // No budget applies outside of the watchdog's calls.
+    assert!(mruby.run("100_000.times.map { |i| [i] }.size").is_ok());
+}
+
+#[test]
+fn test_drop() {
+    let mruby = Mruby::new();
+
+    {
+        let watchdog = Watchdog::new(&mruby);
+
+        assert!(watchdog.run("[1] * 10", Duration::new(1, 0)).is_ok());
+    }
+
+    assert_eq!(mruby.run("([1] * 10).size").unwrap().to_i32().unwrap(), 10);
+}